@@ -0,0 +1,570 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use statrs::function::erf::erfc;
+use statrs::function::gamma::ln_gamma;
+
+use crate::mechanisms::discrete_laplace_pmf;
+
+const INTEGRATION_STEPS: usize = 4000;
+const EPSILON_STEPS: usize = 200;
+const BISECTION_STEPS: usize = 100;
+/// Epsilon beyond which `distributional_epsilon` gives up and reports no bound.
+const MAX_DISTRIBUTIONAL_EPSILON: f64 = 64.0;
+
+/// Density of the noise added to a single bucket, used for the numerical privacy analyses.
+/// The discrete mechanisms are approximated by their continuous counterparts.
+#[derive(Clone, Copy)]
+pub enum NoiseDensity {
+    Laplace { scale: f64 },
+    Gaussian { sigma: f64 },
+    Mixture { laplace_weight: f64, laplace_scale: f64, sigma: f64 },
+}
+
+impl NoiseDensity {
+    pub fn pdf(&self, x: f64) -> f64 {
+        match *self {
+            NoiseDensity::Laplace { scale } => (-x.abs() / scale).exp() / (2.0 * scale),
+            NoiseDensity::Gaussian { sigma } => (-x * x / (2.0 * sigma * sigma)).exp() / (sigma * (2.0 * PI).sqrt()),
+            NoiseDensity::Mixture { laplace_weight, laplace_scale, sigma } => {
+                laplace_weight * NoiseDensity::Laplace { scale: laplace_scale }.pdf(x)
+                    + (1.0 - laplace_weight) * NoiseDensity::Gaussian { sigma }.pdf(x)
+            }
+        }
+    }
+
+    /// Interval outside of which the density is negligible.
+    fn support(&self, shift: f64) -> (f64, f64) {
+        let width = match *self {
+            NoiseDensity::Laplace { scale } => 40.0 * scale,
+            NoiseDensity::Gaussian { sigma } => 12.0 * sigma,
+            NoiseDensity::Mixture { laplace_scale, sigma, .. } => (40.0 * laplace_scale).max(12.0 * sigma),
+        };
+        (-width, shift + width)
+    }
+}
+
+/// Standard normal CDF.
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+/// Exact privacy profile of the Gaussian mechanism (Balle & Wang, ICML 2018): the smallest
+/// delta for which noise of standard deviation `sigma` on a query of L2 `sensitivity` is
+/// (epsilon, delta)-DP,
+/// `Φ(Δ/(2σ) - εσ/Δ) - e^ε Φ(-Δ/(2σ) - εσ/Δ)`.
+pub fn gaussian_delta_for_epsilon(sigma: f64, sensitivity: f64, epsilon: f64) -> f64 {
+    if sigma == 0.0 {
+        return 1.0;
+    }
+    let a = sensitivity / (2.0 * sigma);
+    let b = epsilon * sigma / sensitivity;
+    (normal_cdf(a - b) - epsilon.exp() * normal_cdf(-a - b)).max(0.0)
+}
+
+/// `gaussian_delta_for_epsilon` at every one of `epsilon_values`: the (epsilon, delta)
+/// curve of the Gaussian mechanism, every point of which it satisfies.
+pub fn gaussian_approx_dp_curve(sigma: f64, sensitivity: f64, epsilon_values: &[f64]) -> Vec<f64> {
+    epsilon_values.iter().map(|&epsilon| gaussian_delta_for_epsilon(sigma, sensitivity, epsilon)).collect()
+}
+
+/// L2 norm of a change of `sensitivity_per_component` in each of `n_components`:
+/// `sensitivity_per_component * sqrt(n_components)`, against `n_components` times it in L1.
+/// `n_components` counts the components one record can change, not all of them.
+pub fn l2_sensitivity(sensitivity_per_component: f64, n_components: usize) -> f64 {
+    sensitivity_per_component * (n_components as f64).sqrt()
+}
+
+/// Analytic Gaussian mechanism (Balle & Wang, ICML 2018): the smallest sigma whose exact
+/// privacy profile `gaussian_delta_for_epsilon` stays within `delta` at `epsilon`, found by
+/// bisection since the profile decreases in sigma. Unlike the classic
+/// `sqrt(2 ln(1.25/δ)) Δ/ε` it is tight and also valid for epsilon above 1.
+pub fn analytic_gaussian_scale(epsilon: f64, delta: f64, sensitivity: f64) -> f64 {
+    if epsilon.is_infinite() {
+        return 0.0;
+    }
+    let profile = |sigma: f64| gaussian_delta_for_epsilon(sigma, sensitivity, epsilon);
+    let mut upper = sensitivity;
+    while profile(upper) > delta {
+        upper *= 2.0;
+    }
+    let mut lower = 0.0;
+    for _ in 0..BISECTION_STEPS {
+        let mid = (lower + upper) / 2.0;
+        if profile(mid) > delta {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+    upper
+}
+
+/// Smallest total noise variance of a Gaussian mechanism on a `d`-bucket histogram that is
+/// (`epsilon`, `delta`)-DP, summed over the buckets. A record moves a single bucket, so the
+/// L2 sensitivity and with it the optimal per-bucket sigma do not depend on `d`; the
+/// optimum is the analytic calibration, and `d` only multiplies the total.
+pub fn optimal_gaussian_variance(epsilon: f64, delta: f64, sensitivity: f64, d: usize) -> f64 {
+    d as f64 * analytic_gaussian_scale(epsilon, delta, sensitivity).powi(2)
+}
+
+/// The smallest epsilon for which a mu-GDP mechanism (Dong, Roth, Su 2022) is
+/// (epsilon, `delta`)-DP. mu-GDP holds exactly for Gaussian noise of standard deviation 1
+/// on a query of sensitivity mu, so its privacy profile is the Balle-Wang one, which is
+/// decreasing in epsilon and can be inverted by bisection.
+pub fn gdp_to_epsilon_delta(mu: f64, delta: f64) -> f64 {
+    if mu.is_infinite() {
+        return f64::INFINITY;
+    }
+    let profile = |epsilon: f64| gaussian_delta_for_epsilon(1.0, mu, epsilon);
+    if profile(0.0) <= delta {
+        return 0.0;
+    }
+    let mut upper = 1.0;
+    while profile(upper) > delta {
+        upper *= 2.0;
+    }
+    let mut lower = 0.0;
+    for _ in 0..BISECTION_STEPS {
+        let mid = (lower + upper) / 2.0;
+        if profile(mid) > delta {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+    upper
+}
+
+/// Rényi DP of the Laplace mechanism with noise `scale` and L1 `sensitivity` at `order` > 1
+/// (Mironov, "Rényi Differential Privacy", CSF 2017, Proposition 6), with `λ = scale / sensitivity`:
+/// `1/(α-1) ln(α/(2α-1) e^((α-1)/λ) + (α-1)/(2α-1) e^(-α/λ))`, evaluated in log space so
+/// that large orders do not overflow. It tends to the pure epsilon `1/λ` as `α → ∞`.
+pub fn laplace_rdp(scale: f64, sensitivity: f64, order: f64) -> f64 {
+    if scale == 0.0 {
+        return f64::INFINITY;
+    }
+    let lambda = scale / sensitivity;
+    let a = (order / (2.0 * order - 1.0)).ln() + (order - 1.0) / lambda;
+    let b = ((order - 1.0) / (2.0 * order - 1.0)).ln() - order / lambda;
+    let max = a.max(b);
+    (max + ((a - max).exp() + (b - max).exp()).ln()) / (order - 1.0)
+}
+
+/// Rényi DP of the Gaussian mechanism, `α Δ² / (2σ²)` (Mironov 2017, Proposition 7).
+pub fn gaussian_rdp(sigma: f64, sensitivity: f64, order: f64) -> f64 {
+    if sigma == 0.0 {
+        return f64::INFINITY;
+    }
+    order * sensitivity * sensitivity / (2.0 * sigma * sigma)
+}
+
+/// An (α, ε)-RDP mechanism is (ε + ln(1/δ)/(α-1), δ)-DP (Mironov 2017, Proposition 3).
+pub fn rdp_to_approx_dp(rdp_epsilon: f64, order: f64, delta: f64) -> f64 {
+    rdp_epsilon + (1.0 / delta).ln() / (order - 1.0)
+}
+
+/// Max-information `I_∞(X; M(X))` in bits between `n` records drawn from any distribution
+/// and the output of an `epsilon`-DP mechanism (Dwork, Feldman, Hardt, Pitassi, Reingold,
+/// Roth, NeurIPS 2015): at most `n ε log2 e`. Any two data sets are at most `n` records
+/// apart, so epsilon bounds the information about one record, not about all of them.
+pub fn max_information(epsilon: f64, n: usize) -> f64 {
+    n as f64 * epsilon * std::f64::consts::LOG2_E
+}
+
+/// `beta`-approximate max-information in bits of an `epsilon`-DP mechanism on `n` records
+/// drawn independently (same paper, Theorem 3.5): at most
+/// `log2 e (ε² n / 2 + ε sqrt(n ln(2/β) / 2))`, far below `max_information` once `ε` is
+/// small next to `1 / sqrt(n)`.
+pub fn approximate_max_information(epsilon: f64, n: usize, beta: f64) -> f64 {
+    let n = n as f64;
+    std::f64::consts::LOG2_E * (epsilon * epsilon * n / 2.0 + epsilon * (n * (2.0 / beta).ln() / 2.0).sqrt())
+}
+
+/// Privacy amplification by iteration (Feldman, Mironov, Talwar, Thakurta, FOCS 2018): in
+/// `n_iterations` noisy steps of a contractive update, such as projected noisy SGD on a
+/// smooth convex loss, where only the last iterate is released, the record used at `step`
+/// (from 1) is protected by the noise of every later step as well. Its Rényi divergence at
+/// a fixed order shrinks from the `epsilon` of one step to `epsilon / (n_iterations - step + 1)`.
+/// The records used first gain the most and the last one gains nothing; without
+/// contraction, or with every iterate released, there is no amplification at all.
+pub fn amplification_by_iteration(epsilon: f64, n_iterations: usize, step: usize) -> f64 {
+    epsilon / (n_iterations.saturating_sub(step) + 1) as f64
+}
+
+/// Central epsilon at `delta` of `n_users` reports of a `local_epsilon`-LDP randomizer,
+/// such as randomized response, once a shuffler has dropped who sent which (Feldman,
+/// McMillan, Talwar, FOCS 2021, Theorem 3.1):
+/// `ln(1 + (e^ε₀ - 1)/(e^ε₀ + 1) (8 sqrt(e^ε₀ ln(4/δ) / n) + 8 e^ε₀ / n))`. The bound needs
+/// `ε₀ ≤ ln(n / (16 ln(2/δ)))`; beyond that only the local epsilon is claimed. It holds
+/// for any pure LDP randomizer, not for Gaussian noise.
+pub fn shuffler_amplification(local_epsilon: f64, n_users: usize, delta: f64) -> f64 {
+    let n = n_users as f64;
+    if n_users == 0 || local_epsilon > (n / (16.0 * (2.0 / delta).ln())).ln() {
+        return local_epsilon;
+    }
+    let e0 = local_epsilon.exp();
+    let amplified = (1.0 + (e0 - 1.0) / (e0 + 1.0) * (8.0 * (e0 * (4.0 / delta).ln() / n).sqrt() + 8.0 * e0 / n)).ln();
+    amplified.min(local_epsilon)
+}
+
+/// Moments accountant (Abadi et al., "Deep Learning with Differential Privacy", CCS 2016)
+/// for `n_steps` releases with Gaussian noise `sigma` and no subsampling. The log moment
+/// `ln E[e^(λZ)]` of the privacy loss `Z` is `λ(λ+1)Δ²/(2σ²)` per release and adds up over
+/// the releases, and Markov's inequality on `e^(λZ)` gives
+/// `ε = (T λ(λ+1)Δ²/(2σ²) + ln(1/δ)) / λ`. Without subsampling this is exactly the RDP
+/// conversion at order `λ + 1`; the accountant only pulls ahead once batches are sampled.
+pub fn gaussian_moments_accountant(sigma: f64, sensitivity: f64, n_steps: usize, lambda: u32, delta: f64) -> f64 {
+    if sigma == 0.0 {
+        return f64::INFINITY;
+    }
+    let lambda = lambda as f64;
+    let log_moment = n_steps as f64 * lambda * (lambda + 1.0) * sensitivity * sensitivity / (2.0 * sigma * sigma);
+    (log_moment + (1.0 / delta).ln()) / lambda
+}
+
+/// Exact privacy profile of the binomial mechanism, noise `Binomial(n, 1/2) - n/2`, on an
+/// integer query of `sensitivity`: `Σ_k max(0, P(k) - e^ε P(k - Δ))`. The noise is bounded,
+/// so the outputs below `Δ` can only come from one of the neighbours and delta never drops
+/// below `P(X < Δ) = Σ_{k<Δ} C(n, k) / 2^n`. The distribution is symmetric, so the other
+/// direction gives the same delta.
+pub fn binomial_delta_for_epsilon(n: usize, sensitivity: u64, epsilon: f64) -> f64 {
+    binomial_hockey_stick(&binomial_pmf(n), sensitivity as usize, epsilon)
+}
+
+/// `P(k)` of `Binomial(n, 1/2)` for `k = 0..=n`, through logs so that large `n` do not overflow.
+fn binomial_pmf(n: usize) -> Vec<f64> {
+    (0..=n)
+        .map(|k| (ln_gamma(n as f64 + 1.0) - ln_gamma(k as f64 + 1.0) - ln_gamma((n - k) as f64 + 1.0)
+            - n as f64 * 2f64.ln()).exp())
+        .collect()
+}
+
+fn binomial_hockey_stick(pmf: &[f64], shift: usize, epsilon: f64) -> f64 {
+    pmf.iter().enumerate()
+        .map(|(k, p)| (p - if k >= shift { epsilon.exp() * pmf[k - shift] } else { 0.0 }).max(0.0))
+        .sum::<f64>()
+        .min(1.0)
+}
+
+/// Smallest epsilon for which the binomial mechanism with `n` trials is (epsilon, `delta`)-DP,
+/// by bisection on `binomial_delta_for_epsilon`. The mechanism is never pure DP: the
+/// epsilon is infinite when `delta` is below the mass that only one neighbour can produce.
+pub fn binomial_scale_to_epsilon(n: usize, sensitivity: u64, delta: f64) -> f64 {
+    let pmf = binomial_pmf(n);
+    let profile = |epsilon: f64| binomial_hockey_stick(&pmf, sensitivity as usize, epsilon);
+    if profile(0.0) <= delta {
+        return 0.0;
+    }
+    // the largest finite privacy loss, ln(P(Δ) / P(0)) = ln(C(n, Δ)), is as far as it goes
+    let max_loss = (1..=sensitivity as usize).map(|i| ((n + 1 - i) as f64 / i as f64).ln()).sum::<f64>();
+    if sensitivity as usize > n || profile(max_loss) > delta {
+        return f64::INFINITY;
+    }
+    let (mut lower, mut upper) = (0.0, max_loss);
+    for _ in 0..BISECTION_STEPS {
+        let mid = (lower + upper) / 2.0;
+        if profile(mid) > delta {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+    upper
+}
+
+/// Epsilon of distributional DP (Bassily, Groce, Katz, Smith, FOCS 2013) for one count with
+/// discrete Laplace noise of `scale` when each of the `others` records falls into the bucket
+/// with probability `p`, independently and unseen by the adversary. Without the target the
+/// count is `Binomial(others, p)` plus the noise, with it one more, and the epsilon is the
+/// smallest for which the hockey-stick divergence both ways stays within `delta`, by
+/// bisection. The uncertainty about the others is extra noise only as long as the adversary
+/// does not know them. Far from the bulk of the binomial the heavier Laplace tail sets the
+/// ratio, which tends to `e^(1 / scale)`, so the gain shrinks with `delta` and is nothing at
+/// `delta = 0`.
+pub fn distributional_epsilon(others: usize, p: f64, scale: f64, delta: f64) -> f64 {
+    // probabilities smaller than this are left out of the convolution
+    const NEGLIGIBLE: f64 = 1e-18;
+    let binomial = (0..=others)
+        .map(|k| match p {
+            p if p <= 0.0 => if k == 0 { 1.0 } else { 0.0 },
+            p if p >= 1.0 => if k == others { 1.0 } else { 0.0 },
+            p => (ln_gamma(others as f64 + 1.0) - ln_gamma(k as f64 + 1.0) - ln_gamma((others - k) as f64 + 1.0)
+                + k as f64 * p.ln() + (others - k) as f64 * (1.0 - p).ln()).exp(),
+        })
+        .collect::<Vec<_>>();
+    let width = if scale > 0.0 { (40.0 * scale).ceil() as usize } else { 0 };
+    let noise = (-(width as i64)..=width as i64)
+        .map(|x| if scale > 0.0 { discrete_laplace_pmf(x, scale) } else if x == 0 { 1.0 } else { 0.0 })
+        .collect::<Vec<_>>();
+    // the count without the target at positions 0..others + 2 width, one further along with it
+    let mut absent = vec![0.0; others + 2 * width + 2];
+    for (k, &b) in binomial.iter().enumerate().filter(|(_, &b)| b > NEGLIGIBLE) {
+        for (j, &q) in noise.iter().enumerate() {
+            absent[k + j] += b * q;
+        }
+    }
+    let present = std::iter::once(0.0).chain(absent.iter().copied()).take(absent.len()).collect::<Vec<_>>();
+    let hockey_stick = |p: &[f64], q: &[f64], epsilon: f64| {
+        p.iter().zip(q).map(|(p, q)| (p - epsilon.exp() * q).max(0.0)).sum::<f64>()
+    };
+    let profile = |epsilon: f64| hockey_stick(&present, &absent, epsilon).max(hockey_stick(&absent, &present, epsilon));
+    if profile(0.0) <= delta {
+        return 0.0;
+    }
+    let mut upper = if scale > 0.0 { 1.0 / scale } else { 1.0 };
+    while profile(upper) > delta {
+        if upper > MAX_DISTRIBUTIONAL_EPSILON {
+            return f64::INFINITY;
+        }
+        upper *= 2.0;
+    }
+    let mut lower = 0.0;
+    for _ in 0..BISECTION_STEPS {
+        let mid = (lower + upper) / 2.0;
+        if profile(mid) > delta {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+    upper
+}
+
+/// Hockey-stick divergence `H_{e^eps}(P || Q) = ∫ max(0, p(x) - e^eps q(x)) dx` between the
+/// output distribution on a data set (`P`, noise around 0) and on a neighbour (`Q`, noise
+/// around `sensitivity`). This is the smallest delta for which the mechanism is (eps, delta)-DP.
+pub fn hockey_stick_divergence(density: NoiseDensity, sensitivity: f64, epsilon: f64) -> f64 {
+    let (lower, upper) = density.support(sensitivity);
+    let step = (upper - lower) / INTEGRATION_STEPS as f64;
+    let integrand = |x: f64| (density.pdf(x) - epsilon.exp() * density.pdf(x - sensitivity)).max(0.0);
+    // trapezoidal rule
+    let inner = (1..INTEGRATION_STEPS).map(|i| integrand(lower + i as f64 * step)).sum::<f64>();
+    (step * (inner + (integrand(lower) + integrand(upper)) / 2.0)).clamp(0.0, 1.0)
+}
+
+/// Trade-off function of f-DP (Dong, Roth, Su, "Gaussian Differential Privacy", JRSS-B 2022):
+/// for `n_points` type I error rates `alpha` in [0, 1], the smallest type II error any test
+/// distinguishing the two neighbouring outputs can achieve. Built from the (eps, delta(eps))
+/// curve: `f(alpha) = sup_eps max(0, 1 - delta - e^eps alpha, e^-eps (1 - delta - alpha))`.
+pub fn tradeoff_curve(density: NoiseDensity, sensitivity: f64, n_points: usize) -> Vec<(f64, f64)> {
+    let max_epsilon = match density {
+        NoiseDensity::Laplace { scale } => sensitivity / scale,
+        NoiseDensity::Gaussian { sigma } => {
+            let mu = sensitivity / sigma;
+            mu * mu / 2.0 + 6.0 * mu
+        }
+        NoiseDensity::Mixture { laplace_scale, sigma, .. } => {
+            let mu = sensitivity / sigma;
+            (sensitivity / laplace_scale).max(mu * mu / 2.0 + 6.0 * mu)
+        }
+    };
+    let privacy_profile = (0..=EPSILON_STEPS)
+        .map(|i| {
+            let epsilon = max_epsilon * i as f64 / EPSILON_STEPS as f64;
+            (epsilon, hockey_stick_divergence(density, sensitivity, epsilon))
+        })
+        .collect::<Vec<_>>();
+
+    (0..n_points)
+        .map(|i| {
+            let alpha = i as f64 / (n_points.max(2) - 1) as f64;
+            let beta = privacy_profile.iter()
+                .map(|&(epsilon, delta)| {
+                    (1.0 - delta - epsilon.exp() * alpha)
+                        .max((-epsilon).exp() * (1.0 - delta - alpha))
+                        .max(0.0)
+                })
+                .fold(0.0, f64::max);
+            (alpha, beta)
+        })
+        .collect()
+}
+
+/// Splits `total_epsilon` over the buckets in proportion to `1 / count`, so that small
+/// buckets get less relative noise; empty buckets count as 1. The shares add up to
+/// `total_epsilon`, the bound basic composition gives. Since one record only ever touches
+/// one bucket, parallel composition would bound the release by the largest share instead.
+pub fn adaptive_epsilon_allocation(counts: &[u64], total_epsilon: f64) -> Vec<f64> {
+    let weights = counts.iter().map(|&count| 1.0 / count.max(1) as f64).collect::<Vec<_>>();
+    let total_weight = weights.iter().sum::<f64>();
+    weights.iter().map(|weight| total_epsilon * weight / total_weight).collect()
+}
+
+/// Distribution of the privacy loss random variable `Z = ln(p(o) / q(o))` for an output `o`
+/// of the mechanism on a data set (`p`) against a neighbour (`q`). A mechanism is
+/// (eps, delta)-DP whenever `P(Z > eps) <= delta`, so `cdf(eps)` bounds `1 - delta` from below.
+#[derive(Clone, Copy)]
+pub enum PrivacyLoss {
+    /// Laplace noise at `epsilon = sensitivity / scale`. `Z` is clipped to `[-eps, eps]`: it is
+    /// `eps` for every output on the far side of the true value (probability 1/2) and `-eps`
+    /// past the neighbour's (probability `e^-eps / 2`), with density `e^((z - eps) / 2) / 4`
+    /// in between. Not a double exponential, and `P(Z > eps) = 0`, so delta is 0.
+    Laplace { epsilon: f64 },
+    /// Gaussian noise at `mu = sensitivity / sigma`: `Z ~ N(mu^2 / 2, mu^2)`.
+    Gaussian { mu: f64 },
+}
+
+impl PrivacyLoss {
+    /// Density of the continuous part of `Z`; the point masses of the Laplace case are left out.
+    pub fn pdf(&self, z: f64) -> f64 {
+        match *self {
+            PrivacyLoss::Laplace { epsilon } if z.abs() < epsilon => ((z - epsilon) / 2.0).exp() / 4.0,
+            PrivacyLoss::Laplace { .. } => 0.0,
+            PrivacyLoss::Gaussian { mu } => NoiseDensity::Gaussian { sigma: mu }.pdf(z - mu * mu / 2.0),
+        }
+    }
+
+    pub fn cdf(&self, z: f64) -> f64 {
+        match *self {
+            PrivacyLoss::Laplace { epsilon } if z < -epsilon => 0.0,
+            PrivacyLoss::Laplace { epsilon } if z < epsilon => ((z - epsilon) / 2.0).exp() / 2.0,
+            PrivacyLoss::Laplace { .. } => 1.0,
+            PrivacyLoss::Gaussian { mu } => normal_cdf((z - mu * mu / 2.0) / mu),
+        }
+    }
+
+    /// Interval that holds all of the Laplace privacy loss, or all but 6e-5 of the Gaussian one.
+    pub fn support(&self) -> (f64, f64) {
+        match *self {
+            PrivacyLoss::Laplace { epsilon } => (-epsilon, epsilon),
+            PrivacyLoss::Gaussian { mu } => (mu * mu / 2.0 - 4.0 * mu, mu * mu / 2.0 + 4.0 * mu),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gdp_to_epsilon_delta_inverts_the_gaussian_privacy_profile() {
+        // 1-GDP at epsilon = 0: delta = Φ(1/2) - Φ(-1/2) = 0.382925
+        assert!(gdp_to_epsilon_delta(1.0, 0.382925) < 1e-4);
+        // 1-GDP at epsilon = 1: delta = Φ(-1/2) - e Φ(-3/2) = 0.308538 - e * 0.066807 = 0.126936
+        assert!((gdp_to_epsilon_delta(1.0, 0.126936) - 1.0).abs() < 1e-4);
+        // 0.5-GDP at epsilon = 2: Φ(-3.75) - e^2 Φ(-4.25) = 8.842e-5 - 7.389 * 1.0689e-5 = 9.44e-6
+        assert!((gdp_to_epsilon_delta(0.5, 9.44e-6) - 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn approx_dp_curve_falls_through_the_analytic_calibration() {
+        let sigma = analytic_gaussian_scale(1.0, 1e-5, 1.0);
+        let curve = gaussian_approx_dp_curve(sigma, 1.0, &[0.0, 0.5, 1.0, 2.0]);
+        assert!(curve.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!((curve[2] - 1e-5).abs() < 1e-9);
+        assert!((gdp_to_epsilon_delta(1.0 / sigma, 1e-5) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn max_information_vanishes_with_epsilon() {
+        assert!((max_information(2f64.ln(), 10) - 10.0).abs() < 1e-12);
+        assert_eq!(approximate_max_information(0.0, 1000, 0.05), 0.0);
+        // at ε = 0.01 and n = 1000: log2 e (0.05 + 0.01 sqrt(500 ln 40)) = 0.6917 bits
+        assert!((approximate_max_information(0.01, 1000, 0.05) - 0.6917).abs() < 1e-3);
+        assert!(approximate_max_information(0.01, 1000, 0.05) < max_information(0.01, 1000));
+    }
+
+    #[test]
+    fn analytic_gaussian_scale_beats_the_classic_bound_and_meets_delta() {
+        let (epsilon, delta) = (0.5, 1e-5);
+        let sigma = analytic_gaussian_scale(epsilon, delta, 1.0);
+        let classic = (2.0 * (1.25f64 / delta).ln()).sqrt() / epsilon;
+        assert!(sigma < classic);
+        assert!(gaussian_delta_for_epsilon(sigma, 1.0, epsilon) <= delta);
+        assert!(gaussian_delta_for_epsilon(sigma * 0.99, 1.0, epsilon) > delta);
+    }
+
+    #[test]
+    fn shuffling_amplifies_more_with_more_users() {
+        let (local, delta) = (1.0, 1e-6);
+        let few = shuffler_amplification(local, 1_000_000, delta);
+        let many = shuffler_amplification(local, 100_000_000, delta);
+        assert!(many < few && few < local);
+        // a hundred times the users, a tenth of the epsilon once the 1/sqrt(n) term dominates
+        assert!((few / many - 10.0).abs() < 0.2);
+        // too few users for the bound to apply
+        assert_eq!(shuffler_amplification(local, 100, delta), local);
+    }
+
+    #[test]
+    fn amplification_by_iteration_divides_by_the_steps_left() {
+        let one_step = gaussian_rdp(2.0, 1.0, 8.0);
+        assert_eq!(amplification_by_iteration(one_step, 10, 10), one_step);
+        assert!((amplification_by_iteration(one_step, 10, 1) - gaussian_rdp(2.0 * 10f64.sqrt(), 1.0, 8.0)).abs() < 1e-12);
+        assert!(amplification_by_iteration(one_step, 10, 4) < amplification_by_iteration(one_step, 10, 5));
+    }
+
+    #[test]
+    fn moments_accountant_matches_rdp_and_beats_basic_composition() {
+        let (sigma, delta, steps) = (4.0, 1e-5, 100);
+        for lambda in [1, 4, 31] {
+            let order = lambda as f64 + 1.0;
+            let rdp = rdp_to_approx_dp(steps as f64 * gaussian_rdp(sigma, 1.0, order), order, delta);
+            assert!((gaussian_moments_accountant(sigma, 1.0, steps, lambda, delta) - rdp).abs() < 1e-9);
+        }
+        let best = (1..=32).map(|lambda| gaussian_moments_accountant(sigma, 1.0, steps, lambda, delta)).fold(f64::INFINITY, f64::min);
+        let classic = steps as f64 * (2.0 * (1.25 * steps as f64 / delta).ln()).sqrt() / sigma;
+        assert!(best < classic / 5.0);
+    }
+
+    #[test]
+    fn binomial_epsilon_meets_delta_and_is_never_pure() {
+        let (n, delta) = (400, 1e-6);
+        let epsilon = binomial_scale_to_epsilon(n, 1, delta);
+        assert!(epsilon.is_finite() && epsilon > 0.0);
+        assert!(binomial_delta_for_epsilon(n, 1, epsilon) <= delta);
+        assert!(binomial_delta_for_epsilon(n, 1, epsilon * 0.99) > delta);
+        // more trials, more noise, less epsilon
+        assert!(binomial_scale_to_epsilon(4 * n, 1, delta) < epsilon);
+        // four trials put 1/16 on 0, which the neighbour shifted by one never outputs
+        assert!((binomial_delta_for_epsilon(4, 1, 100.0) - 1.0 / 16.0).abs() < 1e-12);
+        assert_eq!(binomial_scale_to_epsilon(4, 1, 1e-3), f64::INFINITY);
+    }
+
+    #[test]
+    fn laplace_rdp_lies_between_kl_and_pure_epsilon() {
+        // scale 2, sensitivity 1: pure epsilon 0.5, KL divergence 1/λ + e^(-1/λ) - 1 = 0.106531
+        let pure = 0.5;
+        let kl = 0.5 + (-0.5f64).exp() - 1.0;
+        assert!((laplace_rdp(2.0, 1.0, 1.0 + 1e-6) - kl).abs() < 1e-4);
+        assert!((laplace_rdp(2.0, 1.0, 1e6) - pure).abs() < 1e-4);
+        let mid = laplace_rdp(2.0, 1.0, 4.0);
+        assert!(kl < mid && mid < pure);
+    }
+
+    #[test]
+    fn adaptive_epsilon_allocation_sums_to_the_total() {
+        let allocation = adaptive_epsilon_allocation(&[100, 10, 0, 50], 2.0);
+        assert!((allocation.iter().sum::<f64>() - 2.0).abs() < 1e-12);
+        assert!(allocation[0] < allocation[3] && allocation[3] < allocation[1] && allocation[1] < allocation[2]);
+    }
+
+    #[test]
+    fn privacy_loss_bounds_delta() {
+        // Laplace: the atoms at ±eps and the density in between make up the whole mass
+        let epsilon = 0.7;
+        let laplace = PrivacyLoss::Laplace { epsilon };
+        let steps = 10_000;
+        let step = 2.0 * epsilon / steps as f64;
+        let continuous = (0..steps).map(|i| laplace.pdf(-epsilon + (i as f64 + 0.5) * step) * step).sum::<f64>();
+        assert!((continuous + 0.5 + (-epsilon).exp() / 2.0 - 1.0).abs() < 1e-6);
+        assert_eq!(laplace.cdf(epsilon), 1.0);
+        // Gaussian: P(Z > eps) is at least the exact delta at eps
+        let gaussian = PrivacyLoss::Gaussian { mu: 1.0 };
+        for epsilon in [0.0, 0.5, 1.0, 2.0] {
+            assert!(1.0 - gaussian.cdf(epsilon) >= gaussian_delta_for_epsilon(1.0, 1.0, epsilon));
+        }
+    }
+
+    #[test]
+    fn uncertainty_about_the_others_lowers_epsilon() {
+        // with nobody else the count is the target's alone
+        assert!((distributional_epsilon(0, 0.5, 2.0, 1e-12) - 0.5).abs() < 1e-6);
+        // a count of about 50 ± 7 from the others hides much more than Laplace(0.5) does
+        let hidden = distributional_epsilon(1000, 0.05, 0.5, 1e-6);
+        assert!(hidden > 0.0 && hidden < 1.0, "ε = {}", hidden);
+        // with more noise its heavier tail takes over and little is gained
+        assert!(distributional_epsilon(1000, 0.05, 2.0, 1e-6) > 0.45);
+        assert!(distributional_epsilon(1000, 0.05, 0.0, 1e-6) < 1.0);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+const MAX_LOG_ENTRIES: usize = 100;
+/// Rényi orders the noise params panel cycles through.
+const RDP_ORDERS: [f64; 7] = [1.5, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+/// Deltas the (ε, δ) curve of the privacy loss view reads epsilon off at.
+const TARGET_DELTAS: [f64; 7] = [1e-3, 1e-4, 1e-5, 1e-6, 1e-7, 1e-8, 1e-9];
+
+/// What the lower part of the screen shows.
+#[derive(Clone, Copy, PartialEq)]
+pub enum View {
+    Histogram,
+    TradeOff,
+    Releases,
+    PrivacyLoss,
+    SyntheticTwin,
+    Federated,
+    GradientDescent,
+    Projection,
+    Pca,
+    BudgetPlan,
+    NutritionLabel,
+}
+
+/// UI state that is not part of the noising itself.
+pub struct App {
+    pub view: View,
+    /// Whether key presses are currently typed into the search box instead of running commands.
+    pub search_mode: bool,
+    pub search_query: String,
+    /// Whether key presses are typed into the range box, see `finish_range_entry`.
+    pub range_mode: bool,
+    pub range_input: String,
+    /// First and last bucket index of the range query answered from the private CDF.
+    pub range: Option<(usize, usize)>,
+    pub show_event_log: bool,
+    /// Most recent user actions, oldest first, capped at `MAX_LOG_ENTRIES`.
+    pub event_log: VecDeque<(Instant, String)>,
+    pub started: Instant,
+    pub rdp_order: f64,
+    /// Delta at which the (ε, δ) curve is read.
+    pub target_delta: f64,
+    /// Index into the tutorial steps while the walkthrough is running.
+    pub tutorial_step: Option<usize>,
+}
+
+impl App {
+    pub fn new() -> App {
+        App {
+            view: View::Histogram,
+            search_mode: false,
+            search_query: String::new(),
+            range_mode: false,
+            range_input: String::new(),
+            range: None,
+            show_event_log: false,
+            event_log: VecDeque::with_capacity(MAX_LOG_ENTRIES),
+            started: Instant::now(),
+            rdp_order: RDP_ORDERS[1],
+            target_delta: TARGET_DELTAS[3],
+            tutorial_step: None,
+        }
+    }
+
+    pub fn next_view(&mut self) {
+        self.view = match self.view {
+            View::Histogram => View::TradeOff,
+            View::TradeOff => View::Releases,
+            View::Releases => View::PrivacyLoss,
+            View::PrivacyLoss => View::SyntheticTwin,
+            View::SyntheticTwin => View::Federated,
+            View::Federated => View::GradientDescent,
+            View::GradientDescent => View::Projection,
+            View::Projection => View::Pca,
+            View::Pca => View::BudgetPlan,
+            View::BudgetPlan => View::NutritionLabel,
+            View::NutritionLabel => View::Histogram,
+        };
+    }
+
+    pub fn next_rdp_order(&mut self) {
+        let pos = RDP_ORDERS.iter().position(|&order| order == self.rdp_order).unwrap_or(0);
+        self.rdp_order = RDP_ORDERS[(pos + 1) % RDP_ORDERS.len()];
+    }
+
+    pub fn next_target_delta(&mut self) {
+        let pos = TARGET_DELTAS.iter().position(|&delta| delta == self.target_delta).unwrap_or(0);
+        self.target_delta = TARGET_DELTAS[(pos + 1) % TARGET_DELTAS.len()];
+    }
+
+    /// Moves to the next of `step_count` tutorial steps, ending the tutorial after the last.
+    pub fn next_tutorial_step(&mut self, step_count: usize) {
+        self.tutorial_step = self.tutorial_step
+            .map(|step| step + 1)
+            .filter(|&step| step < step_count);
+    }
+
+    pub fn toggle_event_log(&mut self) {
+        self.show_event_log = !self.show_event_log;
+    }
+
+    pub fn log_event(&mut self, event: String) {
+        if self.event_log.len() == MAX_LOG_ENTRIES {
+            self.event_log.pop_front();
+        }
+        self.event_log.push_back((Instant::now(), event));
+    }
+
+    pub fn start_search(&mut self) {
+        self.search_mode = true;
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search_mode = false;
+        self.search_query.clear();
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search_mode || !self.search_query.is_empty()
+    }
+
+    /// Whether the box below the panels is open, for a search or a range.
+    pub fn has_input_box(&self) -> bool {
+        self.is_searching() || self.range_mode
+    }
+
+    pub fn start_range_entry(&mut self) {
+        self.range_mode = true;
+        self.range_input.clear();
+    }
+
+    pub fn cancel_range_entry(&mut self) {
+        self.range_mode = false;
+        self.range_input.clear();
+    }
+
+    /// Reads the first two numbers typed, in any order, as the ends of the range; anything
+    /// else clears the range.
+    pub fn finish_range_entry(&mut self) {
+        let bounds = self.range_input
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|number| number.parse::<usize>().ok())
+            .collect::<Vec<_>>();
+        self.range = match bounds[..] {
+            [a, b, ..] => Some((a.min(b), a.max(b))),
+            _ => None,
+        };
+        self.range_mode = false;
+    }
+
+    /// Whether the bucket label contains the current query, `None` when there is no query.
+    pub fn bucket_matches(&self, label: &str) -> Option<bool> {
+        if self.search_query.is_empty() {
+            return None;
+        }
+        Some(label.contains(self.search_query.as_str()))
+    }
+}
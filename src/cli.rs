@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+const DEFAULT_SEPARATOR: char = ',';
+
+pub struct CliArgs {
+    pub separator: char,
+    /// TOML file with the initial noise parameters, see `Noiser::from_config`.
+    pub config: Option<PathBuf>,
+    /// Parquet file to read the records from instead of `CSV_FILE_PATH`.
+    pub parquet: Option<PathBuf>,
+    /// Write the records to this Parquet file and exit without starting the UI.
+    pub export_parquet: Option<PathBuf>,
+    /// Number of leave-one-out samples for the jackknife sensitivity estimate.
+    pub jackknife_samples: Option<usize>,
+    /// Start with the scripted walkthrough of `tutorial_steps`.
+    pub tutorial: bool,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        CliArgs {
+            separator: DEFAULT_SEPARATOR,
+            config: None,
+            parquet: None,
+            export_parquet: None,
+            jackknife_samples: None,
+            tutorial: false,
+        }
+    }
+}
+
+/// Accepts a single ASCII character, or the escaped form `\t` for tab-separated files
+/// (a literal tab is hard to pass through most shells).
+fn parse_separator(value: &str) -> Result<char, String> {
+    let separator = match value {
+        "\\t" => '\t',
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(format!("--separator expects a single character, got '{}'", value)),
+            }
+        }
+    };
+    if !separator.is_ascii() {
+        return Err(format!("--separator must be an ASCII character, got '{}'", value));
+    }
+    Ok(separator)
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<CliArgs, String> {
+    let mut cli_args = CliArgs::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--separator" => {
+                let value = args.next().ok_or("--separator requires a value")?;
+                cli_args.separator = parse_separator(&value)?;
+            }
+            "--config" => {
+                let value = args.next().ok_or("--config requires a path")?;
+                cli_args.config = Some(PathBuf::from(value));
+            }
+            "--parquet" => {
+                let value = args.next().ok_or("--parquet requires a path")?;
+                cli_args.parquet = Some(PathBuf::from(value));
+            }
+            "--export-parquet" => {
+                let value = args.next().ok_or("--export-parquet requires a path")?;
+                cli_args.export_parquet = Some(PathBuf::from(value));
+            }
+            "--jackknife-samples" => {
+                let value = args.next().ok_or("--jackknife-samples requires a value")?;
+                let samples = value.parse()
+                    .map_err(|_| format!("--jackknife-samples expects a number, got '{}'", value))?;
+                cli_args.jackknife_samples = Some(samples);
+            }
+            "--tutorial" => cli_args.tutorial = true,
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+    Ok(cli_args)
+}
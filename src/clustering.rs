@@ -0,0 +1,68 @@
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
+
+use crate::csv_reader::Record;
+
+fn squared_distance(a: &[f64; 6], b: &[f64; 6]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn nearest_centroid(point: &[f64; 6], centroids: &[[f64; 6]]) -> usize {
+    let mut nearest = 0;
+    for (pos, centroid) in centroids.iter().enumerate() {
+        if squared_distance(point, centroid) < squared_distance(point, &centroids[nearest]) {
+            nearest = pos;
+        }
+    }
+    nearest
+}
+
+/// Lloyd's k-means over the normalized feature vectors of the raw (un-noised) records.
+/// Centroids are seeded with `k` distinct records picked by `seed`; returns the cluster
+/// index of every record.
+pub fn kmeans(records: &[Record], k: usize, seed: u64, max_iter: usize) -> Vec<usize> {
+    let points = records.iter().map(|r| r.to_feature_vector()).collect::<Vec<_>>();
+    let k = k.min(points.len());
+    if k == 0 {
+        return vec![0; points.len()];
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut centroids = sample(&mut rng, points.len(), k).iter()
+        .map(|i| points[i])
+        .collect::<Vec<_>>();
+    let mut assignments = vec![usize::MAX; points.len()];
+
+    for _ in 0..max_iter {
+        let next = points.iter().map(|p| nearest_centroid(p, &centroids)).collect::<Vec<_>>();
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+
+        let mut sums = vec![[0.0; 6]; k];
+        let mut sizes = vec![0usize; k];
+        for (point, &cluster) in points.iter().zip(assignments.iter()) {
+            for (sum, value) in sums[cluster].iter_mut().zip(point.iter()) {
+                *sum += value;
+            }
+            sizes[cluster] += 1;
+        }
+        for cluster in 0..k {
+            // an empty cluster keeps its previous centroid
+            if sizes[cluster] > 0 {
+                centroids[cluster] = sums[cluster].map(|sum| sum / sizes[cluster] as f64);
+            }
+        }
+    }
+    assignments
+}
+
+pub fn cluster_sizes(assignments: &[usize], k: usize) -> Vec<u64> {
+    let mut sizes = vec![0u64; k];
+    for &cluster in assignments {
+        sizes[cluster] += 1;
+    }
+    sizes
+}
@@ -0,0 +1,60 @@
+//! Continual release of running totals by the binary mechanism (Dwork, Naor, Pitassi,
+//! Rothblum, STOC 2010; Chan, Shi, Song, TISSEC 2011), the noise DP-FTRL (Kairouz et al.,
+//! ICML 2021) puts on its running sums of gradients. The rounds are the leaves of a binary
+//! tree, every node gets noise once, and the total after round `t` is the sum of at most
+//! one node per level. A record that arrives in one round is in one node per level, so the
+//! noise grows with the number of levels instead of with the number of rounds summed.
+
+use rand::Rng;
+
+use crate::mechanisms::laplace_sample;
+
+/// Levels of the tree over `n_rounds` rounds, `1 + floor(log2 n_rounds)`.
+pub fn binary_mechanism_levels(n_rounds: usize) -> usize {
+    n_rounds.max(1).ilog2() as usize + 1
+}
+
+/// Running totals of the per-round vectors `rounds` after every round, each node of the
+/// tree over them noised with `Laplace(scale)` on every component. A record in a single
+/// round moves one node per level, so `scale = binary_mechanism_levels · Δ / ε` is ε-DP for
+/// all the totals together.
+pub fn binary_mechanism<R: Rng>(rounds: &[Vec<f64>], scale: f64, rng: &mut R) -> Vec<Vec<f64>> {
+    let dimension = rounds.first().map_or(0, |round| round.len());
+    let levels = binary_mechanism_levels(rounds.len());
+    // level l holds the sums of 2^l consecutive rounds, noised once
+    let nodes = (0..levels)
+        .map(|level| {
+            rounds.chunks(1 << level)
+                .map(|chunk| (0..dimension)
+                    .map(|i| chunk.iter().map(|round| round[i]).sum::<f64>() + laplace_sample(scale, rng))
+                    .collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    (1..=rounds.len())
+        .map(|t| {
+            let mut total = vec![0.0; dimension];
+            let mut start = 0;
+            for level in (0..levels).rev().filter(|&level| t & (1 << level) != 0) {
+                for (sum, node) in total.iter_mut().zip(&nodes[level][start >> level]) {
+                    *sum += node;
+                }
+                start += 1 << level;
+            }
+            total
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_mechanism_sums_the_rounds_so_far() {
+        let rounds = (1..=5).map(|t| vec![t as f64, 1.0]).collect::<Vec<_>>();
+        assert_eq!(binary_mechanism_levels(5), 3);
+        let totals = binary_mechanism(&rounds, 0.0, &mut rand::thread_rng());
+        assert_eq!(totals, vec![vec![1.0, 1.0], vec![3.0, 2.0], vec![6.0, 3.0], vec![10.0, 4.0], vec![15.0, 5.0]]);
+    }
+}
@@ -0,0 +1,92 @@
+use std::error::Error;
+
+use csv::ReaderBuilder;
+
+use crate::dataset::COLUMNS;
+
+/// Lower and upper bound of every column, in `CsvDataSet::columns` order, used to map a
+/// record onto the unit cube and to bound the sensitivity of queries over a column.
+const FEATURE_BOUNDS: [(f64, f64); 6] = [
+    (18.0, 100.0),
+    (0.0, 1.0),
+    (1.0, 20.0),
+    (1.0, 6.0),
+    (0.0, 250000.0),
+    (0.0, 1.0),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub age: u8,
+    pub sex: u8,
+    pub educ: u8,
+    pub race: u8,
+    pub income: u64,
+    pub married: u8,
+}
+
+/// Bounds of the column called `field`, `None` for an unknown column.
+pub fn field_bounds(field: &str) -> Option<(f64, f64)> {
+    COLUMNS.iter().position(|&c| c == field).map(|pos| FEATURE_BOUNDS[pos])
+}
+
+impl Record {
+    fn raw_values(&self) -> [f64; 6] {
+        [
+            self.age as f64,
+            self.sex as f64,
+            self.educ as f64,
+            self.race as f64,
+            self.income as f64,
+            self.married as f64,
+        ]
+    }
+
+    pub fn field_value(&self, field: &str) -> Option<f64> {
+        COLUMNS.iter().position(|&c| c == field).map(|pos| self.raw_values()[pos])
+    }
+
+    pub fn to_feature_vector(&self) -> [f64; 6] {
+        let raw = self.raw_values();
+        let mut features = [0.0; 6];
+        for (pos, value) in raw.iter().enumerate() {
+            let (lower, upper) = FEATURE_BOUNDS[pos];
+            features[pos] = ((value - lower) / (upper - lower)).clamp(0.0, 1.0);
+        }
+        features
+    }
+
+    /// Serializes the record as one header-less row, in `CsvDataSet::columns` order.
+    pub fn to_row(&self, separator: char) -> String {
+        [
+            self.age.to_string(),
+            self.sex.to_string(),
+            self.educ.to_string(),
+            self.race.to_string(),
+            self.income.to_string(),
+            self.married.to_string(),
+        ].join(&separator.to_string())
+    }
+}
+
+/// Parses header-less CSV rows (as stored in `CsvDataSet.data`) into records.
+/// `separator` must be ASCII, which `cli::parse_args` already guarantees.
+pub fn read_data(data: &str, separator: char) -> Result<Vec<Record>, Box<dyn Error>> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(separator as u8)
+        .from_reader(data.as_bytes());
+    let mut records = Vec::<Record>::new();
+    for row in reader.records() {
+        let row = row?;
+        records.push(Record {
+            age: row[0].parse()?,
+            sex: row[1].parse()?,
+            educ: row[2].parse()?,
+            race: row[3].parse()?,
+            income: row[4].parse()?,
+            married: row[5].parse()?,
+        });
+    }
+    Ok(records)
+}
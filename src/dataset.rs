@@ -1,18 +1,374 @@
-const COLUMNS: &'static [&'static str] = &["age", "sex", "educ", "race", "income", "married"];
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
 
-pub struct CsvDataSet<'a> {
-    pub data: &'a String,
+use arrow2::array::{Array, PrimitiveArray};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{Field, Schema};
+use arrow2::io::parquet::read;
+use arrow2::io::parquet::write::{
+    transverse, CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version, WriteOptions,
+};
+use arrow2::types::NativeType;
+use csv::ReaderBuilder;
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::csv_reader::{field_bounds, read_data, Record};
+use crate::error::PriviError;
+use crate::mechanisms::laplace_sample;
+
+pub const COLUMNS: &[&str] = &["age", "sex", "educ", "race", "income", "married"];
+/// The columns `aggregate_buckets` knows the buckets of.
+pub const AGGREGATE_FIELDS: &[&str] = &["educ", "income"];
+/// Bin width of `generate_synthetic_twin` for income, whose values are multiples of it;
+/// the other columns are binned by their integer values.
+const INCOME_BIN_WIDTH: f64 = 10000.0;
+
+pub struct CsvDataSet {
+    pub data: String,
+    pub separator: char,
 }
 
-impl<'a> CsvDataSet<'a> {
+impl CsvDataSet {
     pub fn columns(&self) -> Vec<&'static str> {
         Vec::from(COLUMNS)
     }
 
-    pub fn aggregate_buckets(&self, field: &String) -> Vec<String> {
-        match field.as_str() {
+    pub fn record_count(&self) -> usize {
+        self.data.lines().filter(|line| !line.is_empty()).count()
+    }
+
+    pub fn aggregate_buckets(&self, field: &str) -> Vec<String> {
+        match field {
             "income" => (10000u32..210000).step_by(10000).map(|x| x.to_string()).collect::<Vec<_>>(),
             &_ => (1u8..21).map(|x| x.to_string()).collect::<Vec<_>>(),
         }
     }
-}
\ No newline at end of file
+
+    /// Number of records for every value of `field` that occurs, as written in the data.
+    pub fn value_counts(&self, field: &str) -> BTreeMap<String, u64> {
+        let mut counts = BTreeMap::new();
+        let Some(column) = COLUMNS.iter().position(|&c| c == field) else {
+            return counts;
+        };
+        let mut reader = ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(self.separator as u8)
+            .from_reader(self.data.as_bytes());
+        for row in reader.records().filter_map(|row| row.ok()) {
+            if let Some(value) = row.get(column) {
+                *counts.entry(value.to_string()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Copy of the data set holding only the rows where `given_field == given_value`.
+    pub fn filter_rows(&self, given_field: &str, given_value: &str) -> CsvDataSet {
+        let mut filtered = CsvDataSet { data: String::new(), separator: self.separator };
+        let column = match COLUMNS.iter().position(|&c| c == given_field) {
+            Some(column) => column,
+            None => return filtered,
+        };
+        let mut reader = ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(self.separator as u8)
+            .from_reader(self.data.as_bytes());
+        let rows = reader.records()
+            .filter_map(|row| row.ok())
+            .filter(|row| row.get(column) == Some(given_value))
+            .map(|row| row.iter().collect::<Vec<_>>().join(&self.separator.to_string()))
+            .collect::<Vec<_>>();
+        filtered.data = rows.join("\n");
+        filtered
+    }
+
+    /// The buckets of `aggregate_buckets(field)` that hold at least one record with
+    /// `given_field == given_value`, e.g. the education levels present among women.
+    pub fn aggregate_buckets_given(&self, field: &str, given_field: &str, given_value: &str) -> Vec<String> {
+        let column = match COLUMNS.iter().position(|&c| c == field) {
+            Some(column) => column,
+            None => return Vec::new(),
+        };
+        let filtered = self.filter_rows(given_field, given_value);
+        let values = filtered.data.lines()
+            .filter_map(|line| line.split(self.separator).nth(column))
+            .collect::<Vec<_>>();
+        self.aggregate_buckets(field).into_iter()
+            .filter(|bucket| values.contains(&bucket.as_str()))
+            .collect()
+    }
+
+    /// Differentially private stand-in for the data set: every column's histogram gets
+    /// Laplace noise, and as many records as the original has (the record count is taken
+    /// as public) are sampled from the noised histograms. A record is in all six
+    /// histograms, so each gets `epsilon / 6`. Columns are sampled independently, so the
+    /// marginals carry over but the correlations between columns do not. The noise is only
+    /// private if `rng` is seeded from entropy; a fixed seed is for tests.
+    pub fn generate_synthetic_twin<R: Rng>(&self, epsilon: f64, rng: &mut R) -> CsvDataSet {
+        let records = read_data(&self.data, self.separator).unwrap_or_default();
+        let scale = COLUMNS.len() as f64 / epsilon;
+        let columns = COLUMNS.iter()
+            .map(|&column| {
+                let (lower, width, counts) = column_histogram(&records, column);
+                let bins = counts.len();
+                let noised = counts.iter()
+                    .map(|count| (count + laplace_sample(scale, rng)).max(0.0))
+                    .collect::<Vec<_>>();
+                // all the mass was noised away, nothing is known about the column
+                let weights = if noised.iter().all(|&count| count == 0.0) { vec![1.0; bins] } else { noised };
+                let bin = WeightedIndex::new(&weights).expect("weights are non-negative and not all zero");
+                (0..records.len())
+                    .map(|_| lower + bin.sample(rng) as f64 * width)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let synthetic = (0..records.len())
+            .map(|i| Record {
+                age: columns[0][i] as u8,
+                sex: columns[1][i] as u8,
+                educ: columns[2][i] as u8,
+                race: columns[3][i] as u8,
+                income: columns[4][i] as u64,
+                married: columns[5][i] as u8,
+            })
+            .collect::<Vec<_>>();
+        let mut twin = CsvDataSet { data: String::new(), separator: self.separator };
+        twin.append_rows(&synthetic);
+        twin
+    }
+
+    /// As many rows as the data set has, drawn from it with replacement.
+    pub fn bootstrap_sample(&self, seed: u64) -> CsvDataSet {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let rows = self.data.lines().filter(|line| !line.is_empty()).collect::<Vec<_>>();
+        let data = (0..rows.len())
+            .map(|_| rows[rng.gen_range(0..rows.len())])
+            .collect::<Vec<_>>()
+            .join("\n");
+        CsvDataSet { data, separator: self.separator }
+    }
+
+    /// `KL(P || Q)` in nats of the histogram `P` of `field` in this data set from the
+    /// histogram `Q` in `other`, with `smoothing` added to every bin of both before they are
+    /// normalized: a bin empty in `other` but not here would make the divergence infinite.
+    /// Zero for identical histograms, and the larger the smoothing the closer to zero.
+    pub fn kl_divergence(&self, other: &CsvDataSet, field: &str, smoothing: f64) -> f64 {
+        let distribution = |dataset: &CsvDataSet| {
+            let records = read_data(&dataset.data, dataset.separator).unwrap_or_default();
+            let (_, _, counts) = column_histogram(&records, field);
+            let total = counts.iter().map(|count| count + smoothing).sum::<f64>();
+            counts.iter().map(|count| (count + smoothing) / total).collect::<Vec<_>>()
+        };
+        let (p, q) = (distribution(self), distribution(other));
+        p.iter().zip(q.iter())
+            .filter(|&(&p, _)| p > 0.0)
+            .map(|(p, q)| p * (p / q).ln())
+            .sum()
+    }
+
+    /// Pearson correlation of columns `a` and `b` over the records, zero when either column
+    /// is constant. Independent column histograms, as in `generate_synthetic_twin`, keep
+    /// every column's distribution but leave this near zero.
+    pub fn column_correlation(&self, a: &str, b: &str) -> f64 {
+        let pairs = read_data(&self.data, self.separator).unwrap_or_default().iter()
+            .filter_map(|record| Some((record.field_value(a)?, record.field_value(b)?)))
+            .collect::<Vec<_>>();
+        let n = pairs.len().max(1) as f64;
+        let (mean_a, mean_b) = pairs.iter().fold((0.0, 0.0), |(sa, sb), (x, y)| (sa + x / n, sb + y / n));
+        let (covariance, variance_a, variance_b) = pairs.iter()
+            .map(|(x, y)| (x - mean_a, y - mean_b))
+            .fold((0.0, 0.0, 0.0), |(c, va, vb), (dx, dy)| (c + dx * dy, va + dx * dx, vb + dy * dy));
+        if variance_a == 0.0 || variance_b == 0.0 { 0.0 } else { covariance / (variance_a * variance_b).sqrt() }
+    }
+
+    pub fn append_row(&mut self, row: &Record) {
+        if !self.data.is_empty() && !self.data.ends_with('\n') {
+            self.data.push('\n');
+        }
+        self.data.push_str(&row.to_row(self.separator));
+    }
+
+    pub fn append_rows(&mut self, rows: &[Record]) {
+        for row in rows {
+            self.append_row(row);
+        }
+    }
+
+    /// Writes the records as typed Parquet columns, `u64` for income and `u8` for the rest.
+    pub fn to_parquet(&self, path: &Path) -> Result<(), PriviError> {
+        let records = read_data(&self.data, self.separator)
+            .map_err(|err| PriviError::InvalidData(err.to_string()))?;
+        let u8_column = |value: fn(&Record) -> u8| {
+            PrimitiveArray::<u8>::from_vec(records.iter().map(value).collect()).boxed()
+        };
+        let columns = vec![
+            u8_column(|r| r.age),
+            u8_column(|r| r.sex),
+            u8_column(|r| r.educ),
+            u8_column(|r| r.race),
+            PrimitiveArray::<u64>::from_vec(records.iter().map(|r| r.income).collect()).boxed(),
+            u8_column(|r| r.married),
+        ];
+        let schema = Schema::from(COLUMNS.iter().zip(columns.iter())
+            .map(|(name, column)| Field::new(*name, column.data_type().clone(), false))
+            .collect::<Vec<_>>());
+
+        let options = WriteOptions {
+            write_statistics: true,
+            compression: CompressionOptions::Uncompressed,
+            version: Version::V2,
+            data_pagesize_limit: None,
+        };
+        let encodings = schema.fields.iter()
+            .map(|field| transverse(&field.data_type, |_| Encoding::Plain))
+            .collect();
+        let row_groups = RowGroupIterator::try_new(vec![Ok(Chunk::new(columns))].into_iter(), &schema, options, encodings)?;
+        let mut writer = FileWriter::try_new(File::create(path)?, schema, options)?;
+        for group in row_groups {
+            writer.write(group?)?;
+        }
+        writer.end(None)?;
+        Ok(())
+    }
+
+    /// Reads a file written by `to_parquet`, or any Parquet file with the same six columns.
+    pub fn from_parquet(path: &Path) -> Result<CsvDataSet, PriviError> {
+        let mut reader = File::open(path)?;
+        let metadata = read::read_metadata(&mut reader)?;
+        let schema = read::infer_schema(&metadata)?;
+        let positions = COLUMNS.iter()
+            .map(|&name| schema.fields.iter().position(|field| field.name == name)
+                .ok_or_else(|| PriviError::InvalidData(format!("parquet file has no '{}' column", name))))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut dataset = CsvDataSet { data: String::new(), separator: ',' };
+        let chunks = read::FileReader::new(reader, metadata.row_groups, schema, None, None, None);
+        for chunk in chunks {
+            let chunk = chunk?;
+            let arrays = chunk.arrays();
+            let age = column_values::<u8>(arrays[positions[0]].as_ref(), COLUMNS[0])?;
+            let sex = column_values::<u8>(arrays[positions[1]].as_ref(), COLUMNS[1])?;
+            let educ = column_values::<u8>(arrays[positions[2]].as_ref(), COLUMNS[2])?;
+            let race = column_values::<u8>(arrays[positions[3]].as_ref(), COLUMNS[3])?;
+            let income = column_values::<u64>(arrays[positions[4]].as_ref(), COLUMNS[4])?;
+            let married = column_values::<u8>(arrays[positions[5]].as_ref(), COLUMNS[5])?;
+            let records = (0..chunk.len())
+                .map(|i| Record {
+                    age: age[i],
+                    sex: sex[i],
+                    educ: educ[i],
+                    race: race[i],
+                    income: income[i],
+                    married: married[i],
+                })
+                .collect::<Vec<_>>();
+            dataset.append_rows(&records);
+        }
+        Ok(dataset)
+    }
+}
+
+fn column_values<'a, T: NativeType>(array: &'a dyn Array, name: &str) -> Result<&'a [T], PriviError> {
+    array.as_any().downcast_ref::<PrimitiveArray<T>>()
+        .map(|values| values.values().as_slice())
+        .ok_or_else(|| PriviError::InvalidData(format!("unexpected type {:?} for column '{}'", array.data_type(), name)))
+}
+
+/// Lower bound, bin width and counts of `column` over `records`: bins of `INCOME_BIN_WIDTH`
+/// for income and of the integer values for the other columns, across the column's bounds.
+fn column_histogram(records: &[Record], column: &str) -> (f64, f64, Vec<f64>) {
+    let (lower, upper) = field_bounds(column).unwrap_or((0.0, 0.0));
+    let width = if column == "income" { INCOME_BIN_WIDTH } else { 1.0 };
+    let mut counts = vec![0.0; ((upper - lower) / width) as usize + 1];
+    for value in records.iter().filter_map(|record| record.field_value(column)) {
+        counts[((value.clamp(lower, upper) - lower) / width).round() as usize] += 1.0;
+    }
+    (lower, width, counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(educ: u8) -> Record {
+        Record { age: 30, sex: 1, educ, race: 1, income: 50000, married: 0 }
+    }
+
+    #[test]
+    fn append_row_increases_record_count() {
+        let mut dataset = CsvDataSet { data: String::from("59,1,19,1,190000,1\n"), separator: ',' };
+        dataset.append_row(&record(5));
+        assert_eq!(dataset.record_count(), 2);
+        assert!(dataset.data.ends_with("30,1,5,1,50000,0"));
+    }
+
+    #[test]
+    fn aggregate_buckets_given_only_keeps_buckets_of_matching_records() {
+        let dataset = CsvDataSet {
+            data: String::from("30,0,3,1,50000,0\n40,1,5,1,60000,1\n50,0,7,1,70000,0"),
+            separator: ',',
+        };
+        assert_eq!(dataset.filter_rows("sex", "0").record_count(), 2);
+        assert_eq!(dataset.aggregate_buckets_given("educ", "sex", "0"), vec!["3", "7"]);
+        assert_eq!(dataset.aggregate_buckets_given("income", "married", "1"), vec!["60000"]);
+    }
+
+    #[test]
+    fn append_rows_increases_record_count() {
+        let mut dataset = CsvDataSet { data: String::new(), separator: '\t' };
+        dataset.append_rows(&[record(1), record(2), record(3)]);
+        assert_eq!(dataset.record_count(), 3);
+        assert_eq!(dataset.data.lines().next(), Some("30\t1\t1\t1\t50000\t0"));
+    }
+
+    #[test]
+    fn parquet_round_trip_keeps_records() {
+        let dataset = CsvDataSet {
+            data: String::from("30;0;3;1;50000;0\n40;1;5;2;250000;1"),
+            separator: ';',
+        };
+        let path = std::env::temp_dir().join("simple_privi_round_trip.parquet");
+        dataset.to_parquet(&path).unwrap();
+        let read_back = CsvDataSet::from_parquet(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_data(&read_back.data, read_back.separator).unwrap(),
+                   read_data(&dataset.data, dataset.separator).unwrap());
+    }
+
+    #[test]
+    fn synthetic_twin_keeps_record_count_and_bounds() {
+        let mut dataset = CsvDataSet { data: String::new(), separator: ',' };
+        dataset.append_rows(&(1..=20).map(record).collect::<Vec<_>>());
+        let twin = dataset.generate_synthetic_twin(1.0, &mut StdRng::seed_from_u64(3));
+        let records = read_data(&twin.data, twin.separator).unwrap();
+        assert_eq!(records.len(), 20);
+        assert!(records.iter().all(|r| (1..=20).contains(&r.educ) && r.income % 10000 == 0 && r.income <= 250000));
+        // without noise the only income bin is the original one
+        let exact = dataset.generate_synthetic_twin(f64::INFINITY, &mut StdRng::seed_from_u64(3));
+        assert!(read_data(&exact.data, exact.separator).unwrap().iter().all(|r| r.income == 50000));
+    }
+
+    #[test]
+    fn kl_divergence_is_zero_only_for_the_same_histogram() {
+        let mut p = CsvDataSet { data: String::new(), separator: ',' };
+        p.append_rows(&[record(1), record(2), record(2), record(3)]);
+        let mut q = CsvDataSet { data: String::new(), separator: ',' };
+        q.append_rows(&[record(1), record(1), record(1), record(3)]);
+        assert_eq!(p.kl_divergence(&p, "educ", 0.5), 0.0);
+        assert!(p.kl_divergence(&q, "educ", 0.5) > 0.0);
+        assert!(p.kl_divergence(&q, "educ", 0.5) < p.kl_divergence(&q, "educ", 0.01));
+    }
+
+    #[test]
+    fn column_correlation_of_linked_columns() {
+        let mut dataset = CsvDataSet { data: String::new(), separator: ',' };
+        dataset.append_rows(&(1..=10).map(|educ| Record { income: 10000 * educ as u64, ..record(educ) }).collect::<Vec<_>>());
+        assert!((dataset.column_correlation("educ", "income") - 1.0).abs() < 1e-12);
+        assert_eq!(dataset.column_correlation("educ", "age"), 0.0);
+    }
+}
@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum PriviError {
+    Io(io::Error),
+    /// The config file is not valid TOML or does not match `NoiserConfig`.
+    ConfigParse(toml::de::Error),
+    /// The config file parses, but one of its values is out of range.
+    InvalidConfig(String),
+    /// A row of the data set could not be parsed into a `Record`.
+    InvalidData(String),
+    /// OpenDP refused to build or run a measurement.
+    Mechanism(String),
+    Parquet(arrow2::error::Error),
+}
+
+impl fmt::Display for PriviError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PriviError::Io(err) => write!(f, "could not read file: {}", err),
+            PriviError::ConfigParse(err) => write!(f, "could not parse config: {}", err),
+            PriviError::InvalidConfig(reason) => write!(f, "invalid config: {}", reason),
+            PriviError::InvalidData(reason) => write!(f, "invalid data: {}", reason),
+            PriviError::Mechanism(reason) => write!(f, "could not apply noise: {}", reason),
+            PriviError::Parquet(err) => write!(f, "parquet error: {}", err),
+        }
+    }
+}
+
+impl Error for PriviError {}
+
+impl From<io::Error> for PriviError {
+    fn from(err: io::Error) -> Self {
+        PriviError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for PriviError {
+    fn from(err: toml::de::Error) -> Self {
+        PriviError::ConfigParse(err)
+    }
+}
+
+impl From<arrow2::error::Error> for PriviError {
+    fn from(err: arrow2::error::Error) -> Self {
+        PriviError::Parquet(err)
+    }
+}
@@ -0,0 +1,89 @@
+//! The clip-then-noise step of DP-SGD (Abadi et al., CCS 2016) on histograms instead of
+//! gradients: every client contributes a vector, its histogram of the field, which is
+//! clipped to an L2 norm of `clip_norm` before the vectors are summed and noised. That is
+//! the pattern that makes each step of gradient descent private; `gradient_descent` runs it
+//! on per-record gradients.
+
+use rand::Rng;
+
+use crate::mechanisms::gaussian_noise;
+
+pub struct SimulatedGradientNoiser {
+    /// Largest L2 norm a client's contribution may have, the L2 sensitivity of the sum.
+    pub clip_norm: f64,
+    /// Noise standard deviation in units of `clip_norm`.
+    pub noise_multiplier: f64,
+}
+
+impl SimulatedGradientNoiser {
+    /// Scales `gradient` down to `clip_norm` if it is longer, leaves it alone otherwise.
+    pub fn clip(&self, gradient: &[f64]) -> Vec<f64> {
+        let norm = gradient.iter().map(|value| value * value).sum::<f64>().sqrt();
+        let factor = if norm > self.clip_norm { self.clip_norm / norm } else { 1.0 };
+        gradient.iter().map(|value| value * factor).collect()
+    }
+
+    /// Sum of the clipped `gradients` with Gaussian noise of standard deviation
+    /// `noise_multiplier * clip_norm`, and the number of gradients that had to be clipped.
+    /// A small clip norm needs little noise but biases the sum towards zero; a large one
+    /// keeps the sum intact and drowns it in noise.
+    pub fn noised_sum<R: Rng>(&self, gradients: &[Vec<f64>], rng: &mut R) -> (Vec<f64>, usize) {
+        let dimension = gradients.first().map_or(0, |gradient| gradient.len());
+        let mut sum = vec![0.0; dimension];
+        let mut clipped = 0;
+        for gradient in gradients {
+            let clipped_gradient = self.clip(gradient);
+            if clipped_gradient != *gradient {
+                clipped += 1;
+            }
+            for (total, value) in sum.iter_mut().zip(clipped_gradient) {
+                *total += value;
+            }
+        }
+        (gaussian_noise(&sum, self.noise_multiplier * self.clip_norm, rng), clipped)
+    }
+
+    /// Every iterate of `steps` of gradient descent at `learning_rate` on the mean of
+    /// `½ |θ - x|²` over the `examples`, from `θ = 0`. Each step takes the mean gradient from
+    /// `noised_sum` over the per-example gradients `θ - x`; without noise or clipping the
+    /// iterates move towards the mean of the examples.
+    pub fn gradient_descent<R: Rng>(&self, examples: &[Vec<f64>], steps: usize, learning_rate: f64, rng: &mut R) -> Vec<Vec<f64>> {
+        let n = examples.len().max(1) as f64;
+        let mut theta = vec![0.0; examples.first().map_or(0, |example| example.len())];
+        (0..steps)
+            .map(|_| {
+                let gradients = examples.iter()
+                    .map(|example| theta.iter().zip(example).map(|(t, x)| t - x).collect())
+                    .collect::<Vec<Vec<f64>>>();
+                let (sum, _) = self.noised_sum(&gradients, rng);
+                for (t, g) in theta.iter_mut().zip(sum) {
+                    *t -= learning_rate * g / n;
+                }
+                theta.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clip_only_shortens_long_gradients() {
+        let noiser = SimulatedGradientNoiser { clip_norm: 5.0, noise_multiplier: 0.0 };
+        assert_eq!(noiser.clip(&[3.0, 4.0]), vec![3.0, 4.0]);
+        assert_eq!(noiser.clip(&[6.0, 8.0]), vec![3.0, 4.0]);
+        let (sum, clipped) = noiser.noised_sum(&[vec![3.0, 4.0], vec![6.0, 8.0]], &mut rand::thread_rng());
+        assert_eq!((sum, clipped), (vec![6.0, 8.0], 1));
+    }
+
+    #[test]
+    fn gradient_descent_without_noise_finds_the_mean() {
+        let noiser = SimulatedGradientNoiser { clip_norm: 10.0, noise_multiplier: 0.0 };
+        let examples = [vec![1.0, 0.0], vec![0.0, 1.0], vec![0.0, 1.0], vec![0.0, 1.0]];
+        let iterates = noiser.gradient_descent(&examples, 2, 0.5, &mut rand::thread_rng());
+        assert_eq!(iterates, vec![vec![0.125, 0.375], vec![0.1875, 0.5625]]);
+        assert_eq!(noiser.gradient_descent(&examples, 1, 1.0, &mut rand::thread_rng()), vec![vec![0.25, 0.75]]);
+    }
+}
@@ -0,0 +1,99 @@
+//! Hypothesis tests and confidence intervals on released counts. Both only read the noised
+//! histogram, so they are post-processing and cost no privacy beyond the release; what they
+//! have to account for is the noise, which widens the spread of every count beyond the
+//! sampling error (Gaboardi, Lim, Rogers, Vadhan, ICML 2016). A test that ignores it
+//! rejects far too often, an interval that ignores it covers too rarely.
+
+use statrs::function::beta::beta_reg;
+
+use crate::accounting::normal_cdf;
+
+const QUANTILE_BISECTIONS: usize = 60;
+
+pub struct HypothesisTestResult {
+    pub z: f64,
+    /// Two-sided p-value with the noise variance in the standard error.
+    pub p_value: f64,
+    /// Two-sided p-value as if the counts were exact.
+    pub naive_p_value: f64,
+    pub reject: bool,
+}
+
+/// Two-sided z-test of `H0: p_a = p_b` for the noised counts `a` and `b` of two buckets of
+/// one histogram, each with independent noise of variance `noise_variance`. Under the null
+/// `a - b` has sampling variance about `a + b` and noise variance `2 noise_variance`. The
+/// noise is not normal for Laplace, so the p-value is the normal approximation of both.
+pub fn noisy_counts_z_test(a: f64, b: f64, noise_variance: f64, alpha: f64) -> HypothesisTestResult {
+    let sampling_variance = (a + b).max(0.0);
+    let two_sided = |variance: f64| {
+        if variance > 0.0 { 2.0 * (1.0 - normal_cdf((a - b).abs() / variance.sqrt())) } else { 1.0 }
+    };
+    let variance = sampling_variance + 2.0 * noise_variance;
+    let p_value = two_sided(variance);
+    HypothesisTestResult {
+        z: if variance > 0.0 { (a - b) / variance.sqrt() } else { 0.0 },
+        p_value,
+        naive_p_value: two_sided(sampling_variance),
+        reject: p_value < alpha,
+    }
+}
+
+/// `x` with `I_x(a, b) = q` for the regularized incomplete beta function, the `q` quantile
+/// of `Beta(a, b)`, by bisection.
+fn beta_quantile(a: f64, b: f64, q: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..QUANTILE_BISECTIONS {
+        let mid = (low + high) / 2.0;
+        if beta_reg(a, b, mid) < q { low = mid } else { high = mid }
+    }
+    (low + high) / 2.0
+}
+
+/// Clopper-Pearson interval of level `1 - alpha` for the proportion behind `x` successes in
+/// `n` trials, from the `alpha / 2` and `1 - alpha / 2` quantiles of `Beta(x, n - x + 1)`
+/// and `Beta(x + 1, n - x)`. Exact in that it never covers less than `1 - alpha`.
+pub fn clopper_pearson(x: f64, n: f64, alpha: f64) -> (f64, f64) {
+    let x = x.clamp(0.0, n);
+    let lower = if x > 0.0 { beta_quantile(x, n - x + 1.0, alpha / 2.0) } else { 0.0 };
+    let upper = if x < n { beta_quantile(x + 1.0, n - x, 1.0 - alpha / 2.0) } else { 1.0 };
+    (lower, upper)
+}
+
+/// Interval of level `1 - alpha` for the proportion `count / n` of a count released with
+/// Laplace noise of variance `noise_variance`. Half of `alpha` goes to the noise: the true
+/// count lies within `b ln(2 / alpha)` of `noised_count` with probability `1 - alpha / 2`
+/// for the Laplace scale `b = sqrt(noise_variance / 2)`, and the Clopper-Pearson interval at
+/// `alpha / 2` is taken from the lowest and the highest count in that range. By the union
+/// bound it covers at least `1 - alpha`, wider than either part alone.
+pub fn noised_proportion_ci(noised_count: u64, n: u64, noise_variance: f64, alpha: f64) -> (f64, f64) {
+    let margin = (noise_variance / 2.0).sqrt() * (2.0 / alpha).ln();
+    let (count, n) = (noised_count as f64, n as f64);
+    (clopper_pearson(count - margin, n, alpha / 2.0).0, clopper_pearson(count + margin, n, alpha / 2.0).1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_widens_the_standard_error() {
+        // 60 against 40: z = 20 / sqrt(100) = 2 without noise, 20 / sqrt(400) = 1 with
+        let result = noisy_counts_z_test(60.0, 40.0, 150.0, 0.05);
+        assert!((result.z - 1.0).abs() < 1e-12);
+        assert!((result.p_value - 0.3173).abs() < 1e-4);
+        assert!((result.naive_p_value - 0.0455).abs() < 1e-4);
+        assert!(!result.reject);
+        assert!(noisy_counts_z_test(60.0, 40.0, 0.0, 0.05).reject);
+    }
+
+    #[test]
+    fn clopper_pearson_matches_the_tables() {
+        // 3 of 10 at 95%: (0.0667, 0.6525)
+        let (lower, upper) = clopper_pearson(3.0, 10.0, 0.05);
+        assert!((lower - 0.06674).abs() < 1e-4 && (upper - 0.65245).abs() < 1e-4);
+        assert_eq!(clopper_pearson(0.0, 10.0, 0.05).0, 0.0);
+        // the noise only ever widens it
+        let (noised_lower, noised_upper) = noised_proportion_ci(3, 10, 2.0, 0.05);
+        assert!(noised_lower < lower && noised_upper > upper);
+    }
+}
@@ -0,0 +1,53 @@
+//! Inverse sensitivity mechanism (Asi, Duchi, NeurIPS 2020) for the median: every candidate
+//! output `t` is scored by `len(t)`, the number of records that would have to change before
+//! the median is `t`, and sampled with probability proportional to `e^(-ε len(t) / 2)`.
+//! `len` moves by at most one between neighbours whatever the range of the field, so unlike
+//! Laplace noise on the median nothing is calibrated to `upper - lower`.
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+/// ε-DP median of `sorted` values in `[lower, upper]`. Between two neighbouring values all
+/// candidates share one `len`, how many values lie between them and the median `sorted[m]`,
+/// so the intervals on either side of `sorted[m]` both score zero; an interval is picked
+/// with weight `width * e^(-ε len / 2)` and a point drawn uniformly inside it.
+pub fn inverse_sensitivity_median<R: Rng>(sorted: &[f64], (lower, upper): (f64, f64), epsilon: f64, rng: &mut R) -> f64 {
+    let n = sorted.len();
+    let median_rank = (n.max(1) - 1) / 2;
+    let ends = std::iter::once(lower).chain(sorted.iter().copied()).chain(std::iter::once(upper)).collect::<Vec<_>>();
+    // weights in logs, an interval far from the median would underflow otherwise
+    let log_weights = ends.windows(2).enumerate()
+        .map(|(rank, pair)| {
+            // interval `rank` has `rank` values below it, the median is the one at index m
+            let len = if rank <= median_rank { median_rank - rank } else { rank - median_rank - 1 };
+            (pair[1] - pair[0]).ln() - epsilon * len as f64 / 2.0
+        })
+        .collect::<Vec<_>>();
+    let max_log_weight = log_weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max_log_weight == f64::NEG_INFINITY {
+        // every value sits on the bounds and there is nothing to choose from
+        return lower;
+    }
+    let weights = log_weights.iter().map(|log_weight| (log_weight - max_log_weight).exp());
+    let rank = WeightedIndex::new(weights).unwrap().sample(rng);
+    rng.gen_range(ends[rank]..=ends[rank + 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_epsilon_lands_evenly_around_the_median() {
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut rng = rand::thread_rng();
+        let medians = (0..1000)
+            .map(|_| inverse_sensitivity_median(&sorted, (0.0, 10.0), 100.0, &mut rng))
+            .collect::<Vec<_>>();
+        assert!(medians.iter().all(|median| (2.0..=4.0).contains(median)));
+        // the intervals below and above the true median 3 are equally likely
+        let below = medians.iter().filter(|&&median| median < 3.0).count();
+        assert!((400..=600).contains(&below));
+        assert_eq!(inverse_sensitivity_median(&[], (0.0, 0.0), 1.0, &mut rng), 0.0);
+    }
+}
@@ -0,0 +1,102 @@
+//! Privacy nutrition label in the manner of Kelley, Bresee, Cranor and Reeder (SOUPS 2009):
+//! the facts of the current release in a fixed order under fixed headings, so that two
+//! configurations can be read side by side without knowing the tool.
+
+use crate::report_card::privacy_grade;
+
+const RULE_WIDTH: usize = 48;
+
+pub struct NutritionLabel {
+    pub mechanism: String,
+    pub epsilon: f64,
+    pub delta: f64,
+    pub sensitivity: f64,
+    pub query: String,
+    pub data: String,
+    /// Error no bucket exceeds with probability `1 - alpha`.
+    pub error_bound: f64,
+    pub alpha: f64,
+}
+
+/// What epsilon and delta promise in plain words: the most any one person can shift the odds
+/// of an outcome, and how sure that leaves an attacker who started at even odds on whether
+/// the person is in the data.
+pub fn risk_description(epsilon: f64, delta: f64) -> String {
+    if epsilon.is_infinite() {
+        return String::from("No protection: the release is exact and can reveal whether anyone is in the data.");
+    }
+    let factor = epsilon.exp();
+    let strength = match privacy_grade(epsilon) {
+        "A+" | "A" => "Strong",
+        "B" => "Moderate",
+        _ => "Weak",
+    };
+    let exception = if delta > 0.0 { format!(" except with probability {:.0e}", delta) } else { String::new() };
+    format!("{} protection. Adding or removing any one person changes the chance of any outcome by at most \
+             {:.2}×{}, so an attacker who thought it a coin flip whether they are in the data can be at most {:.0}% sure afterwards.",
+            strength, factor, exception, 100.0 * factor / (1.0 + factor))
+}
+
+impl NutritionLabel {
+    pub fn generate_nutrition_label(&self) -> String {
+        let rule = "─".repeat(RULE_WIDTH);
+        let facts = [
+            ("Mechanism", self.mechanism.clone()),
+            ("Query", self.query.clone()),
+            ("Data", self.data.clone()),
+        ];
+        let guarantee = [
+            ("Epsilon (ε)", format!("{:.3}", self.epsilon)),
+            ("Delta (δ)", if self.delta > 0.0 { format!("{:.1e}", self.delta) } else { String::from("0 (pure DP)") }),
+            ("Sensitivity (Δ)", format!("{}", self.sensitivity)),
+        ];
+        let row = |(name, value): &(&str, String)| format!("{:<18}{}", name, value);
+        let mut lines = vec![String::from("Privacy Facts"), "━".repeat(RULE_WIDTH)];
+        lines.extend(facts.iter().map(row));
+        lines.push(rule.clone());
+        lines.extend(guarantee.iter().map(row));
+        lines.push(rule.clone());
+        lines.push(row(&("Accuracy", format!("every bucket within ±{:.1} with probability {:.0}%",
+                                              self.error_bound, 100.0 * (1.0 - self.alpha)))));
+        lines.push(rule);
+        lines.push(format!("Risk: {}", risk_description(self.epsilon, self.delta)));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(epsilon: f64, delta: f64) -> NutritionLabel {
+        NutritionLabel {
+            mechanism: String::from("Laplace"),
+            epsilon,
+            delta,
+            sensitivity: 1.0,
+            query: String::from("Histogram of educ, noised counts"),
+            data: String::from("All records, 21 buckets"),
+            error_bound: 12.5,
+            alpha: 0.05,
+        }
+    }
+
+    #[test]
+    fn label_lists_the_facts_in_a_fixed_order() {
+        let text = label(1.0, 0.0).generate_nutrition_label();
+        let headings = ["Privacy Facts", "Mechanism", "Query", "Data", "Epsilon (ε)", "Delta (δ)", "Sensitivity (Δ)", "Accuracy", "Risk:"];
+        let positions = headings.iter().map(|heading| text.find(heading).unwrap()).collect::<Vec<_>>();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(text.contains("0 (pure DP)") && !text.contains("except with probability"));
+        assert!(label(1.0, 1e-6).generate_nutrition_label().contains("1.0e-6"));
+    }
+
+    #[test]
+    fn risk_description_bounds_the_attacker() {
+        // e^1 = 2.72, and an attacker at even odds ends up at most 73% sure
+        let risk = risk_description(1.0, 0.0);
+        assert!(risk.contains("2.72×") && risk.contains("73% sure"));
+        assert!(risk_description(1.0, 1e-6).contains("except with probability 1e-6"));
+        assert!(risk_description(f64::INFINITY, 0.0).starts_with("No protection"));
+    }
+}
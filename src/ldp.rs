@@ -0,0 +1,123 @@
+//! Local differential privacy: every respondent noises their own answer before it leaves
+//! their hands, so no curator ever sees the true data.
+
+use rand::Rng;
+
+/// Probability of answering truthfully under epsilon-DP randomized response,
+/// `e^ε / (1 + e^ε)`, written so that an infinite epsilon gives 1 instead of NaN.
+pub fn truthful_probability(epsilon: f64) -> f64 {
+    1.0 / (1.0 + (-epsilon).exp())
+}
+
+/// Randomized response (Warner, 1965): each respondent reports their true answer with
+/// probability `p = truthful_probability(epsilon)` and the opposite one otherwise. The
+/// number of reported "yes" answers `Y` has mean `p T + (1 - p)(n - T)` for `T` true
+/// "yes" answers out of `n`, which gives the unbiased estimate
+/// `T = (Y - (1 - p) n) / (2p - 1)`.
+pub fn randomized_response_count<R: Rng>(answers: &[bool], epsilon: f64, rng: &mut R) -> f64 {
+    let p = truthful_probability(epsilon);
+    let n = answers.len() as f64;
+    if p == 0.5 {
+        // the reports carry no information at all
+        return n / 2.0;
+    }
+    let reported_yes = answers.iter()
+        .filter(|&&answer| if rng.gen_bool(p) { answer } else { !answer })
+        .count() as f64;
+    (reported_yes - (1.0 - p) * n) / (2.0 * p - 1.0)
+}
+
+/// Probability `e^ε / (e^ε + k - 1)` that generalized randomized response over `k` values
+/// reports the respondent's own one.
+fn own_value_probability(epsilon: f64, k: usize) -> f64 {
+    1.0 / (1.0 + k.saturating_sub(1) as f64 * (-epsilon).exp())
+}
+
+/// `value` itself with `own_value_probability`, any other of the `domain` values otherwise.
+fn randomized_value<R: Rng>(value: usize, domain: usize, epsilon: f64, rng: &mut R) -> usize {
+    if domain < 2 || rng.gen_bool(own_value_probability(epsilon, domain)) {
+        return value;
+    }
+    let other = rng.gen_range(0..domain - 1);
+    if other >= value { other + 1 } else { other }
+}
+
+/// Generalized randomized response over `domain` values: each respondent reports their own
+/// value with probability `p = e^ε / (e^ε + k - 1)` and each other one with
+/// `q = 1 / (e^ε + k - 1)`. A value held by `T` of the `n` respondents is reported about
+/// `p T + q (n - T)` times, which gives the estimate `(C - n q) / (p - q)`. Its variance
+/// grows with the size of the domain.
+pub fn generalized_randomized_response<R: Rng>(values: &[usize], domain: usize, epsilon: f64, rng: &mut R) -> Vec<f64> {
+    let mut reported = vec![0.0; domain];
+    for value in randomized_values(values, domain, epsilon, rng) {
+        reported[value] += 1.0;
+    }
+    debiased_counts(&reported, values.len(), domain, epsilon)
+}
+
+/// Every one of `values` through `randomized_value`, the reports of generalized randomized
+/// response before any estimate is made from them.
+pub fn randomized_values<R: Rng>(values: &[usize], domain: usize, epsilon: f64, rng: &mut R) -> Vec<usize> {
+    values.iter().map(|&value| randomized_value(value, domain, epsilon, rng)).collect()
+}
+
+/// `(C - n q) / (p - q)` for the counts `reported` out of `n` reports of generalized
+/// randomized response over `domain` values.
+pub fn debiased_counts(reported: &[f64], n: usize, domain: usize, epsilon: f64) -> Vec<f64> {
+    let p = own_value_probability(epsilon, domain);
+    let q = (1.0 - p) / domain.saturating_sub(1).max(1) as f64;
+    reported.iter().map(|count| (count - n as f64 * q) / (p - q)).collect()
+}
+
+/// Hash range of optimized local hashing, `e^ε + 1` rounded: the `g` that minimizes the
+/// variance of `local_hashing`.
+pub fn optimal_hash_range(epsilon: f64) -> usize {
+    (epsilon.exp() + 1.0).round().max(2.0) as usize
+}
+
+/// `value` hashed into `0..g` by the function of `seed` (a splitmix64 finalizer).
+fn seeded_hash(seed: u64, value: usize, g: usize) -> usize {
+    let mut z = seed ^ (value as u64).wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    ((z ^ (z >> 31)) % g as u64) as usize
+}
+
+/// Local hashing (Wang, Blocki, Li, Jha, USENIX Security 2017): each respondent draws a hash
+/// function into `g` values, hashes their value and reports the hash by generalized
+/// randomized response over the `g` values, together with the function. A value `i` is
+/// supported by a report when it hashes to the reported value, which happens with
+/// `p_1 = e^ε / (e^ε + g - 1)` for the respondent's own value and `p_0 = 1 / g` for any
+/// other, so `(C_i - n p_0) / (p_1 - p_0)` estimates its count whatever the size of the domain.
+pub fn local_hashing<R: Rng>(values: &[usize], domain: usize, g: usize, epsilon: f64, rng: &mut R) -> Vec<f64> {
+    let reports = values.iter()
+        .map(|&value| {
+            let seed = rng.gen::<u64>();
+            (seed, randomized_value(seeded_hash(seed, value, g), g, epsilon, rng))
+        })
+        .collect::<Vec<_>>();
+    let (p_1, p_0) = (own_value_probability(epsilon, g), 1.0 / g as f64);
+    let n = values.len() as f64;
+    (0..domain)
+        .map(|i| {
+            let support = reports.iter().filter(|&&(seed, hashed)| seeded_hash(seed, i, g) == hashed).count() as f64;
+            (support - n * p_0) / (p_1 - p_0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_oracles_are_exact_or_unbiased_without_noise() {
+        let mut rng = rand::thread_rng();
+        let values = [vec![0; 1500], vec![2; 500]].concat();
+        assert_eq!(generalized_randomized_response(&values, 4, 50.0, &mut rng), vec![1500.0, 0.0, 500.0, 0.0]);
+        // without noise only the collisions of the hashes are left, p_0 = 1/2 in expectation
+        let estimates = local_hashing(&values, 4, 2, 50.0, &mut rng);
+        assert!(estimates.iter().zip([1500.0, 0.0, 500.0, 0.0]).all(|(estimate, count)| (estimate - count).abs() < 300.0));
+        assert_eq!(optimal_hash_range(2f64.ln()), 3);
+    }
+}
@@ -1,14 +1,17 @@
 #![warn(unused_extern_crates)]
+use std::env;
 use std::error::Error;
 use std::fs;
 use std::io;
 use std::io::Stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
 
 use crossterm::{
-    event::{self, Event as CEvent, KeyCode},
+    event::{self, Event as CEvent, KeyCode, KeyModifiers},
     terminal::{disable_raw_mode, enable_raw_mode},
 };
 use tui::{
@@ -17,18 +20,66 @@ use tui::{
     style::{Color, Modifier, Style}, Terminal,
     text::{Span, Spans},
     widgets::{
-        Block, Borders, Paragraph, Tabs,
+        Block, Borders, Cell, Paragraph, Row, Table, Tabs,
     }};
 use tui::layout::Rect;
-use tui::widgets::{BarChart, Wrap};
+use tui::symbols;
+use tui::widgets::{Axis, BarChart, Chart, Dataset, GraphType, Wrap};
 
+use crate::accounting::PrivacyLoss;
+use crate::app::{App, View};
+use crate::cli::parse_args;
+use crate::clustering::{cluster_sizes, kmeans};
+use crate::csv_reader::read_data;
 use crate::dataset::CsvDataSet;
-use crate::noiser::{NoiseApplier, Noiser};
+use crate::noiser::{recommend_mechanism, NoiseApplier, NoiseTarget, NoiseType, Noiser, range_query, CONTAMINATION_FRACTION, DP_GD_STEPS, FTRL_ROUNDS, PRIVATE_COUNT_SHARE, SVT_MAX_ABOVE, TOP_K};
+use crate::privacy_meter::PrivacyMeter;
+use crate::query::QueryType;
+use crate::styled_bar_chart::StyledBarChart;
+use crate::tutorial::{tutorial_steps, TutorialStep};
 
 mod noiser;
+mod odometer;
+mod pca;
 mod dataset;
+mod mechanisms;
+mod csv_reader;
+mod clustering;
+mod privacy_meter;
+mod cli;
+mod continual;
+mod correlated;
+mod app;
+mod styled_bar_chart;
+mod accounting;
+mod metrics;
+mod error;
+mod federated;
+mod hierarchical;
+mod hypothesis;
+mod inverse_sensitivity;
+mod label;
+mod ldp;
+mod matrix_mechanism;
+mod postprocess;
+mod query;
+mod random_projection;
+mod release;
+mod report_card;
+mod smooth_sensitivity;
+mod tutorial;
+mod wavelet;
 
-const CSV_FILE_PATH: &'static str = "data/data.csv";
+const CSV_FILE_PATH: &str = "data/data.csv";
+const CLUSTER_COUNT: usize = 4;
+const CLUSTER_SEED: u64 = 42;
+const CLUSTER_MAX_ITER: usize = 100;
+const MAX_DISPLAYED_EPSILON: f64 = 10.0;
+const AMPLIFICATION_ITERATIONS: usize = 50;
+/// Probability the approximate max-information bound may fail.
+const MAX_INFORMATION_BETA: f64 = 0.05;
+/// Significance level of the z-test between the two buckets of the range query.
+const HYPOTHESIS_ALPHA: f64 = 0.05;
 
 enum Event<I> {
     Input(I),
@@ -36,28 +87,55 @@ enum Event<I> {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let cli_args = parse_args(env::args().skip(1))?;
     let education_sensitive_field_to_aggregate: String = String::from("educ");
     let income_sensitive_field_to_aggregate: String = String::from("income");
 
-    let contents = fs::read_to_string(CSV_FILE_PATH)?;
-    // Skip headers and then rejoin the CSV
-    let contents = contents.split("\n").skip(1)
-        .map(|x| x.to_string())
-        .collect::<Vec<String>>().join("\n");
-
-    let dataset = CsvDataSet {
-        data: &contents
+    let dataset = match &cli_args.parquet {
+        Some(path) => CsvDataSet::from_parquet(path)?,
+        None => {
+            let contents = fs::read_to_string(CSV_FILE_PATH)?;
+            // Skip headers and then rejoin the CSV
+            let contents = contents.split("\n").skip(1)
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>().join("\n");
+            CsvDataSet {
+                data: contents,
+                separator: cli_args.separator,
+            }
+        }
     };
+    if let Some(path) = &cli_args.export_parquet {
+        dataset.to_parquet(path)?;
+        return Ok(());
+    }
     let aggregate_field = &education_sensitive_field_to_aggregate;
-    let mut noiser = Noiser::new(&dataset, aggregate_field);
+    let mut noiser = match &cli_args.config {
+        Some(path) => Noiser::from_config(path, &dataset)?,
+        None => Noiser::new(&dataset, aggregate_field),
+    };
+    if let Some(samples) = cli_args.jackknife_samples {
+        noiser.jackknife_samples = samples;
+    }
     noiser.refresh_data();
-    let aggregate_buckets = dataset.aggregate_buckets(aggregate_field);
+    let mut aggregate_buckets = dataset.aggregate_buckets(noiser.aggregate_field);
+
+    // Clusters are computed once on the raw records, independent of the aggregated field
+    let records = read_data(&dataset.data, dataset.separator)?;
+    let clusters = cluster_sizes(&kmeans(&records, CLUSTER_COUNT, CLUSTER_SEED, CLUSTER_MAX_ITER),
+                                 CLUSTER_COUNT);
 
     /*
     Start of UI related code
      */
     enable_raw_mode().expect("can run in raw mode");
 
+    // Raw mode delivers Ctrl+C as a key press, but a SIGINT can still come from outside
+    // (e.g. `kill -INT`); either way the terminal has to be restored before exiting.
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler_flag = interrupted.clone();
+    ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst))?;
+
     let (tx, rx) = mpsc::channel();
     let tick_rate = Duration::from_millis(200);
     thread::spawn(move || {
@@ -73,10 +151,8 @@ fn main() -> Result<(), Box<dyn Error>> {
                 }
             }
 
-            if last_tick.elapsed() >= tick_rate {
-                if let Ok(_) = tx.send(Event::Tick) {
-                    last_tick = Instant::now();
-                }
+            if last_tick.elapsed() >= tick_rate && tx.send(Event::Tick).is_ok() {
+                last_tick = Instant::now();
             }
         }
     });
@@ -86,34 +162,191 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let menu_titles = vec!["Noise Type", "Increase Noise", "Decrease Noise", "Switch Field", "Quit"];
+    let menu_titles = vec!["Noise Type", "Increase Noise", "Decrease Noise", "Switch Field", "Frequencies", "Aggregate Query", "Book Queries", "Multi Stats", "Estimate Median", "Uncertainty (bootstrap)", "Condition", "K Suppression", "Projection", "Regression (isotonic)", "Vector Split (SVT)", "Gradient Clip", "Log", "Order (RDP)", "Target δ", "Window (range query)", "/ Search", "Tab View", "Quit"];
+    let mut app = App::new();
+    if noiser.budget.is_some() {
+        // plan the split before the first query spends anything
+        app.view = View::BudgetPlan;
+    }
+    let tutorial = tutorial_steps();
+    if cli_args.tutorial {
+        app.tutorial_step = Some(0);
+        tutorial[0].apply(&mut noiser);
+    }
 
     loop {
+        if interrupted.load(Ordering::SeqCst) {
+            break;
+        }
         terminal.draw(|rect| {
             draw_stuff(&noiser,
+                       &app,
                        &aggregate_buckets,
+                       &clusters,
                        &menu_titles,
+                       app.tutorial_step.map(|step| &tutorial[step]),
                        rect);
         })?;
 
         match rx.recv()? {
+            Event::Input(event) if app.search_mode => match event.code {
+                KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Esc => app.clear_search(),
+                KeyCode::Enter => {
+                    app.search_mode = false;
+                    app.log_event(format!("Searched buckets for '{}'", app.search_query));
+                }
+                KeyCode::Backspace => {
+                    app.search_query.pop();
+                }
+                KeyCode::Char(c) => app.search_query.push(c),
+                _ => {}
+            },
+            Event::Input(event) if app.range_mode => match event.code {
+                KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Esc => app.cancel_range_entry(),
+                KeyCode::Enter => {
+                    app.finish_range_entry();
+                    app.log_event(format!("Asked for the range '{}'", app.range_input));
+                }
+                KeyCode::Backspace => {
+                    app.range_input.pop();
+                }
+                KeyCode::Char(c) => app.range_input.push(c),
+                _ => {}
+            },
+            Event::Input(event) if app.tutorial_step.is_some()
+                && matches!(event.code, KeyCode::Char(' ') | KeyCode::Enter) => {
+                app.next_tutorial_step(tutorial.len());
+                match app.tutorial_step {
+                    Some(step) => {
+                        tutorial[step].apply(&mut noiser);
+                        app.log_event(format!("Tutorial: {}", tutorial[step].title));
+                    }
+                    None => app.log_event(String::from("Finished the tutorial")),
+                }
+            }
             Event::Input(event) => match event.code {
-                KeyCode::Char('q') => {
-                    disable_raw_mode()?;
-                    terminal.show_cursor()?;
-                    break;
+                KeyCode::Char('q') => break,
+                KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Char('r') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    noiser.reset();
+                    app.log_event(String::from("Reset noise parameters"));
                 }
                 KeyCode::Char('n') => {
                     noiser.toggle_noise_type();
+                    app.log_event(format!("Switched to {}", noiser.noise_type));
                 }
                 KeyCode::Char('i') => {
                     noiser.increase_noise();
+                    app.log_event(format!("Increased accuracy to {}", noiser.accuracy));
                 }
                 KeyCode::Char('d') => {
                     noiser.decrease_noise();
+                    app.log_event(format!("Decreased accuracy to {}", noiser.accuracy));
+                }
+                KeyCode::Char('f') => {
+                    noiser.toggle_noise_target();
+                    app.log_event(format!("Switched target to {}", noiser.noise_target));
+                }
+                KeyCode::Char('a') => {
+                    noiser.toggle_query_type();
+                    app.log_event(format!("Switched query to {}", noiser.query_type));
+                }
+                KeyCode::Char('b') => {
+                    noiser.toggle_precommit();
+                    app.log_event(match noiser.odometer.commitment {
+                        Some(commitment) => format!("Reserved ε = {:.2} for the next {} releases",
+                                                    commitment.reserved(), commitment.queries),
+                        None => String::from("Dropped the reserved budget"),
+                    });
+                }
+                KeyCode::Char('m') => {
+                    noiser.toggle_multi_stat();
+                    app.log_event(format!("Switched statistics to {}", noiser.multi_stat));
+                }
+                KeyCode::Char('e') => {
+                    noiser.toggle_median_comparison();
+                    app.log_event(String::from(if noiser.median_comparison {
+                        "Comparing private medians"
+                    } else {
+                        "Stopped comparing private medians"
+                    }));
+                }
+                KeyCode::Char('u') => {
+                    noiser.toggle_bootstrap();
+                    app.log_event(String::from(if noiser.show_bootstrap {
+                        "Showing bootstrap intervals of the true counts"
+                    } else {
+                        "Hid bootstrap intervals"
+                    }));
+                }
+                KeyCode::Char('c') => {
+                    noiser.toggle_condition();
+                    app.log_event(match noiser.condition {
+                        Some((field, value)) => format!("Conditioned on {} = {}", field, value),
+                        None => String::from("Cleared condition"),
+                    });
+                }
+                KeyCode::Char('k') => {
+                    noiser.toggle_suppression();
+                    app.log_event(match noiser.suppression_k {
+                        Some(k) => format!("Suppressing buckets below {}", k),
+                        None => String::from("Stopped suppressing buckets"),
+                    });
+                }
+                KeyCode::Char('p') => {
+                    noiser.toggle_consistency();
+                    app.log_event(String::from(if noiser.consistency {
+                        "Projecting counts onto the true total"
+                    } else {
+                        "Stopped projecting counts"
+                    }));
+                }
+                KeyCode::Char('r') => {
+                    noiser.toggle_isotonic();
+                    app.log_event(String::from(if noiser.isotonic {
+                        "Making CDF totals non-decreasing"
+                    } else {
+                        "Left CDF totals as noised"
+                    }));
+                }
+                KeyCode::Char('v') => {
+                    noiser.toggle_svt_split();
+                    app.log_event(match noiser.svt_threshold_share {
+                        Some(share) => format!("SVT threshold gets {:.0}% of epsilon", share * 100.0),
+                        None => String::from("SVT split back to optimal"),
+                    });
+                }
+                KeyCode::Char('g') => {
+                    noiser.toggle_clip_norm();
+                    app.log_event(format!("Switched clip norm to {}", noiser.clip_norm));
+                }
+                KeyCode::Char('o') => {
+                    app.next_rdp_order();
+                    app.log_event(format!("Switched RDP order to {}", app.rdp_order));
+                }
+                KeyCode::Char('l') | KeyCode::Char('L') => {
+                    app.toggle_event_log();
+                }
+                KeyCode::Char('/') => {
+                    app.start_search();
+                }
+                KeyCode::Char('t') => {
+                    app.next_target_delta();
+                    app.log_event(format!("Reading ε off the (ε, δ) curve at δ = {:.0e}", app.target_delta));
+                }
+                KeyCode::Char('w') => {
+                    app.start_range_entry();
+                }
+                KeyCode::Tab => {
+                    app.next_view();
+                }
+                KeyCode::Esc => {
+                    app.clear_search();
                 }
                 KeyCode::Char('s') => {
-                    match noiser.aggregate_field.as_str() {
+                    match noiser.aggregate_field {
                         "educ"=> {
                             noiser.aggregate_field = &income_sensitive_field_to_aggregate;
                         },
@@ -122,30 +355,66 @@ fn main() -> Result<(), Box<dyn Error>> {
                         },
                         _ => {}
                     }
-                    noiser.accuracy = 0;
-                    noiser.refresh_data();
+                    aggregate_buckets = dataset.aggregate_buckets(noiser.aggregate_field);
+                    noiser.reset_accuracy();
+                    app.log_event(format!("Switched field to {}", noiser.aggregate_field));
                 }
                 _ => {}
             },
             Event::Tick => {}
         }
     }
+    disable_raw_mode()?;
+    terminal.show_cursor()?;
 
     Ok(())
 }
 
 fn draw_stuff(noiser: &Noiser,
-              aggregate_buckets: &Vec<String>,
-              menu_titles: &Vec<&str>,
+              app: &App,
+              aggregate_buckets: &[String],
+              clusters: &[u64],
+              menu_titles: &[&str],
+              tutorial_step: Option<&TutorialStep>,
               rect: &mut Frame<CrosstermBackend<Stdout>>,
 ) {
     let size = rect.size();
+    let mut constraints = vec![Constraint::Percentage(30), Constraint::Min(0)];
+    if app.has_input_box() {
+        constraints.push(Constraint::Length(3));
+    }
+    if tutorial_step.is_some() {
+        constraints.push(Constraint::Percentage(30));
+    }
+    if app.show_event_log {
+        constraints.push(Constraint::Percentage(20));
+    }
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
+        .constraints(constraints)
         .split(size);
 
+    if app.range_mode {
+        let range = Paragraph::new(app.range_input.as_str())
+            .block(Block::default()
+                .title("Range of bucket indices from 0, e.g. 2-7 (Enter to answer from the private CDF, Esc to cancel)")
+                .borders(Borders::ALL))
+            .style(Style::default().fg(Color::Yellow));
+        rect.render_widget(range, chunks[2]);
+    } else if app.is_searching() {
+        let search = Paragraph::new(format!("/{}", app.search_query))
+            .block(Block::default().title("Search buckets (Enter to keep, Esc to clear)").borders(Borders::ALL))
+            .style(Style::default().fg(if app.search_mode { Color::Yellow } else { Color::DarkGray }));
+        rect.render_widget(search, chunks[2]);
+    }
+    if let Some(step) = tutorial_step {
+        draw_tutorial(step, rect, chunks[if app.has_input_box() { 3 } else { 2 }]);
+    }
+    if app.show_event_log {
+        draw_event_log(app, rect, chunks[chunks.len() - 1]);
+    }
+
     let header_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(
@@ -173,74 +442,1075 @@ fn draw_stuff(noiser: &Noiser,
 
     rect.render_widget(tabs, header_chunks[0]);
 
-    let noise_params = noise_params(noiser);
-    let noise_block = Paragraph::new(noise_params)
-        .block(Block::default().title("Noise Params").borders(Borders::ALL))
+    let noise_block = Block::default().title("Noise Params").borders(Borders::ALL);
+    let noise_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(noise_block.inner(header_chunks[1]));
+    rect.render_widget(noise_block, header_chunks[1]);
+
+    let noise_params = noise_params(noiser, app);
+    let noise_paragraph = Paragraph::new(noise_params)
         .style(Style::default().fg(Color::Green))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
-    rect.render_widget(noise_block, header_chunks[1]);
+    rect.render_widget(noise_paragraph, noise_chunks[0]);
+
+    let privacy_meter = PrivacyMeter {
+        epsilon: noiser.epsilon(),
+        max_displayed_epsilon: MAX_DISPLAYED_EPSILON,
+    };
+    rect.render_widget(privacy_meter, noise_chunks[1]);
+
+    match app.view {
+        View::Histogram => draw_graphs(noiser, app, aggregate_buckets, clusters, rect, chunks),
+        View::TradeOff => draw_tradeoff(noiser, rect, chunks[1]),
+        View::Releases => draw_releases(noiser, rect, chunks[1]),
+        View::PrivacyLoss => draw_privacy_loss(noiser, app, rect, chunks[1]),
+        View::SyntheticTwin => draw_synthetic_twin(noiser, aggregate_buckets, rect, chunks[1]),
+        View::Federated => draw_federated(noiser, app, aggregate_buckets, rect, chunks[1]),
+        View::GradientDescent => {
+            let halves = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(chunks[1]);
+            draw_gradient_descent(noiser, rect, halves[0]);
+            draw_continual_release(noiser, rect, halves[1]);
+        }
+        View::Projection => draw_projection(noiser, rect, chunks[1]),
+        View::Pca => draw_pca(noiser, rect, chunks[1]),
+        View::BudgetPlan => draw_budget_plan(noiser, rect, chunks[1]),
+        View::NutritionLabel => draw_nutrition_label(noiser, rect, chunks[1]),
+    }
+}
+
+fn draw_tradeoff(noiser: &Noiser,
+                 rect: &mut Frame<CrosstermBackend<Stdout>>,
+                 area: Rect,
+) {
+    // a trade-off of 1 - alpha means no test can tell the neighbouring data sets apart
+    let perfect_privacy = [(0.0, 1.0), (1.0, 0.0)];
+    let datasets = vec![
+        Dataset::default()
+            .name("perfect privacy")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&perfect_privacy),
+        Dataset::default()
+            .name(format!("{} mechanism", noiser.noise_type))
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&noiser.tradeoff),
+    ];
+    let axis_labels = || vec![Span::raw("0"), Span::raw("0.5"), Span::raw("1")];
+    let chart = Chart::new(datasets)
+        .block(Block::default().title("f-DP Trade-off (Dong, Roth, Su 2022)").borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("Type I error")
+            .bounds([0.0, 1.0])
+            .labels(axis_labels()))
+        .y_axis(Axis::default()
+            .title("Type II error")
+            .bounds([0.0, 1.0])
+            .labels(axis_labels()));
+    rect.render_widget(chart, area);
+}
+
+/// Density of the privacy loss, with the CDF at epsilon next to `1 - delta` in the title.
+fn draw_privacy_loss(noiser: &Noiser,
+                     app: &App,
+                     rect: &mut Frame<CrosstermBackend<Stdout>>,
+                     area: Rect,
+) {
+    let Some(privacy_loss) = noiser.privacy_loss() else {
+        let message = Paragraph::new(format!("No closed-form privacy loss for {} at this accuracy", noiser.noise_type))
+            .block(Block::default().title("Privacy Loss").borders(Borders::ALL));
+        rect.render_widget(message, area);
+        return;
+    };
+    let epsilon = noiser.epsilon();
+    let delta = noiser.delta_for_epsilon(epsilon).unwrap_or(0.0);
+    let (lower, upper) = privacy_loss.support();
+    let max_density = noiser.privacy_loss_pdf.iter().map(|&(_, density)| density).fold(0.0, f64::max);
+    let title = match privacy_loss {
+        PrivacyLoss::Laplace { .. } => format!(
+            "Privacy Loss Z (point masses P(Z = ε) = 0.5, P(Z = -ε) = {:.3}); P(Z ≤ ε) = {:.4}, 1 - δ = {:.4}",
+            (-epsilon).exp() / 2.0, privacy_loss.cdf(epsilon), 1.0 - delta),
+        PrivacyLoss::Gaussian { .. } => format!(
+            "Privacy Loss Z; P(Z ≤ ε) = {:.4} ≥ 1 - δ = {:.4}", privacy_loss.cdf(epsilon), 1.0 - delta),
+    };
+    let datasets = vec![
+        Dataset::default()
+            .name(format!("{} mechanism", noiser.noise_type))
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&noiser.privacy_loss_pdf),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("z")
+            .bounds([lower, upper])
+            .labels(vec![Span::raw(format!("{:.2}", lower)), Span::raw(format!("{:.2}", upper))]))
+        .y_axis(Axis::default()
+            .title("density")
+            .bounds([0.0, max_density])
+            .labels(vec![Span::raw("0"), Span::raw(format!("{:.3}", max_density))]));
+    match noiser.epsilon_for_delta(app.target_delta).filter(|_| !noiser.approx_dp_curve.is_empty()) {
+        Some(target_epsilon) => {
+            let loss_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(area);
+            rect.render_widget(chart, loss_chunks[0]);
+            draw_approx_dp_curve(noiser, app.target_delta, target_epsilon, rect, loss_chunks[1]);
+        }
+        None => rect.render_widget(chart, area),
+    }
+}
+
+/// The (ε, δ) pairs the Gaussian noise satisfies, δ on a log scale, with the ε it takes
+/// to get down to `target_delta` marked.
+fn draw_approx_dp_curve(noiser: &Noiser,
+                        target_delta: f64,
+                        target_epsilon: f64,
+                        rect: &mut Frame<CrosstermBackend<Stdout>>,
+                        area: Rect,
+) {
+    let curve = &noiser.approx_dp_curve;
+    let last = curve.last().map_or(1.0, |&(epsilon, _)| epsilon);
+    let (lower, upper) = curve.iter().fold((0.0f64, f64::NEG_INFINITY), |(lower, upper), &(_, log_delta)| {
+        (lower.min(log_delta), upper.max(log_delta))
+    });
+    let target = [(target_epsilon, target_delta.log10())];
+    let datasets = vec![
+        Dataset::default()
+            .name("δ(ε)")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::LightCyan))
+            .data(curve),
+        Dataset::default()
+            .name(format!("δ = {:.0e}", target_delta))
+            .marker(symbols::Marker::Block)
+            .style(Style::default().fg(Color::Red))
+            .data(&target),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+            .title(format!("(ε, δ) curve of the Gaussian noise: δ = {:.0e} needs ε = {:.3} (t changes δ)",
+                           target_delta, target_epsilon))
+            .borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("ε")
+            .bounds([0.0, last])
+            .labels(vec![Span::raw("0"), Span::raw(format!("{:.2}", last))]))
+        .y_axis(Axis::default()
+            .title("log10 δ")
+            .bounds([lower, upper])
+            .labels(vec![Span::raw(format!("{:.0}", lower)), Span::raw(format!("{:.1}", upper))]));
+    rect.render_widget(chart, area);
+}
+
+/// The real histogram above the same histogram over the synthetic twin.
+fn draw_synthetic_twin(noiser: &Noiser,
+                       aggregate_buckets: &[String],
+                       rect: &mut Frame<CrosstermBackend<Stdout>>,
+                       area: Rect,
+) {
+    let twin_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+    let real_data = aggregate_buckets.iter()
+        .zip(noiser.aggregated_data.iter())
+        .map(|(bucket, &count)| (bucket.as_str(), count))
+        .collect::<Vec<_>>();
+    let synthetic_data = aggregate_buckets.iter()
+        .zip(noiser.synthetic_counts.iter())
+        .map(|(bucket, &count)| (bucket.as_str(), count))
+        .collect::<Vec<_>>();
+    let real = StyledBarChart::new(&real_data)
+        .block(Block::default().title("Sensitive Values").borders(Borders::ALL))
+        .bar_width(6)
+        .bar_style(Style::default().fg(Color::Yellow))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+    let synthetic = StyledBarChart::new(&synthetic_data)
+        .block(Block::default()
+            .title(format!("Synthetic Twin (ε = {:.3} split over the 6 column histograms, KL = {:.4} nats, corr(educ, income) {:.3} real vs {:.3} twin)",
+                           noiser.epsilon(), noiser.synthetic_kl, noiser.synthetic_correlation.0, noiser.synthetic_correlation.1))
+            .borders(Borders::ALL))
+        .bar_width(6)
+        .bar_style(Style::default().fg(Color::LightBlue))
+        .value_style(Style::default().fg(Color::Black).bg(Color::LightBlue));
+    rect.render_widget(real, twin_chunks[0]);
+    rect.render_widget(synthetic, twin_chunks[1]);
+}
+
+/// The plain sum of the client histograms next to the clipped and noised one.
+fn draw_federated(noiser: &Noiser,
+                  app: &App,
+                  aggregate_buckets: &[String],
+                  rect: &mut Frame<CrosstermBackend<Stdout>>,
+                  area: Rect,
+) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(area);
+    let federated_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(rows[0]);
+    let unclipped_data = aggregate_buckets.iter()
+        .zip(noiser.aggregated_data.iter())
+        .map(|(bucket, &count)| (bucket.as_str(), count))
+        .collect::<Vec<_>>();
+    let clipped_data = aggregate_buckets.iter()
+        .zip(noiser.federated_sum.iter())
+        .map(|(bucket, &sum)| (bucket.as_str(), sum.round().max(0.0) as u64))
+        .collect::<Vec<_>>();
+    let unclipped = StyledBarChart::new(&unclipped_data)
+        .block(Block::default().title("Unclipped sum over clients").borders(Borders::ALL))
+        .bar_width(4)
+        .bar_style(Style::default().fg(Color::Yellow))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+    let clipped = StyledBarChart::new(&clipped_data)
+        .block(Block::default()
+            .title(format!("Clipped to norm {} + noise ({} clients clipped)", noiser.clip_norm, noiser.federated_clipped))
+            .borders(Borders::ALL))
+        .bar_width(4)
+        .bar_style(Style::default().fg(Color::LightMagenta))
+        .value_style(Style::default().fg(Color::Black).bg(Color::LightMagenta));
+    rect.render_widget(unclipped, federated_chunks[0]);
+    rect.render_widget(clipped, federated_chunks[1]);
+    draw_iteration_amplification(noiser, app.rdp_order, rect, rows[1]);
+}
+
+/// Epsilon of the record used at each step of noisy gradient descent with the noise of the
+/// federated sum, with the last iterate released alone or every iterate released.
+fn draw_iteration_amplification(noiser: &Noiser,
+                                order: f64,
+                                rect: &mut Frame<CrosstermBackend<Stdout>>,
+                                area: Rect,
+) {
+    let amplified = noiser.iteration_amplification(AMPLIFICATION_ITERATIONS, order);
+    let last = amplified.last().map_or(0.0, |&(_, epsilon)| epsilon);
+    let every_iterate = [(1.0, last), (AMPLIFICATION_ITERATIONS as f64, last)];
+    let datasets = vec![
+        Dataset::default()
+            .name("every iterate released")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&every_iterate),
+        Dataset::default()
+            .name("last iterate only")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::LightMagenta))
+            .data(&amplified),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+            .title(format!("Amplification by iteration over {} contractive steps (RDP order {}, δ = {:.0e})",
+                           AMPLIFICATION_ITERATIONS, order, noiser.delta))
+            .borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("Step using the record")
+            .bounds([1.0, AMPLIFICATION_ITERATIONS as f64])
+            .labels(vec![Span::raw("1"), Span::raw(AMPLIFICATION_ITERATIONS.to_string())]))
+        .y_axis(Axis::default()
+            .title("ε")
+            .bounds([0.0, last.max(f64::MIN_POSITIVE)])
+            .labels(vec![Span::raw("0"), Span::raw(format!("{:.2}", last))]));
+    rect.render_widget(chart, area);
+}
+
+/// Learning curve of DP gradient descent on the histogram, MSE on a log scale, with the MSE
+/// of a single Gaussian release at the same (ε, δ) as a flat line.
+fn draw_gradient_descent(noiser: &Noiser,
+                         rect: &mut Frame<CrosstermBackend<Stdout>>,
+                         area: Rect,
+) {
+    let curve = noiser.dp_gd_curve.iter()
+        .map(|&(step, mse)| (step, mse.max(f64::MIN_POSITIVE).log10()))
+        .collect::<Vec<_>>();
+    let one_shot_log = noiser.dp_gd_one_shot_mse.max(f64::MIN_POSITIVE).log10();
+    let one_shot = [(1.0, one_shot_log), (DP_GD_STEPS as f64, one_shot_log)];
+    let (lower, upper) = curve.iter().fold((one_shot_log, one_shot_log), |(lower, upper), &(_, log_mse)| {
+        (lower.min(log_mse), upper.max(log_mse))
+    });
+    let datasets = vec![
+        Dataset::default()
+            .name("one Gaussian release of the counts")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&one_shot),
+        Dataset::default()
+            .name("DP gradient descent")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::LightGreen))
+            .data(&curve),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+            .title(format!("DP gradient descent on the histogram: {} steps at ε = {:.3}, δ = {:.0e} in total, gradients clipped to norm √2",
+                           DP_GD_STEPS, noiser.epsilon(), noiser.delta))
+            .borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("Step")
+            .bounds([1.0, DP_GD_STEPS as f64])
+            .labels(vec![Span::raw("1"), Span::raw(DP_GD_STEPS.to_string())]))
+        .y_axis(Axis::default()
+            .title("MSE (log scale)")
+            .bounds([lower, upper])
+            .labels(vec![Span::raw(format!("{:.1}", 10f64.powf(lower))), Span::raw(format!("{:.0}", 10f64.powf(upper)))]));
+    rect.render_widget(chart, area);
+}
+
+/// MSE per bucket of the running average of the counts over the rounds of arriving records,
+/// log scale, with the tree noise of DP-FTRL against independent noise on every round.
+fn draw_continual_release(noiser: &Noiser,
+                          rect: &mut Frame<CrosstermBackend<Stdout>>,
+                          area: Rect,
+) {
+    let log = |mse: f64| mse.max(f64::MIN_POSITIVE).log10();
+    let tree = noiser.ftrl_errors.iter().map(|&(round, mse, _)| (round, log(mse))).collect::<Vec<_>>();
+    let independent = noiser.ftrl_errors.iter().map(|&(round, _, mse)| (round, log(mse))).collect::<Vec<_>>();
+    let (lower, upper) = tree.iter().chain(&independent).fold((f64::INFINITY, f64::NEG_INFINITY), |(lower, upper), &(_, log_mse)| {
+        (lower.min(log_mse), upper.max(log_mse))
+    });
+    let (lower, upper) = if lower < upper { (lower, upper) } else { (0.0, 1.0) };
+    let datasets = vec![
+        Dataset::default()
+            .name("independent noise every round")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&independent),
+        Dataset::default()
+            .name("tree noise (DP-FTRL)")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::LightCyan))
+            .data(&tree),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+            .title(format!("Running average of the counts as the records arrive over {} rounds, ε = {:.3} per record",
+                           FTRL_ROUNDS, noiser.epsilon()))
+            .borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("Round")
+            .bounds([1.0, FTRL_ROUNDS as f64])
+            .labels(vec![Span::raw("1"), Span::raw(FTRL_ROUNDS.to_string())]))
+        .y_axis(Axis::default()
+            .title("MSE (log scale)")
+            .bounds([lower, upper])
+            .labels(vec![Span::raw(format!("{:.0e}", 10f64.powf(lower))), Span::raw(format!("{:.0e}", 10f64.powf(upper)))]));
+    rect.render_widget(chart, area);
+}
+
+/// MSE per bucket of noise on a random projection against the target dimension, with the
+/// MSE of noise on the counts as a flat line.
+fn draw_projection(noiser: &Noiser,
+                   rect: &mut Frame<CrosstermBackend<Stdout>>,
+                   area: Rect,
+) {
+    let max_dim = noiser.projection_tradeoff.last().map_or(1.0, |&(dim, _)| dim);
+    let max_mse = noiser.projection_tradeoff.iter()
+        .map(|&(_, mse)| mse)
+        .fold(noiser.projection_direct_mse, f64::max)
+        .max(f64::MIN_POSITIVE);
+    let direct = [(0.0, noiser.projection_direct_mse), (max_dim, noiser.projection_direct_mse)];
+    let datasets = vec![
+        Dataset::default()
+            .name("noise on the counts")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&direct),
+        Dataset::default()
+            .name("noise on the projection")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&noiser.projection_tradeoff),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+            .title(format!("Johnson-Lindenstrauss projection of {} buckets (Gaussian noise, ε = {:.3}, δ = {:.0e})",
+                           noiser.aggregated_data.len(), noiser.epsilon(), noiser.delta))
+            .borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("Target dimension")
+            .bounds([0.0, max_dim])
+            .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", max_dim))]))
+        .y_axis(Axis::default()
+            .title("MSE per bucket")
+            .bounds([0.0, max_mse])
+            .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", max_mse))]));
+    rect.render_widget(chart, area);
+}
+
+/// Synthetic records on the top two principal components of the private second moment matrix.
+fn draw_pca(noiser: &Noiser,
+            rect: &mut Frame<CrosstermBackend<Stdout>>,
+            area: Rect,
+) {
+    let bound = |coordinate: fn(&(f64, f64)) -> f64| {
+        noiser.pca_points.iter().map(coordinate).fold((f64::INFINITY, f64::NEG_INFINITY), |(lower, upper), value| {
+            (lower.min(value), upper.max(value))
+        })
+    };
+    let (x_bounds, y_bounds) = if noiser.pca_points.is_empty() {
+        ((-1.0, 1.0), (-1.0, 1.0))
+    } else {
+        (bound(|point| point.0), bound(|point| point.1))
+    };
+    let datasets = vec![
+        Dataset::default()
+            .name("synthetic records")
+            .marker(symbols::Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&noiser.pca_points),
+    ];
+    let angle = noiser.pca_angle.map_or(String::from("no noise"), |angle| format!("first component {:.1}° off the exact one", angle));
+    let chart = Chart::new(datasets)
+        .block(Block::default()
+            .title(format!("Synthetic twin on two principal components found with Laplace noise on the second moment matrix (ε = {:.3}, {})",
+                           noiser.epsilon(), angle))
+            .borders(Borders::ALL))
+        .x_axis(Axis::default()
+            .title("PC 1")
+            .bounds([x_bounds.0, x_bounds.1])
+            .labels(vec![Span::raw(format!("{:.2}", x_bounds.0)), Span::raw(format!("{:.2}", x_bounds.1))]))
+        .y_axis(Axis::default()
+            .title("PC 2")
+            .bounds([y_bounds.0, y_bounds.1])
+            .labels(vec![Span::raw(format!("{:.2}", y_bounds.0)), Span::raw(format!("{:.2}", y_bounds.1))]));
+    rect.render_widget(chart, area);
+}
 
-    draw_graphs(aggregate_buckets, &noiser.aggregated_data, &noiser.noised_data, rect, chunks);
+/// Every histogram released so far, newest first.
+fn draw_releases(noiser: &Noiser,
+                 rect: &mut Frame<CrosstermBackend<Stdout>>,
+                 area: Rect,
+) {
+    let header = Row::new(vec!["Time (UTC)", "Field", "Mechanism", "Epsilon", "Noised counts"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let rows = noiser.release_history().iter().rev()
+        .map(|release| {
+            let counts = release.noised_counts.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ");
+            Row::new(vec![
+                Cell::from(release.time_of_day()),
+                Cell::from(release.field.clone()),
+                Cell::from(release.noise_type.to_string()),
+                Cell::from(format!("{:.3}", release.epsilon)),
+                Cell::from(counts),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().title("Release History").borders(Borders::ALL))
+        .widths(&[
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(24),
+            Constraint::Length(8),
+            Constraint::Min(0),
+        ]);
+    rect.render_widget(table, area);
 }
 
-fn draw_graphs(aggregate_buckets: &Vec<String>,
-               aggregated_data: &Vec<u64>,
-               noised_data: &Vec<u64>,
+/// How a planned workflow of queries would split the budget, evenly or for the least error.
+fn draw_budget_plan(noiser: &Noiser,
+                    rect: &mut Frame<CrosstermBackend<Stdout>>,
+                    area: Rect,
+) {
+    let (total, plan) = noiser.budget_plan();
+    let mse = |sensitivity: f64, epsilon: f64| 2.0 * (sensitivity / epsilon).powi(2);
+    let header = Row::new(vec!["Query", "Sensitivity", "Equal ε", "Laplace MSE", "Least-error ε", "Laplace MSE"])
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let mut rows = plan.iter()
+        .map(|&(query, sensitivity, equal, optimal)| Row::new(vec![
+            Cell::from(query.to_string()),
+            Cell::from(format!("{:.4}", sensitivity)),
+            Cell::from(format!("{:.3}", equal)),
+            Cell::from(format!("{:.1}", mse(sensitivity, equal))),
+            Cell::from(format!("{:.3}", optimal)),
+            Cell::from(format!("{:.1}", mse(sensitivity, optimal))),
+        ]))
+        .collect::<Vec<_>>();
+    let sum = |epsilon: fn(&(QueryType, f64, f64, f64)) -> f64| {
+        plan.iter().map(|step| mse(step.1, epsilon(step))).sum::<f64>()
+    };
+    rows.push(Row::new(vec![
+        Cell::from("Total"),
+        Cell::from(""),
+        Cell::from(format!("{:.3}", total)),
+        Cell::from(format!("{:.1}", sum(|step| step.2))),
+        Cell::from(format!("{:.3}", total)),
+        Cell::from(format!("{:.1}", sum(|step| step.3))),
+    ]).style(Style::default().add_modifier(Modifier::BOLD)));
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default()
+            .title(format!("Budget plan for {} over ε = {:.2}: three histograms (a count each), a mean and a max; least error puts ε in proportion to Δ^(2/3)",
+                           noiser.aggregate_field, total))
+            .borders(Borders::ALL))
+        .widths(&[
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(9),
+            Constraint::Length(16),
+            Constraint::Length(14),
+            Constraint::Min(0),
+        ]);
+    rect.render_widget(table, area);
+}
+
+fn draw_nutrition_label(noiser: &Noiser,
+                        rect: &mut Frame<CrosstermBackend<Stdout>>,
+                        area: Rect,
+) {
+    let label = Paragraph::new(noiser.nutrition_label().generate_nutrition_label())
+        .block(Block::default().title("Privacy Nutrition Label").borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    rect.render_widget(label, area);
+}
+
+fn as_percentages(frequencies: &[f64]) -> Vec<u64> {
+    frequencies.iter().map(|f| (f * 100.0).round() as u64).collect()
+}
+
+fn draw_graphs(noiser: &Noiser,
+               app: &App,
+               aggregate_buckets: &[String],
+               clusters: &[u64],
                rect: &mut Frame<CrosstermBackend<Stdout>>,
                chunks: Vec<Rect>,
 ) {
+    let (aggregated_data, noised_data, unit) = match noiser.noise_target {
+        NoiseTarget::Counts => (noiser.aggregated_data.clone(), noiser.noised_data.clone(), ""),
+        NoiseTarget::Frequencies => (as_percentages(&noiser.aggregated_frequencies),
+                                     as_percentages(&noiser.noised_frequencies),
+                                     " (%)"),
+    };
+    let bar_styles = aggregate_buckets.iter()
+        .map(|label| match app.bucket_matches(label) {
+            Some(true) => Some(Style::default().fg(Color::LightCyan).add_modifier(Modifier::BOLD)),
+            Some(false) => Some(Style::default().fg(Color::DarkGray)),
+            None => None,
+        })
+        .collect::<Vec<_>>();
     let graph_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
-            [Constraint::Percentage(50), Constraint::Percentage(50)].as_ref(),
+            [Constraint::Percentage(40), Constraint::Percentage(40), Constraint::Percentage(20)].as_ref(),
         )
         .split(chunks[1]);
 
-    let block1 = Block::default().title("Sensitive Values").borders(Borders::ALL);
-    let block2 = Block::default().title("Noised Values").borders(Borders::ALL);
+    let sensitive_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [Constraint::Percentage(75), Constraint::Percentage(25)].as_ref(),
+        )
+        .split(graph_chunks[0]);
+
+    let bootstrap_note = if noiser.bootstrap_intervals.is_empty() { "" } else { " - red: bootstrap 90% interval" };
+    let block1 = Block::default().title(format!("Sensitive Values{}{}", unit, bootstrap_note)).borders(Borders::ALL);
+    let svt_note = if noiser.svt_selected.is_empty() {
+        String::new()
+    } else {
+        format!(" - green: above {:.0} by the sparse vector technique", noiser.svt_threshold)
+    };
+    let interval_note = if noiser.proportion_intervals.is_empty() { "" } else { " - cyan: 95% interval for the share" };
+    let block2 = Block::default().title(format!("Noised Values{}{} - magenta: private mode{}", unit, svt_note, interval_note)).borders(Borders::ALL);
     let mut chart_data1 = Vec::<(&str, u64)>::new();
     for (pos, _e) in aggregate_buckets.iter().enumerate() {
         chart_data1.push((aggregate_buckets[pos].as_str(), aggregated_data[pos]) as (&str, u64))
     }
-    let left = BarChart::default()
+    let left = StyledBarChart::new(&chart_data1)
         .block(block1)
-        .data(&chart_data1)
         .bar_width(6)
         .bar_style(Style::default().fg(Color::Yellow))
-        .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+        .value_style(Style::default().fg(Color::Black).bg(Color::Yellow))
+        .bar_styles(bar_styles.clone())
+        .error_bars(noiser.bootstrap_intervals.clone(), Style::default().fg(Color::Red));
 
     let mut chart_data2 = Vec::<(&str, u64)>::new();
-    for (pos, _e) in aggregate_buckets.iter().enumerate() {
-        chart_data2.push((aggregate_buckets[pos].as_str(), noised_data[pos]) as (&str, u64))
+    // empty when nothing was released, e.g. after a failed PTR test
+    for (bucket, &noised) in aggregate_buckets.iter().zip(noised_data.iter()) {
+        chart_data2.push((bucket.as_str(), noised))
     }
-    let right = BarChart::default()
+    // the search highlight wins over the sparse vector selection, and that over the private mode
+    let noised_styles = bar_styles.iter().enumerate()
+        .map(|(pos, style)| style.or_else(|| {
+            noiser.svt_selected.get(pos).copied().unwrap_or(false).then(|| Style::default().fg(Color::LightGreen))
+        }).or_else(|| (noiser.private_mode == Some(pos)).then(|| Style::default().fg(Color::LightMagenta))))
+        .collect::<Vec<_>>();
+    let right = StyledBarChart::new(&chart_data2)
         .block(block2)
-        .data(&chart_data2)
         .bar_width(6)
         .bar_style(Style::default().fg(Color::Yellow))
-        .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+        .value_style(Style::default().fg(Color::Black).bg(Color::Yellow))
+        .bar_styles(noised_styles)
+        .error_bars(noiser.proportion_intervals.clone(), Style::default().fg(Color::Cyan));
+
+    // in hundredths of a bit, the bars only hold integers
+    let information_data = aggregate_buckets.iter()
+        .zip(noiser.information_content_change())
+        .map(|(bucket, change)| (bucket.as_str(), change.map_or(0, |bits| (bits * 100.0).round() as u64)))
+        .collect::<Vec<_>>();
+    let information = StyledBarChart::new(&information_data)
+        .block(Block::default()
+            .title("Information content change |Δ bits| × 100 (empty buckets left at 0)")
+            .borders(Borders::ALL))
+        .bar_width(6)
+        .bar_style(Style::default().fg(Color::LightMagenta))
+        .value_style(Style::default().fg(Color::Black).bg(Color::LightMagenta))
+        .bar_styles(bar_styles);
+
+    let cluster_labels = (1..=clusters.len()).map(|c| format!("C{}", c)).collect::<Vec<_>>();
+    let cluster_data = cluster_labels.iter()
+        .zip(clusters.iter())
+        .map(|(label, &size)| (label.as_str(), size))
+        .collect::<Vec<_>>();
+    let cluster_chart = BarChart::default()
+        .block(Block::default().title("Clusters (k-means, un-noised)").borders(Borders::ALL))
+        .data(&cluster_data)
+        .bar_width(4)
+        .bar_style(Style::default().fg(Color::Magenta))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Magenta));
+
+    rect.render_widget(left, sensitive_chunks[0]);
+    rect.render_widget(cluster_chart, sensitive_chunks[1]);
+    if noiser.individual_epsilons.is_empty() {
+        rect.render_widget(right, graph_chunks[1]);
+    } else {
+        let noised_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(75), Constraint::Percentage(25)].as_ref())
+            .split(graph_chunks[1]);
+        let level_labels = noiser.individual_epsilons.iter().map(|(level, _)| format!("{:.2}", level)).collect::<Vec<_>>();
+        let level_data = level_labels.iter()
+            .zip(noiser.individual_epsilons.iter())
+            .map(|(label, &(_, records))| (label.as_str(), records))
+            .collect::<Vec<_>>();
+        let level_chart = BarChart::default()
+            .block(Block::default().title("Records by individual ε").borders(Borders::ALL))
+            .data(&level_data)
+            .bar_width(5)
+            .bar_style(Style::default().fg(Color::Cyan))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+        rect.render_widget(right, noised_chunks[0]);
+        rect.render_widget(level_chart, noised_chunks[1]);
+    }
+    rect.render_widget(information, graph_chunks[2]);
+}
+
+/// Renders the log newest-last, scrolled so that the latest entries stay visible.
+fn draw_event_log(app: &App, rect: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+    let block = Block::default().title("Event Log").borders(Borders::ALL);
+    let visible_rows = block.inner(area).height as usize;
+    let lines = app.event_log.iter()
+        .map(|(at, event)| {
+            let elapsed = at.duration_since(app.started).as_secs();
+            Spans::from(vec![
+                Span::styled(format!("[{:02}:{:02}] ", elapsed / 60, elapsed % 60),
+                             Style::default().fg(Color::DarkGray)),
+                Span::raw(event.clone()),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let scroll = lines.len().saturating_sub(visible_rows) as u16;
+    let log = Paragraph::new(lines)
+        .block(block)
+        .scroll((scroll, 0));
+    rect.render_widget(log, area);
+}
+
+fn draw_tutorial(step: &TutorialStep, rect: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+    let block = Block::default()
+        .title(format!("Tutorial: {} (Space or Enter to continue)", step.title))
+        .borders(Borders::ALL);
+    let explanation = Paragraph::new(step.explanation)
+        .block(block)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: true });
+    rect.render_widget(explanation, area);
+}
+
+fn param_line(text: String) -> Spans<'static> {
+    Spans::from(vec![
+        Span::styled(text,
+                     Style::default().fg(Color::Black)
+                         .add_modifier(Modifier::BOLD)),
+    ])
+}
 
-    rect.render_widget(left, graph_chunks[0]);
-    rect.render_widget(right, graph_chunks[1]);
+fn grade_color(grade: &str) -> Color {
+    match grade {
+        "A+" | "A" => Color::Green,
+        "B" => Color::Yellow,
+        "C" => Color::LightRed,
+        _ => Color::Red,
+    }
+}
+
+/// One cell per bucket with its adaptive epsilon, colored from green (least epsilon,
+/// most noise) to red (most epsilon, least noise).
+fn privacy_heatmap_line(noiser: &Noiser, buckets: &[String]) -> Spans<'static> {
+    let max_epsilon = noiser.bucket_epsilons.iter().cloned().fold(0.0, f64::max);
+    let mut spans = vec![Span::styled("Bucket ε: ", Style::default().fg(Color::Black).add_modifier(Modifier::BOLD))];
+    for (bucket, &epsilon) in buckets.iter().zip(noiser.bucket_epsilons.iter()) {
+        let color = match epsilon / max_epsilon {
+            share if share < 0.25 => Color::Green,
+            share if share < 0.5 => Color::Yellow,
+            share if share < 0.75 => Color::LightRed,
+            _ => Color::Red,
+        };
+        spans.push(Span::styled(format!(" {}:{:.3} ", bucket, epsilon), Style::default().fg(Color::Black).bg(color)));
+    }
+    Spans::from(spans)
 }
 
-fn noise_params(noiser: &Noiser) -> Vec<Spans<'static>> {
+fn report_card_lines(noiser: &Noiser) -> Vec<Spans<'static>> {
+    let report_card = noiser.report_card();
+    let label_style = Style::default().fg(Color::Black).add_modifier(Modifier::BOLD);
+    let grade_style = |grade: &str| Style::default().fg(grade_color(grade)).add_modifier(Modifier::BOLD);
     vec![
         Spans::from(vec![
-            Span::styled(format!("Type: {}", noiser.noise_type),
-                         Style::default().fg(Color::Black)
-                             .add_modifier(Modifier::BOLD)),
-        ]),
-        Spans::from(vec![
-            Span::styled(format!("Noise: {}", noiser.accuracy),
-                         Style::default().fg(Color::Black)
-                             .add_modifier(Modifier::BOLD)),
-        ]),
-        Spans::from(vec![
-            Span::styled(format!("Field: {}", noiser.aggregate_field),
-                         Style::default().fg(Color::Black)
-                             .add_modifier(Modifier::BOLD)),
+            Span::styled("Privacy: ", label_style),
+            Span::styled(report_card.privacy_grade, grade_style(report_card.privacy_grade)),
+            Span::styled("  Utility: ", label_style),
+            Span::styled(report_card.utility_grade, grade_style(report_card.utility_grade)),
         ]),
+        param_line(report_card.summary),
     ]
 }
+
+fn epsilon_line(noiser: &Noiser) -> String {
+    let epsilon = noiser.epsilon();
+    let line = match noiser.delta_for_epsilon(epsilon) {
+        Some(delta) => format!("Epsilon: {:.3}, δ = {:.2e}", epsilon, delta),
+        None => format!("Epsilon: {:.3}", epsilon),
+    };
+    match (noiser.gdp_mu(), noiser.gdp_epsilon()) {
+        (Some(mu), Some(gdp_epsilon)) => format!("{}, μ-GDP: μ = {:.3} (ε = {:.3} at δ = {:.0e})",
+                                                 line, mu, gdp_epsilon, noiser.delta),
+        _ => line,
+    }
+}
+
+fn distributional_line(noiser: &Noiser) -> String {
+    match noiser.distributional_epsilons {
+        Some((uniform, empirical)) => format!("Distributional ε = {:.2} (assuming uniform input), {:.2} (assuming the observed bucket shares) at δ = {:.0e}, against the worst-case {:.2}",
+                                              uniform, empirical, noiser.delta, noiser.epsilon()),
+        None => String::from("Distributional ε: for noised Laplace counts only"),
+    }
+}
+
+fn rdp_line(noiser: &Noiser, order: f64) -> String {
+    match (noiser.rdp_epsilon(order), noiser.rdp_to_approx_dp(order, noiser.delta)) {
+        (Some(rdp_epsilon), Some(epsilon)) => format!("RDP ε(α = {}) = {:.3}, i.e. ε = {:.3} at δ = {:.0e}",
+                                                      order, rdp_epsilon, epsilon, noiser.delta),
+        _ => format!("RDP ε(α = {}): no closed form for {}", order, noiser.noise_type),
+    }
+}
+
+fn max_information_line(noiser: &Noiser) -> String {
+    match noiser.iid_max_information(MAX_INFORMATION_BETA) {
+        Some(iid) if iid.is_finite() => format!("Max information ≤ {:.1} bits for any data, ≤ {:.1} bits for i.i.d. records (β = {})",
+                                                noiser.max_information(), iid, MAX_INFORMATION_BETA),
+        _ => String::from("Max information: unbounded (δ > 0 or no noise)"),
+    }
+}
+
+fn utility_line(noiser: &Noiser) -> String {
+    let line = format!("MSE = {:.2}, MAD = {:.2}, L1 = {:.3}, L2 = {:.3}, EMD = {:.3} buckets",
+                       noiser.mean_squared_error(), noiser.median_absolute_deviation(),
+                       noiser.histogram_l1_distance(), noiser.histogram_l2_distance(),
+                       noiser.histogram_earth_movers_distance());
+    match noiser.expected_median_absolute_deviation() {
+        Some(expected) => format!("{} (expected {:.2})", line, expected),
+        None => line,
+    }
+}
+
+fn noise_params(noiser: &Noiser, app: &App) -> Vec<Spans<'static>> {
+    // frequencies are always noised with the Gaussian mechanism
+    let noise_type = match noiser.noise_target {
+        NoiseTarget::Counts => noiser.noise_type.to_string(),
+        NoiseTarget::Frequencies => NoiseType::Gaussian.to_string(),
+    };
+    let mut lines = vec![param_line(format!("Type: {}", noise_type))];
+    if let (NoiseTarget::Counts, NoiseType::Mixture { laplace_weight }) = (noiser.noise_target, noiser.noise_type) {
+        let (laplace_scale, sigma) = noiser.mixture_scales();
+        lines.push(param_line(format!("Laplace b = {:.2} (w = {:.2}), Gaussian σ = {:.2} (w = {:.2})",
+                                      laplace_scale, laplace_weight, sigma, 1.0 - laplace_weight)));
+    }
+    if let Some(passed) = noiser.ptr_passed {
+        lines.push(param_line(format!("PTR test: {} (δ = {:.0e})",
+                                      if passed { "passed, released" } else { "failed, nothing released" },
+                                      noiser.delta)));
+    }
+    if let (NoiseTarget::Counts, NoiseType::AnalyticGaussian) = (noiser.noise_target, noiser.noise_type) {
+        let (analytic, classic) = noiser.analytic_gaussian_scales();
+        lines.push(param_line(format!("Analytic σ = {:.2} vs classic σ = {:.2} (expected MSE {:.1} vs {:.1})",
+                                      analytic, classic, analytic * analytic, classic * classic)));
+    }
+    if noiser.noise_target == NoiseTarget::Counts && noiser.epsilon().is_finite() {
+        let (noise_type, mse) = recommend_mechanism(noiser.epsilon(), noiser.delta, noiser.sensitivity() as u64,
+                                                    noiser.aggregated_data.len());
+        lines.push(param_line(format!("Recommended: {} (MSE = {:.1} at ε = {:.3}, δ = {:.0e})",
+                                      noise_type, mse, noiser.epsilon(), noiser.delta)));
+    }
+    if let Some((binomial, laplace)) = noiser.binomial_laplace_epsilons() {
+        lines.push(param_line(format!("Binomial ε = {:.3} (δ = {:.0e}) vs discrete Laplace ε = {:.3} at the same variance {:.0}",
+                                      binomial, noiser.delta, laplace, noiser.scale().powi(2))));
+    }
+    if let Some((current, optimal, classic)) = noiser.gaussian_variances() {
+        lines.push(param_line(format!(
+            "Total variance over {} buckets: {:.0} now, {:.0} optimal, {:.0} classic ({:.0}% less MSE than classic)",
+            noiser.aggregated_data.len(), current, optimal, classic, 100.0 * (1.0 - optimal / classic))));
+    }
+    lines.extend(vec![
+        param_line(format!("Target: {}", noiser.noise_target)),
+        param_line(format!("Noise: {}", noiser.accuracy)),
+        param_line(epsilon_line(noiser)),
+        param_line(distributional_line(noiser)),
+        param_line(max_information_line(noiser)),
+        param_line(rdp_line(noiser, app.rdp_order)),
+        param_line(format!("Realized privacy loss of this release: {:.3} on average (worst case ε = {:.3})",
+                           noiser.data_dependent_privacy_loss(), noiser.epsilon())),
+        param_line(format!("Robust ε = {:.2} (at {:.0}% contamination), nominal ε = {:.2}",
+                           noiser.robust_privacy_loss(), CONTAMINATION_FRACTION * 100.0, noiser.epsilon())),
+        param_line(format!("Worst-case error ≤ {:.1} (observed: {})",
+                           noiser.worst_case_error(), noiser.observed_worst_error)),
+        param_line(utility_line(noiser)),
+        param_line(format!("Sensitivity: {} (empirical: {}, jackknife: {})",
+                           noiser.sensitivity(), noiser.empirical_sensitivity, noiser.jackknife_estimate)),
+        param_line(format!("L1 sensitivity {} (Laplace), L2 sensitivity {} (Gaussian, OpenDP count stability: {})",
+                           noiser.sensitivity(), noiser.l2_sensitivity(),
+                           noiser.opendp_l2_sensitivity().map_or("n/a".to_string(), |l2| l2.to_string()))),
+        param_line(format!("Field: {}", noiser.aggregate_field)),
+    ]);
+    if let Some((lambda, moments, rdp, classic)) = noiser.composed_gaussian_bounds(app.rdp_order) {
+        lines.push(param_line(format!(
+            "{} releases at δ = {:.0e}: moments accountant ε = {:.3} (λ = {}), RDP(α = {}) ε = {:.3}, classic ε = {:.3}",
+            noiser.odometer.release_count().max(1), noiser.delta, moments, lambda, app.rdp_order, rdp, classic)));
+    }
+    if let (NoiseTarget::Counts, Some(k)) = (noiser.noise_target, noiser.suppression_k) {
+        lines.push(param_line(format!("Suppressed buckets: {} (count < {}, sensitivity now {})",
+                                      noiser.suppressed_buckets(k), k, noiser.sensitivity())));
+    }
+    if let Some((before, after)) = noiser.consistency_mse {
+        lines.push(param_line(format!("Consistency projection: MSE {:.1} before, {:.1} after", before, after)));
+    }
+    if let Some((before, after)) = noiser.isotonic_mse {
+        lines.push(param_line(format!("CDF totals MSE: {:.1} before, {:.1} after isotonic regression{}",
+                                      before, after, if noiser.isotonic { "" } else { " (not applied)" })));
+    }
+    if let Some((mse, direct_mse)) = noiser.range_query_comparison {
+        lines.push(param_line(format!("Range queries MSE: {} {:.1} vs direct Laplace {:.1} (same ε)",
+                                      noiser.noise_type, mse, direct_mse)));
+    }
+    if let Some((mse, direct_mse)) = noiser.laplace_comparison {
+        lines.push(param_line(format!("Average MSE: {} {:.1} vs direct Laplace {:.1} (same ε)",
+                                      noiser.noise_type, mse, direct_mse)));
+    }
+    if let Some(kl) = noiser.per_instance_kl {
+        lines.push(param_line(format!("Per-instance loss of the first record: KL = {:.4} (worst case ε = {:.3})",
+                                      kl, noiser.epsilon())));
+    }
+    if let Some(regret) = noiser.utility_regret {
+        lines.push(param_line(format!("Utility regret: {:.1}% {} Laplace at the same ε (MSE 2(Δ/ε)² = {:.1})",
+                                      regret.abs(), if regret >= 0.0 { "above" } else { "below" },
+                                      noiser.laplace_baseline_mse())));
+    }
+    if let Some((epsilon, empirical, exact)) = noiser.hockey_stick {
+        lines.push(param_line(format!("Hockey-stick divergence at ε = {:.3}: {:.4} sampled vs δ = {:.4} exact",
+                                      epsilon, empirical, exact)));
+    }
+    if let Some(advantage) = noiser.mi_advantage {
+        lines.push(param_line(format!("MI Attack Advantage: {:.2}%", advantage * 100.0)));
+    }
+    if let Some(verified) = noiser.calibration_verified {
+        lines.push(param_line(format!("OpenDP calibration: {}", if verified { "verified" } else { "FAILED" })));
+    }
+    if let Some(worst_case) = noiser.verify_worst_case_privacy_loss() {
+        let parameter = if noiser.noise_type == NoiseType::Gaussian { "ρ" } else { "ε" };
+        let check = match noiser.opendp_privacy_parameter() {
+            Some((ours, opendp)) if (ours - opendp).abs() <= 1e-9 * ours.abs() => {
+                format!(", {} = {:.4} agrees with OpenDP", parameter, ours)
+            }
+            Some((ours, opendp)) => format!(", DISCREPANCY: {} = {:.4} here, {:.4} in OpenDP", parameter, ours, opendp),
+            None => String::new(),
+        };
+        let worst_case = if worst_case.is_finite() { format!("{:.4}", worst_case) } else { "unbounded".to_string() };
+        lines.push(param_line(format!("Worst-case privacy loss: {}{}", worst_case, check)));
+    }
+    if let Some((between, rejection, opendp)) = noiser.sampler_comparison {
+        lines.push(param_line(format!(
+            "Discrete Laplace samplers: TV {:.3} rejection vs OpenDP (to the exact pmf: {:.3} rejection, {:.3} OpenDP)",
+            between, rejection, opendp)));
+    }
+    if let Some(answer) = noiser.query_answer {
+        lines.push(param_line(format!("Query: {} of {} ≈ {:.2} (Δ = {:.4}, scale = {:.2}, ε = {:.3})",
+                                      noiser.query_type, noiser.aggregate_field, answer,
+                                      noiser.query_sensitivity(), noiser.query_scale(), noiser.epsilon())));
+    }
+    if !noiser.svt_selected.is_empty() {
+        let (threshold_epsilon, query_epsilon) = noiser.svt_epsilon_split(noiser.epsilon(), SVT_MAX_ABOVE);
+        let split = if noiser.svt_threshold_share.is_some() { "manual" } else { "optimal" };
+        lines.push(param_line(format!("SVT split ({}): ε₁ = {:.3} threshold, ε₂ = {:.3} comparisons",
+                                      split, threshold_epsilon, query_epsilon)));
+    }
+    if let Some(mode) = noiser.private_mode {
+        let probability = noiser.mode_probabilities(noiser.epsilon()).get(mode).copied().unwrap_or_default();
+        lines.push(param_line(format!("Private mode (report noisy max by the Gumbel-max trick): {} = {}, drawn with probability {:.0}% (ε = {:.3})",
+                                      noiser.aggregate_field, noiser.buckets()[mode], probability * 100.0, noiser.epsilon())));
+    }
+    if let Some((one_shot, laplace)) = noiser.top_k_recovery {
+        lines.push(param_line(format!("Top {} buckets recovered: {:.0}% one-shot Gumbel, {:.0}% noisy Laplace histogram (same ε)",
+                                      TOP_K, one_shot * 100.0, laplace * 100.0)));
+    }
+    if let Some(share) = noiser.unique_output_share {
+        lines.push(param_line(format!("Re-ID risk heuristic: {:.0}% of runs give an output no other run repeats (more noise, more unique outputs; not a risk of re-identification)",
+                                      share * 100.0)));
+    }
+    if let Some((measured, uniform, smallest)) = noiser.heterogeneous_dp {
+        lines.push(param_line(format!("Heterogeneous DP (each record at its own ε, sample mechanism): MSE {:.1}, against {:.1} for Laplace at the smallest ε = {:.3} for everyone",
+                                      measured, uniform, smallest)));
+    }
+    if let Some((count, histogram_sum)) = noiser.private_count {
+        lines.push(param_line(format!("Records: {} by a private count on {:.0}% of ε, {} summing the histogram on the rest, {} exact",
+                                      count, PRIVATE_COUNT_SHARE * 100.0, histogram_sum, noiser.aggregated_data.iter().sum::<u64>())));
+    }
+    if let Some((sparse, dense)) = noiser.sparse_gaussian {
+        lines.push(param_line(format!("Sparsity {:.0}% of buckets empty; sparse Gaussian (empty buckets kept at zero, small counts cut) MSE {:.1}, Gaussian on every bucket {:.1}",
+                                      noiser.sparsity() * 100.0, sparse, dense)));
+    }
+    if let Some((measured, expected, unprojected, counts)) = noiser.subspace_range_errors {
+        lines.push(param_line(format!("Range queries, Gaussian on all of them projected onto their subspace: MSE {:.1} (expected {:.1}, {:.1} unprojected), Gaussian on the counts: {:.1}",
+                                      measured, expected, unprojected, counts)));
+    }
+    if let Some((a, b)) = app.range {
+        let n = noiser.range_cdf.len();
+        if b < n {
+            let noised = noiser.noised_data[a..=b].iter().sum::<u64>();
+            let truth = noiser.aggregated_data[a..=b].iter().sum::<u64>();
+            lines.push(param_line(format!("Buckets {}-{}: {} from the private CDF, {} from the noised counts, {} exact",
+                                          a, b, range_query(&noiser.range_cdf, a, b), noised, truth)));
+            if let Some(test) = noiser.private_z_test(a, b, HYPOTHESIS_ALPHA).filter(|_| a != b && b < noiser.noised_data.len()) {
+                lines.push(param_line(format!("z-test bucket {} = bucket {} on the noised counts: z = {:.2}, p = {:.3} with the noise ({:.3} ignoring it), {} at α = {}",
+                                              a, b, test.z, test.p_value, test.naive_p_value,
+                                              if test.reject { "rejected" } else { "not rejected" }, HYPOTHESIS_ALPHA)));
+            }
+        } else if n > 0 {
+            lines.push(param_line(format!("Buckets {}-{}: out of range, the last bucket is {}", a, b, n - 1)));
+        }
+        if let Some(conditional) = noiser.conditional_privacy_loss((a, b)) {
+            let unconditional = noiser.conditional_privacy_loss((0, noiser.aggregated_data.len() - 1)).unwrap_or(conditional);
+            lines.push(param_line(format!("Conditional ε = {:.2} given knowledge of bucket range [{}, {}] (replacing a record without it: {:.2})",
+                                          conditional, a, b, unconditional)));
+        }
+    }
+    if !noiser.multi_stat_answers.is_empty() {
+        let answers = noiser.multi_stat_answers.iter()
+            .map(|(statistic, answer, epsilon)| format!("{} ≈ {:.2} (ε = {:.3})", statistic, answer, epsilon))
+            .collect::<Vec<_>>();
+        lines.push(param_line(format!("Stats of {}: {}", noiser.aggregate_field, answers.join(", "))));
+    }
+    if let Some((median, smooth_scale, global_scale)) = noiser.smooth_median {
+        lines.push(param_line(format!("Median {} ≈ {:.1} (smooth sensitivity scale {:.1} vs global {:.1})",
+                                      noiser.aggregate_field, median, smooth_scale, global_scale)));
+    }
+    if let Some((inverse_sensitivity, laplace)) = noiser.private_medians.filter(|_| noiser.median_comparison) {
+        lines.push(param_line(format!("Private Median (IS): {:.1}", inverse_sensitivity)));
+        lines.push(param_line(format!("Private Median (Laplace): {:.1}", laplace)));
+    }
+    if noiser.epsilon().is_finite() {
+        let (ptr_scale, global_scale) = noiser.ptr_mean_scales();
+        let answer = noiser.ptr_mean.map_or(String::from("refused by the test"), |mean| format!("≈ {:.0}", mean));
+        lines.push(param_line(format!("PTR mean income {} (scale {:.0} vs global {:.0})",
+                                      answer, ptr_scale, global_scale)));
+    }
+    if let Some(married_count) = noiser.married_count {
+        lines.push(param_line(format!("Married records: {}", married_count)));
+    }
+    if !noiser.unknown_domain_histogram.is_empty() {
+        let outside = noiser.values_outside_buckets().iter()
+            .map(|(value, count)| format!("{} = {}", value, count))
+            .collect::<Vec<_>>();
+        lines.push(param_line(format!("Without a fixed domain: {} values of {} released, {} outside the buckets{}{}",
+                                      noiser.unknown_domain_histogram.len(), noiser.aggregate_field, outside.len(),
+                                      if outside.is_empty() { "" } else { ": " }, outside.join(", "))));
+    }
+    if let Some((g, hashing, direct)) = noiser.local_histogram_mse {
+        lines.push(param_line(format!("Local DP histogram at ε = {:.3}: MSE {:.0} by local hashing into {} values, {:.0} by randomized response over all {} buckets",
+                                      noiser.epsilon(), hashing, g, direct, noiser.aggregated_data.len())));
+    }
+    if let Some((counted, debiased, output)) = noiser.perturbation_mse {
+        lines.push(param_line(format!("Input perturbation at ε = {:.3} (every record's bucket randomized before counting): MSE {:.0} as counted, {:.0} debiased; output perturbation (Laplace on the counts): {:.1}",
+                                      noiser.epsilon(), counted, debiased, output)));
+    }
+    if let (Some(central), Some(local)) = (noiser.central_educ_count, noiser.local_educ_count) {
+        lines.push(param_line(format!("educ > 10: {} central, {} local (randomized response)", central, local)));
+        lines.push(param_line(format!("Local ε = {:.3} vs. Central ε after shuffling = {:.3} (δ = {:.0e})",
+                                      noiser.epsilon(), noiser.shuffled_epsilon(), noiser.delta)));
+    }
+    let (odometer_epsilon, odometer_delta) = noiser.odometer.current_bound();
+    let cost = match noiser.release_cost() {
+        (epsilon, _) if epsilon.is_infinite() => String::from("no noise: the true values, not a DP release, nothing charged"),
+        (epsilon, delta) => format!("a refresh costs ε = {:.2}, δ = {:.1e}", epsilon, delta),
+    };
+    lines.push(param_line(format!("Odometer: {} releases, ε ≤ {:.2}, δ ≤ {:.1e} so far; {}",
+                                  noiser.odometer.release_count(), odometer_epsilon, odometer_delta, cost)));
+    if let Some(commitment) = noiser.odometer.commitment {
+        let spent = noiser.odometer.spent();
+        let total = noiser.budget.map_or(String::from("no total set"), |budget| {
+            format!("{:.2} total, {:.2} left for other releases", budget, budget - spent - commitment.reserved())
+        });
+        lines.push(param_line(format!("Budget: {:.2} reserved ({} of {} releases left), {:.2} spent ({:.2} from the reservation), {}",
+                                      commitment.reserved(), commitment.remaining(), commitment.queries,
+                                      spent, commitment.spent, total)));
+    }
+    if let Some(budget) = noiser.budget {
+        let status = if noiser.odometer.spent() <= budget { "within" } else { "exceeded" };
+        lines.push(param_line(format!("Budget: ε ≤ {:.2} ({})", budget, status)));
+    }
+    let previous = Vec::from(noiser.release_history().clone());
+    let auxiliary_risk = noiser.auxiliary_information_risk(&previous);
+    if auxiliary_risk > 0.0 {
+        let other_fields = previous.iter().filter(|release| release.field != noiser.aggregate_field).count();
+        lines.push(Spans::from(Span::styled(
+            format!("Caution: auxiliary info risk = {:.2} ε additional ({} earlier releases of another field)",
+                    auxiliary_risk, other_fields),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+    }
+    if noiser.query_rejected {
+        lines.push(Spans::from(Span::styled("QUERY REJECTED (budget exhausted)",
+                                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))));
+    }
+    if let (Some((given_field, given_value)), Some(populated)) = (noiser.condition, noiser.populated_buckets()) {
+        lines.push(param_line(format!("Given {} = {}: {} of {} buckets populated",
+                                      given_field, given_value, populated.len(),
+                                      noiser.buckets().len())));
+    }
+    if !noiser.bucket_epsilons.is_empty() {
+        lines.push(privacy_heatmap_line(noiser, &noiser.buckets()));
+    }
+    lines.extend(report_card_lines(noiser));
+    lines
+}
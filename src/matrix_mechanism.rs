@@ -0,0 +1,90 @@
+//! The matrix mechanism (Li, Hay, Rastogi, Miklau, McGregor, PODS 2010) for a workload `W`
+//! of linear queries on a histogram: noise is added to the answers of a strategy and the
+//! workload is answered from them by least squares. With `W` itself as the strategy the
+//! least squares step projects the noised answers onto the column space of `W`, which
+//! drops every noise component outside the `rank(W)`-dimensional subspace the true answers
+//! live in. Finding the strategy of least error is a semidefinite program; this keeps `W`.
+
+use rand::Rng;
+
+use crate::accounting::analytic_gaussian_scale;
+use crate::mechanisms::gaussian_noise;
+use crate::pca::symmetric_eigen;
+
+/// Eigenvalues of `Wᵀ W` below this share of the largest count as zero.
+const RANK_TOLERANCE: f64 = 1e-9;
+
+/// One row per range `[a, b]` of `n_buckets` buckets, `a ≤ b`, with ones on the buckets in it.
+pub fn range_workload(n_buckets: usize) -> Vec<Vec<f64>> {
+    (0..n_buckets)
+        .flat_map(|a| (a..n_buckets).map(move |b| (0..n_buckets).map(|i| if (a..=b).contains(&i) { 1.0 } else { 0.0 }).collect()))
+        .collect()
+}
+
+/// Largest column norm of `workload`: one count moving by one moves the answers by that in L2.
+pub fn workload_l2_sensitivity(workload: &[Vec<f64>]) -> f64 {
+    let d = workload.first().map_or(0, |row| row.len());
+    (0..d)
+        .map(|j| workload.iter().map(|row| row[j] * row[j]).sum::<f64>().sqrt())
+        .fold(0.0, f64::max)
+}
+
+/// Orthonormal basis of the column space of `workload`, the vectors `W v / sqrt(λ)` for the
+/// eigenpairs of `Wᵀ W` with non-zero `λ`. Its length is the rank of the workload.
+pub fn column_space_basis(workload: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let d = workload.first().map_or(0, |row| row.len());
+    let gram = (0..d)
+        .map(|i| (0..d).map(|j| workload.iter().map(|row| row[i] * row[j]).sum()).collect())
+        .collect::<Vec<Vec<f64>>>();
+    let pairs = symmetric_eigen(&gram);
+    let largest = pairs.first().map_or(0.0, |(value, _)| *value);
+    pairs.into_iter()
+        .filter(|(value, _)| *value > RANK_TOLERANCE * largest)
+        .map(|(value, vector)| {
+            workload.iter()
+                .map(|row| row.iter().zip(&vector).map(|(w, v)| w * v).sum::<f64>() / value.sqrt())
+                .collect()
+        })
+        .collect()
+}
+
+/// `values` projected onto the span of the orthonormal `basis`.
+pub fn project_onto_basis(values: &[f64], basis: &[Vec<f64>]) -> Vec<f64> {
+    let mut projected = vec![0.0; values.len()];
+    for vector in basis {
+        let coefficient = vector.iter().zip(values).map(|(u, x)| u * x).sum::<f64>();
+        for (p, u) in projected.iter_mut().zip(vector) {
+            *p += coefficient * u;
+        }
+    }
+    projected
+}
+
+/// Noise for the answers of `workload` from the Gaussian mechanism on them, calibrated to
+/// `sensitivity` per count times `workload_l2_sensitivity` at (`epsilon`, `delta`), and
+/// projected onto the column space of the workload. Its covariance is `σ² P` for the
+/// projection `P`, a total variance of `σ² rank(W)` instead of `σ²` per query.
+pub fn subspace_noise<R: Rng>(workload: &[Vec<f64>], epsilon: f64, delta: f64, sensitivity: f64, rng: &mut R) -> Vec<f64> {
+    let sigma = analytic_gaussian_scale(epsilon, delta, sensitivity * workload_l2_sensitivity(workload));
+    let noise = gaussian_noise(&vec![0.0; workload.len()], sigma, rng);
+    project_onto_basis(&noise, &column_space_basis(workload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_workload_spans_the_histogram() {
+        let workload = range_workload(3);
+        assert_eq!(workload.len(), 6);
+        // the middle bucket is in four of the six ranges
+        assert_eq!(workload_l2_sensitivity(&workload), 2.0);
+        let basis = column_space_basis(&workload);
+        assert_eq!(basis.len(), 3);
+        // true answers are already in the column space
+        let answers = workload.iter().map(|row| row.iter().zip([4.0, 1.0, 2.0]).map(|(w, x)| w * x).sum()).collect::<Vec<f64>>();
+        assert!(project_onto_basis(&answers, &basis).iter().zip(&answers).all(|(p, a)| (p - a).abs() < 1e-9));
+        assert!(subspace_noise(&workload, f64::INFINITY, 1e-6, 1.0, &mut rand::thread_rng()).iter().all(|&noise| noise == 0.0));
+    }
+}
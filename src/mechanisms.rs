@@ -0,0 +1,252 @@
+use std::f64::consts::SQRT_2;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Binomial, Distribution, Normal};
+use statrs::function::erf::erfc_inv;
+
+use crate::accounting::analytic_gaussian_scale;
+
+/// Draws a uniform sample from (0, 1] where every representable double can be hit.
+/// `rng.gen::<f64>()` only produces multiples of 2^-53, which leaves gaps near zero that
+/// `ln` turns into a visible pattern in the noise.
+fn uniform_full_precision<R: Rng>(rng: &mut R) -> f64 {
+    // each leading zero bit halves the interval the sample falls into
+    let mut exponent = -1;
+    while exponent > -1022 && !rng.gen::<bool>() {
+        exponent -= 1;
+    }
+    let mantissa = rng.gen::<u64>() >> 12;
+    (1.0 + mantissa as f64 / (1u64 << 52) as f64) * 2f64.powi(exponent)
+}
+
+/// Snapping mechanism from Mironov, "On Significance of the Least Significant Bits for
+/// Differential Privacy" (CCS 2012).
+///
+/// Textbook Laplace implemented with floating point leaks the true value through the
+/// low-order bits of the output. Snapping clamps the input to `[-bound, bound]`, adds
+/// Laplace noise, rounds the result to the nearest multiple of the smallest power of two
+/// `>= scale` and clamps again, which hides those bits at a slight cost in epsilon.
+pub fn snapping_sample<R: Rng>(value: f64, scale: f64, bound: f64, rng: &mut R) -> f64 {
+    let clamped = value.clamp(-bound, bound);
+    if scale <= 0.0 {
+        return clamped;
+    }
+    let snap_to = 2f64.powi(scale.log2().ceil() as i32);
+    let sign = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+    let noisy = clamped + sign * scale * uniform_full_precision(rng).ln();
+    ((noisy / snap_to).round() * snap_to).clamp(-bound, bound)
+}
+
+/// Adds independent zero-mean Gaussian noise with standard deviation `scale` to every value.
+pub fn gaussian_noise<R: Rng>(values: &[f64], scale: f64, rng: &mut R) -> Vec<f64> {
+    if scale <= 0.0 {
+        return values.to_vec();
+    }
+    let normal = Normal::new(0.0, scale).unwrap();
+    values.iter().map(|value| value + normal.sample(rng)).collect()
+}
+
+pub fn laplace_sample<R: Rng>(scale: f64, rng: &mut R) -> f64 {
+    // inverse CDF of Laplace(0, scale), u uniform on (-1/2, 1/2)
+    let u = rng.gen::<f64>() - 0.5;
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+}
+
+/// Discrete Laplace, `P(x) ∝ e^(-|x| / scale)` on the integers, by the rejection step of
+/// Canonne, Kamath, Steinke 2020: a magnitude from the geometric distribution with
+/// `P(Y ≥ k) = e^(-k / scale)` and a fair sign, rejecting the negative zero that would
+/// give 0 twice its share. The generator is seeded per sample so that a draw can be
+/// replayed and checked by hand.
+pub fn discrete_laplace_rejection_sample(scale: f64, seed: u64) -> i64 {
+    if scale == 0.0 {
+        return 0;
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    loop {
+        let magnitude = (-scale * uniform_full_precision(&mut rng).ln()).floor() as i64;
+        let negative = rng.gen::<bool>();
+        if !(negative && magnitude == 0) {
+            return if negative { -magnitude } else { magnitude };
+        }
+    }
+}
+
+/// `P(X = x)` of the discrete Laplace distribution, `tanh(1 / (2 scale)) e^(-|x| / scale)`.
+pub fn discrete_laplace_pmf(x: i64, scale: f64) -> f64 {
+    (0.5 / scale).tanh() * (-(x.unsigned_abs() as f64) / scale).exp()
+}
+
+/// `Laplace(center, scale)` conditioned on `[lower, upper]`, by inverting the CDF on the
+/// part of `[0, 1]` that maps into the bounds. The normalization depends on `center`, which
+/// moves the density ratio of two neighbours by up to another `e^(Δ / scale)`: at a given
+/// epsilon the scale has to be twice that of untruncated Laplace noise.
+pub fn truncated_laplace_sample(center: f64, scale: f64, lower: f64, upper: f64, seed: u64) -> f64 {
+    if scale == 0.0 {
+        return center.clamp(lower, upper);
+    }
+    let cdf = |x: f64| {
+        let z = (x - center) / scale;
+        if z < 0.0 { 0.5 * z.exp() } else { 1.0 - 0.5 * (-z).exp() }
+    };
+    let (low, high) = (cdf(lower), cdf(upper));
+    if low == high {
+        // far outside the bounds both ends of the CDF round to the same value
+        return center.clamp(lower, upper);
+    }
+    let u = low + (high - low) * StdRng::seed_from_u64(seed).gen::<f64>();
+    let sample = if u < 0.5 { center + scale * (2.0 * u).ln() } else { center - scale * (2.0 * (1.0 - u)).ln() };
+    sample.clamp(lower, upper)
+}
+
+/// Noise of the binomial mechanism (Agarwal et al., cpSGD, NeurIPS 2018): `n` fair coin
+/// flips, centred. Integer valued for even `n`, bounded by `n / 2` and with variance `n / 4`.
+pub fn binomial_noise<R: Rng>(n: usize, rng: &mut R) -> i64 {
+    Binomial::new(n as u64, 0.5).unwrap().sample(rng) as i64 - (n / 2) as i64
+}
+
+/// Gumbel(0, scale) by inverse CDF.
+pub fn gumbel_sample<R: Rng>(scale: f64, rng: &mut R) -> f64 {
+    -scale * (-uniform_full_precision(rng).ln()).ln()
+}
+
+/// The exponential mechanism by the Gumbel-max trick: `Gumbel(2Δ / epsilon)` on every score
+/// and the index of the largest, drawn from the generator of `seed`. The argmax of
+/// `s_i + Gumbel(β)` is `i` with probability `e^(s_i / β) / Σ_j e^(s_j / β)`, which at
+/// `β = 2Δ / ε` are the weights `exp(ε s_i / 2Δ)` of `exponential_mechanism_probabilities`,
+/// with no weight computed or normalized.
+pub fn gumbel_max_selection(scores: &[f64], sensitivity: f64, epsilon: f64, seed: u64) -> usize {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let scale = 2.0 * sensitivity / epsilon;
+    scores.iter()
+        .map(|score| score + gumbel_sample(scale, &mut rng))
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (pos, noised)| if noised > best.1 { (pos, noised) } else { best })
+        .0
+}
+
+/// Probability of every index under the exponential mechanism, `exp(ε s_i / 2Δ)` normalized,
+/// with the largest score taken off first so that large counts do not overflow.
+pub fn exponential_mechanism_probabilities(scores: &[f64], sensitivity: f64, epsilon: f64) -> Vec<f64> {
+    let top = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weights = scores.iter().map(|score| (epsilon * (score - top) / (2.0 * sensitivity)).exp()).collect::<Vec<_>>();
+    let total = weights.iter().sum::<f64>();
+    weights.iter().map(|weight| weight / total).collect()
+}
+
+/// Split of `total_epsilon` between the threshold (`ε₁`) and the comparisons (`ε₂`) of the
+/// sparse vector technique that minimizes the error of its answers (Lyu, Su, Li, VLDB 2017):
+/// `ε₁ : ε₂ = 1 : (2c)^(2/3)` for `c = max_above` positives. Half and half is not optimal,
+/// the comparison noise grows with `c` and deserves the larger share. Every split is
+/// `total_epsilon`-DP, even a published one; a poor split only costs accuracy.
+pub fn svt_optimal_epsilon_split(total_epsilon: f64, max_above: usize) -> (f64, f64) {
+    let ratio = (2.0 * max_above.max(1) as f64).powf(2.0 / 3.0);
+    let threshold_epsilon = total_epsilon / (1.0 + ratio);
+    (threshold_epsilon, total_epsilon - threshold_epsilon)
+}
+
+/// Test step of Propose-Test-Release (Dwork & Lei, STOC 2009). `distance_to_instability`
+/// is the number of records that must change before the local sensitivity exceeds the
+/// proposed bound; it is itself a query of sensitivity 1, so it is noised with
+/// `Laplace(scale)` and the release goes ahead only if the noised distance clears
+/// `scale * ln(1 / delta)`. The test costs `1 / scale` of epsilon and fails to catch an
+/// unstable data set with probability at most `delta`.
+pub fn propose_test_release<R: Rng>(distance_to_instability: f64, scale: f64, delta: f64, rng: &mut R) -> bool {
+    distance_to_instability + laplace_sample(scale, rng) > scale * (1.0 / delta).ln()
+}
+
+/// Per value, adds Laplace noise with probability `laplace_weight` and Gaussian noise otherwise.
+pub fn mixture_noise<R: Rng>(values: &[f64], laplace_weight: f64, laplace_scale: f64, sigma: f64, rng: &mut R) -> Vec<f64> {
+    values.iter()
+        .map(|value| {
+            if rng.gen::<f64>() < laplace_weight {
+                value + laplace_sample(laplace_scale, rng)
+            } else {
+                gaussian_noise(&[*value], sigma, rng)[0]
+            }
+        })
+        .collect()
+}
+
+/// Smallest noised count the sparse Gaussian mechanism keeps: a bucket of one record with
+/// `N(0, sigma²)` on it reaches `1 + sigma Φ⁻¹(1 - delta)` with probability `delta`.
+pub fn sparse_histogram_threshold(sigma: f64, delta: f64) -> f64 {
+    1.0 + sigma * SQRT_2 * erfc_inv(2.0 * delta)
+}
+
+/// Gaussian sparse histogram (Wilkins, Kifer, Zhang, Karrer 2024): Gaussian noise on the
+/// non-empty buckets only, and every noised count below `sparse_histogram_threshold`
+/// released as zero. Empty buckets stay exactly zero. Adding a record either moves a
+/// non-empty count by one, which the Gaussian noise covers at (epsilon, delta) whatever
+/// the number of buckets, or makes a bucket of one, which gets past the threshold with
+/// probability delta. The noise per bucket is the same as on the full histogram; what is
+/// saved is the noise on the empty buckets, paid for with the small counts cut to zero.
+pub fn sparse_gaussian_mechanism<R: Rng>(counts: &[u64], epsilon: f64, delta: f64, rng: &mut R) -> Vec<u64> {
+    let sigma = analytic_gaussian_scale(epsilon, delta, 1.0);
+    let threshold = sparse_histogram_threshold(sigma, delta);
+    counts.iter()
+        .map(|&count| {
+            if count == 0 {
+                return 0;
+            }
+            let noised = gaussian_noise(&[count as f64], sigma, rng)[0];
+            if noised < threshold { 0 } else { noised.round() as u64 }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncated_laplace_stays_in_bounds() {
+        let samples = (0..5000).map(|seed| truncated_laplace_sample(2.0, 3.0, 0.0, 100.0, seed)).collect::<Vec<_>>();
+        assert!(samples.iter().all(|&sample| (0.0..=100.0).contains(&sample)));
+        // the tail cut off below zero pushes the mean up
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!(mean > 2.5 && mean < 4.5, "mean {}", mean);
+        // wide bounds leave plain Laplace noise around the center
+        let wide = (0..5000).map(|seed| truncated_laplace_sample(50.0, 3.0, -1e6, 1e6, seed)).sum::<f64>() / 5000.0;
+        assert!((wide - 50.0).abs() < 0.3);
+        assert_eq!(truncated_laplace_sample(1e6, 1.0, 0.0, 10.0, 1), 10.0);
+    }
+
+    #[test]
+    fn rejection_sampler_matches_the_discrete_laplace_pmf() {
+        let scale = 2.0;
+        let n = 20000;
+        let samples = (0..n).map(|seed| discrete_laplace_rejection_sample(scale, seed)).collect::<Vec<_>>();
+        for x in -2..=2 {
+            let frequency = samples.iter().filter(|&&sample| sample == x).count() as f64 / n as f64;
+            let p = discrete_laplace_pmf(x, scale);
+            assert!((frequency - p).abs() < 4.0 * (p * (1.0 - p) / n as f64).sqrt(), "P({}) = {} vs {}", x, frequency, p);
+        }
+        assert!(((-1000..=1000).map(|x| discrete_laplace_pmf(x, scale)).sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn gumbel_max_selects_like_the_exponential_mechanism() {
+        let scores = [3.0, 1.0, 0.0, 2.0];
+        let n = 20000;
+        let mut selected = [0; 4];
+        for seed in 0..n {
+            selected[gumbel_max_selection(&scores, 1.0, 1.0, seed)] += 1;
+        }
+        for (count, p) in selected.iter().zip(exponential_mechanism_probabilities(&scores, 1.0, 1.0)) {
+            let frequency = *count as f64 / n as f64;
+            assert!((frequency - p).abs() < 4.0 * (p * (1.0 - p) / n as f64).sqrt(), "{} vs {}", frequency, p);
+        }
+    }
+
+    #[test]
+    fn sparse_gaussian_keeps_empty_buckets_empty() {
+        // Φ⁻¹(0.975) = 1.96
+        assert!((sparse_histogram_threshold(2.0, 0.025) - 4.919928).abs() < 1e-5);
+        let counts = [0, 5000, 0, 1, 0];
+        let noised = sparse_gaussian_mechanism(&counts, 1.0, 1e-6, &mut rand::thread_rng());
+        assert_eq!([noised[0], noised[2], noised[3], noised[4]], [0, 0, 0, 0]);
+        assert!(noised[1].abs_diff(5000) < 50);
+        assert_eq!(sparse_gaussian_mechanism(&counts, f64::INFINITY, 1e-6, &mut rand::thread_rng()), counts);
+    }
+}
@@ -0,0 +1,78 @@
+//! Utility metrics comparing a noised histogram against the true one, bucket by bucket.
+
+fn absolute_errors(true_counts: &[u64], noised_counts: &[u64]) -> Vec<f64> {
+    true_counts.iter().zip(noised_counts.iter())
+        .map(|(&t, &n)| t.abs_diff(n) as f64)
+        .collect()
+}
+
+pub fn mean_squared_error(true_counts: &[u64], noised_counts: &[u64]) -> f64 {
+    let errors = absolute_errors(true_counts, noised_counts);
+    if errors.is_empty() {
+        return 0.0;
+    }
+    errors.iter().map(|e| e * e).sum::<f64>() / errors.len() as f64
+}
+
+/// Median of `|noised_i - true_i|`. Unlike the MSE a single wild bucket barely moves it.
+pub fn median_absolute_deviation(true_counts: &[u64], noised_counts: &[u64]) -> f64 {
+    let mut errors = absolute_errors(true_counts, noised_counts);
+    if errors.is_empty() {
+        return 0.0;
+    }
+    errors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = errors.len() / 2;
+    if errors.len().is_multiple_of(2) {
+        (errors[mid - 1] + errors[mid]) / 2.0
+    } else {
+        errors[mid]
+    }
+}
+
+/// `Σ |p_i - q_i|` between two distributions over the same buckets, twice their total
+/// variation distance.
+pub fn l1_distance(p: &[f64], q: &[f64]) -> f64 {
+    p.iter().zip(q.iter()).map(|(a, b)| (a - b).abs()).sum()
+}
+
+pub fn l2_distance(p: &[f64], q: &[f64]) -> f64 {
+    p.iter().zip(q.iter()).map(|(a, b)| (a - b).powi(2)).sum::<f64>().sqrt()
+}
+
+/// Earth mover's (1-Wasserstein) distance between two distributions over the same ordered
+/// buckets, one bucket apart from the next: `Σ |P_i - Q_i|` over their running totals. Mass
+/// moved to a far bucket costs more than mass moved next door, unlike with the L1 distance.
+pub fn earth_movers_distance(p: &[f64], q: &[f64]) -> f64 {
+    p.iter().zip(q.iter())
+        .scan(0.0, |gap, (a, b)| {
+            *gap += a - b;
+            Some(gap.abs())
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_absolute_deviation_matches_manual_computation() {
+        // absolute errors 0, 5, 2, 9, 1 -> sorted 0, 1, 2, 5, 9
+        assert_eq!(median_absolute_deviation(&[10, 20, 30, 40, 50], &[10, 25, 28, 31, 51]), 2.0);
+        // absolute errors 3, 1, 4, 0 -> sorted 0, 1, 3, 4
+        assert_eq!(median_absolute_deviation(&[3, 5, 8, 2], &[0, 6, 12, 2]), 2.0);
+    }
+
+    #[test]
+    fn mean_squared_error_matches_manual_computation() {
+        assert_eq!(mean_squared_error(&[10, 20, 30], &[12, 20, 27]), 13.0 / 3.0);
+    }
+
+    #[test]
+    fn earth_movers_distance_grows_with_how_far_the_mass_moves() {
+        let p = [1.0, 0.0, 0.0, 0.0];
+        assert_eq!(earth_movers_distance(&p, &[0.0, 1.0, 0.0, 0.0]), 1.0);
+        assert_eq!(earth_movers_distance(&p, &[0.0, 0.0, 0.0, 1.0]), 3.0);
+        assert_eq!(l1_distance(&p, &[0.0, 0.0, 0.0, 1.0]), 2.0);
+    }
+}
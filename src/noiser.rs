@@ -1,31 +1,272 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt;
-use ary::ary;
-use opendp::accuracy::{accuracy_to_discrete_gaussian_scale, accuracy_to_discrete_laplacian_scale};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use opendp::accuracy::{accuracy_to_discrete_gaussian_scale, accuracy_to_discrete_laplacian_scale, accuracy_to_gaussian_scale, accuracy_to_laplacian_scale};
 use opendp::core::Transformation;
 use opendp::domains::{AllDomain, VectorDomain};
 use opendp::measurements::{make_base_discrete_gaussian, make_base_discrete_laplace};
 use opendp::measures::ZeroConcentratedDivergence;
 use opendp::metrics::{L2Distance, SymmetricDistance};
 use opendp::transformations::{make_count_by_categories, make_select_column, make_split_dataframe};
+use serde::Deserialize;
+
+use crate::accounting::{
+    amplification_by_iteration, analytic_gaussian_scale, approximate_max_information, max_information, shuffler_amplification, binomial_delta_for_epsilon, l2_sensitivity, binomial_scale_to_epsilon, gaussian_approx_dp_curve, gaussian_delta_for_epsilon, gaussian_moments_accountant, optimal_gaussian_variance, gaussian_rdp, gdp_to_epsilon_delta, laplace_rdp, rdp_to_approx_dp, tradeoff_curve,
+    adaptive_epsilon_allocation, distributional_epsilon, NoiseDensity, PrivacyLoss,
+};
+use crate::continual::{binary_mechanism, binary_mechanism_levels};
+use crate::correlated::{adjacent_correlation_matrix, cholesky, correlated_laplace_noise, noise_amplification, privacy_inflation};
+use crate::csv_reader::{field_bounds, read_data, Record};
+use crate::dataset::{CsvDataSet, AGGREGATE_FIELDS};
+use crate::error::PriviError;
+use crate::hypothesis::{noised_proportion_ci, noisy_counts_z_test, HypothesisTestResult};
+use crate::inverse_sensitivity::inverse_sensitivity_median;
+use crate::ldp::{debiased_counts, generalized_randomized_response, local_hashing, optimal_hash_range, randomized_response_count, randomized_values};
+use crate::federated::SimulatedGradientNoiser;
+use crate::hierarchical::{hierarchical_mechanism, tree_levels, worst_leaf_noise};
+use crate::mechanisms::{
+    binomial_noise, discrete_laplace_pmf, discrete_laplace_rejection_sample, exponential_mechanism_probabilities, gaussian_noise, gumbel_max_selection, gumbel_sample, laplace_sample, mixture_noise, propose_test_release, snapping_sample,
+    sparse_gaussian_mechanism,
+    svt_optimal_epsilon_split, truncated_laplace_sample,
+};
+use crate::metrics;
+use crate::noiser::NoiseTarget::{Counts, Frequencies};
+use crate::noiser::NoiseType::{
+    AnalyticGaussian, Binomial, CdfNoise, CorrelatedLaplace, Gaussian, Laplace, Mixture, ProposeTestRelease, Snapping,
+    Hierarchical, TruncatedLaplace, Wavelet,
+};
+use crate::label::NutritionLabel;
+use crate::matrix_mechanism::{column_space_basis, range_workload, subspace_noise, workload_l2_sensitivity};
+use crate::odometer::PrivacyOdometer;
+use crate::pca::{private_principal_components, project_onto, second_moment_matrix, symmetric_eigen, unit_ball_features};
+use crate::postprocess::{isotonic_regression, project_nonneg_sum_constraint};
+use crate::random_projection::{project_back, projection_matrix, projection_sensitivity, random_projection};
+use crate::query::{allocate_budget, bounded_laplace_scale, MultiStatQuery, QueryType, Statistic};
+use crate::release::DpRelease;
+use crate::wavelet::{generalized_sensitivity, privelet_noise};
+use crate::smooth_sensitivity::{laplace_beta, median, smooth_sensitivity, sorted_values};
+use crate::report_card::ReportCard;
 
-use crate::dataset::CsvDataSet;
-use crate::noiser::NoiseType::{Gaussian, Laplace};
+/// Field, condition, noise target and type, and the epsilon, scale and delta of the noise.
+type SimulationKey<'a> = (&'a str, Option<(&'static str, &'static str)>, NoiseTarget, NoiseType, f64, f64, f64);
+/// Field, condition and suppression.
+type SensitivityKey<'a> = (&'a str, Option<(&'static str, &'static str)>, Option<u64>);
+/// The noise parameters, then suppression, consistency, isotonic regression, query type,
+/// statistics, sparse vector split and clip norm.
+type ReleaseKey<'a> = (SimulationKey<'a>, Option<u64>, bool, bool, QueryType, MultiStatQuery, Option<f64>, f64);
 
 #[derive(Clone)]
 pub struct Noiser<'a> {
-    dataset: &'a CsvDataSet<'a>,
-    pub aggregate_field: &'a String,
+    dataset: &'a CsvDataSet,
+    pub aggregate_field: &'a str,
+    /// Restricts the histogram to the records where the given field has the given value.
+    pub condition: Option<(&'static str, &'static str)>,
+    /// Buckets with fewer records than this are zeroed before noising, see `noised_with_suppression`.
+    pub suppression_k: Option<u64>,
+    /// Whether released counts are projected to be non-negative and sum to the true total.
+    pub consistency: bool,
+    /// Whether `CdfNoise` makes the noised running totals non-decreasing before differencing.
+    pub isotonic: bool,
+    /// Scalar query over `aggregate_field` answered next to the histogram, see `query_answer`.
+    pub query_type: QueryType,
+    /// `query_type` over `aggregate_field` with Laplace noise at `query_scale`.
+    pub query_answer: Option<f64>,
+    /// Statistics of `aggregate_field` released together, see `noised_multi_stats`.
+    pub multi_stat: MultiStatQuery,
+    /// Each requested statistic with its noised value and its share of epsilon.
+    pub multi_stat_answers: Vec<(Statistic, f64, f64)>,
+    /// Median of `aggregate_field` with noise from its smooth sensitivity, with that noise
+    /// scale and the one the global sensitivity needs, see `noised_smooth_median`.
+    pub smooth_median: Option<(f64, f64, f64)>,
+    /// Whether the median is also estimated by `private_median` and by `laplace_median`.
+    pub median_comparison: bool,
+    /// Medians of `aggregate_field` by the inverse sensitivity mechanism and by Laplace noise.
+    pub private_medians: Option<(f64, f64)>,
+    /// Histogram of `aggregate_field` over `CsvDataSet::generate_synthetic_twin` at `epsilon()`.
+    pub synthetic_counts: Vec<u64>,
+    /// `KL(true || synthetic twin)` of the `aggregate_field` histograms, in nats.
+    pub synthetic_kl: f64,
+    /// Correlation of educ and income in the data set and in its synthetic twin.
+    pub synthetic_correlation: (f64, f64),
+    /// Clip norm of the DP-SGD style sum over clients, see `federated_gradients`.
+    pub clip_norm: f64,
+    /// Noised sum of the clipped client histograms and how many clients were clipped.
+    pub federated_sum: Vec<f64>,
+    pub federated_clipped: usize,
+    /// `(step, MSE)` of `dp_gradient_descent`, and the MSE of one Gaussian release of the
+    /// counts at the same (epsilon, delta).
+    pub dp_gd_curve: Vec<(f64, f64)>,
+    pub dp_gd_one_shot_mse: f64,
+    /// `(round, MSE of the tree, MSE of independent noise)` of the running average over
+    /// `FTRL_ROUNDS` rounds, see `running_average_errors`.
+    pub ftrl_errors: Vec<(f64, f64, f64)>,
+    /// Target dimension and MSE per bucket of Gaussian noise on a random projection of the
+    /// counts, and the MSE of Gaussian noise on the counts themselves, see
+    /// `projection_mse_tradeoff`.
+    pub projection_tradeoff: Vec<(f64, f64)>,
+    pub projection_direct_mse: f64,
+    /// Records of the synthetic twin on the top two private principal components, see
+    /// `private_pca`.
+    pub pca_points: Vec<(f64, f64)>,
+    /// Degrees between the first private principal component and the exact one.
+    pub pca_angle: Option<f64>,
+    /// Mean income released by Propose-Test-Release, `None` when the test refused it.
+    pub ptr_mean: Option<f64>,
     pub noise_type: NoiseType,
+    pub noise_target: NoiseTarget,
     pub accuracy: usize,
     pub alpha: f64,
+    pub delta: f64,
+    /// Epsilon the release should stay within, only set from a config file.
+    pub budget: Option<f64>,
     pub aggregated_data: Vec<u64>,
     pub noised_data: Vec<u64>,
+    pub aggregated_frequencies: Vec<f64>,
+    pub noised_frequencies: Vec<f64>,
+    pub empirical_sensitivity: f64,
+    /// `contaminated_sensitivity` at `CONTAMINATION_FRACTION`, see `robust_privacy_loss`.
+    pub contaminated_sensitivity: f64,
+    /// Records left out by `jackknife_sensitivity`, set with `--jackknife-samples`.
+    pub jackknife_samples: usize,
+    pub jackknife_estimate: f64,
+    pub observed_worst_error: f64,
+    /// `reidentification_risk_estimate` over `ERROR_TRIALS` runs, for counts.
+    pub unique_output_share: Option<f64>,
+    pub tradeoff: Vec<(f64, f64)>,
+    /// Whether `bootstrap_intervals` are computed.
+    pub show_bootstrap: bool,
+    /// Bootstrap 90% interval of every true count, see `bootstrap_intervals`.
+    pub bootstrap_intervals: Vec<(u64, u64)>,
+    /// `proportion_intervals` of the last release at `PROPORTION_CI_ALPHA`.
+    pub proportion_intervals: Vec<(u64, u64)>,
+    /// Field and condition the intervals were computed for.
+    bootstrap_key: Option<(&'a str, Option<(&'static str, &'static str)>)>,
+    /// Field, condition and suppression the sensitivity estimates were computed for.
+    sensitivity_key: Option<SensitivityKey<'a>>,
+    /// Parameters the simulations of `refresh_data` last ran for, see `simulation_key`.
+    simulation_key: Option<SimulationKey<'a>>,
+    /// Parameters the outputs of `release` were last drawn for, see `release_key`.
+    release_key: Option<ReleaseKey<'a>>,
+    /// Privacy spent by the outputs of the last refresh, see `release_cost`.
+    release_cost: (f64, f64),
+    /// Privacy spent by every release since startup.
+    pub odometer: PrivacyOdometer,
+    /// `utility_regret` of the current mechanism, `None` without noise.
+    pub utility_regret: Option<f64>,
+    /// Epsilon, empirical and exact `hockey_stick_divergence` of the last release.
+    pub hockey_stick: Option<(f64, f64, f64)>,
+    /// `per_instance_dp` of the first record at `epsilon()`.
+    pub per_instance_kl: Option<f64>,
+    /// Bucket with the largest count by `report_noisy_max`.
+    pub private_mode: Option<usize>,
+    /// `private_cdf` at the current epsilon, for the range queries of `range_query`.
+    pub range_cdf: Vec<u64>,
+    /// MSE of `heterogeneous_dp_histogram` over `individual_privacy_levels`, the expected
+    /// MSE of Laplace counts at the smallest level, and that level.
+    pub heterogeneous_dp: Option<(f64, f64, f64)>,
+    /// Distinct `individual_privacy_levels` and the number of records at each.
+    pub individual_epsilons: Vec<(f64, u64)>,
+    /// `distributional_dp_epsilon` for uniform input and for the observed bucket shares,
+    /// Laplace counts only.
+    pub distributional_epsilons: Option<(f64, f64)>,
+    /// Private count of `stability_extension_histogram` at the current epsilon and the sum
+    /// of its histogram.
+    pub private_count: Option<(u64, u64)>,
+    /// `sparse_gaussian_mse` at the current epsilon.
+    pub sparse_gaussian: Option<(f64, f64)>,
+    /// `subspace_range_errors` at the current epsilon.
+    pub subspace_range_errors: Option<(f64, f64, f64, f64)>,
+    /// Share of the true top `TOP_K` buckets recovered by `one_shot_top_k` and by
+    /// `laplace_top_k` at the current epsilon, averaged over `ERROR_TRIALS` runs.
+    pub top_k_recovery: Option<(f64, f64)>,
+    /// Epsilon per bucket for a follow-up release, see `adaptive_epsilon_allocation`.
+    pub bucket_epsilons: Vec<f64>,
+    /// Buckets that the sparse vector technique found above `svt_threshold`.
+    pub svt_selected: Vec<bool>,
+    pub svt_threshold: f64,
+    /// Share of epsilon for the SVT threshold noise, `None` for `svt_optimal_epsilon_split`.
+    pub svt_threshold_share: Option<f64>,
+    /// Density of the privacy loss random variable, see `privacy_loss_distribution`.
+    pub privacy_loss_pdf: Vec<(f64, f64)>,
+    /// `(epsilon, log10 delta)` points of `approx_dp_curve`, empty for pure DP.
+    pub approx_dp_curve: Vec<(f64, f64)>,
+    /// Noised number of married records, an example of `count_with_predicate`.
+    pub married_count: Option<u64>,
+    /// `private_histogram_unknown_domain` of `aggregate_field` at the current epsilon.
+    pub unknown_domain_histogram: BTreeMap<String, u64>,
+    /// Number of records with `educ > 10` estimated under central DP (`count_with_predicate`)
+    /// and local DP (`local_dp_count`) at the same epsilon.
+    pub central_educ_count: Option<u64>,
+    pub local_educ_count: Option<u64>,
+    /// `local_histogram_mse` at the current epsilon.
+    pub local_histogram_mse: Option<(usize, f64, f64)>,
+    /// `perturbation_mse` at the current epsilon.
+    pub perturbation_mse: Option<(f64, f64, f64)>,
+    /// Advantage of `membership_inference_attack` on the first record, for counts only.
+    pub mi_advantage: Option<f64>,
+    /// `compare_discrete_laplace_samplers` at the current scale, Laplace counts only.
+    pub sampler_comparison: Option<(f64, f64, f64)>,
+    /// Outcome of `laplace_calibration_test` at the current accuracy, Laplace counts only.
+    pub calibration_verified: Option<bool>,
+    /// Average MSE of the current noise and of direct Laplace noise, while `CdfNoise`,
+    /// `CorrelatedLaplace`, `Wavelet`, `Hierarchical` or `TruncatedLaplace` is selected.
+    pub laplace_comparison: Option<(f64, f64)>,
+    /// Average squared error over all range queries of the current noise and of direct
+    /// Laplace noise, while `CdfNoise`, `Wavelet` or `Hierarchical` is selected.
+    pub range_query_comparison: Option<(f64, f64)>,
+    /// MSE of the `CdfNoise` running totals before and after isotonic regression.
+    pub isotonic_mse: Option<(f64, f64)>,
+    /// MSE of the release before and after consistency post-processing.
+    pub consistency_mse: Option<(f64, f64)>,
+    /// Whether the test step of `ProposeTestRelease` let the last release through.
+    pub ptr_passed: Option<bool>,
+    /// Whether `privacy_filter` turned the last release down for want of budget.
+    pub query_rejected: bool,
+    /// The last `MAX_RELEASES` noised histograms, oldest first.
+    releases: VecDeque<DpRelease>,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
 pub enum NoiseType {
     Laplace,
     Gaussian,
+    /// Continuous Laplace made safe for floating-point data, see `snapping_sample`.
+    Snapping,
+    /// Each noise value comes from Laplace with probability `laplace_weight` and from
+    /// Gaussian otherwise, see `Noiser::mixture_scales`.
+    Mixture { laplace_weight: f64 },
+    /// Discrete Laplace scaled to `proposed_sensitivity`, released only if a private test
+    /// finds the data far from any data set with a larger local sensitivity, see
+    /// `Noiser::distance_to_instability`.
+    ProposeTestRelease { proposed_sensitivity: u64 },
+    /// Continuous Gaussian calibrated exactly to (epsilon, `Noiser::delta`), see
+    /// `accounting::analytic_gaussian_scale`.
+    AnalyticGaussian,
+    /// Laplace noise on the running totals of the histogram, made non-decreasing again and
+    /// differenced back into counts, see `noised_cdf`.
+    CdfNoise,
+    /// Laplace noise with correlation `rho` between adjacent buckets, calibrated to the
+    /// epsilon of independent Laplace noise, see `correlated`.
+    CorrelatedLaplace { rho: f64 },
+    /// Laplace noise on the Haar wavelet coefficients, calibrated to the epsilon of
+    /// independent Laplace noise, see `wavelet`.
+    Wavelet,
+    /// Laplace noise on every node of a binary tree over the buckets, the same scale on
+    /// every level and calibrated to the epsilon of independent Laplace noise, made
+    /// consistent by least squares, see `hierarchical`.
+    Hierarchical,
+    /// Centred `Binomial(n, 1/2)` noise, see `mechanisms::binomial_noise`. The noise is set
+    /// by `n` rather than by the accuracy, and is only (epsilon, `delta`)-DP.
+    Binomial { n: usize },
+    /// Laplace noise conditioned on the output staying in `[lower, upper]`, calibrated to the
+    /// epsilon of untruncated Laplace noise, see `mechanisms::truncated_laplace_sample`.
+    TruncatedLaplace { lower: f64, upper: f64 },
 }
 
 impl fmt::Display for NoiseType {
@@ -33,103 +274,3067 @@ impl fmt::Display for NoiseType {
         match self {
             Laplace => write!(f, "Laplace"),
             Gaussian => write!(f, "Gaussian"),
+            Snapping => write!(f, "Snapping"),
+            Mixture { laplace_weight } => write!(f, "Mixture ({:.0}% Laplace)", laplace_weight * 100.0),
+            ProposeTestRelease { proposed_sensitivity } => write!(f, "PTR (proposed Δ = {})", proposed_sensitivity),
+            AnalyticGaussian => write!(f, "Analytic Gaussian"),
+            CdfNoise => write!(f, "CDF Laplace"),
+            CorrelatedLaplace { rho } => write!(f, "Correlated Laplace (ρ = {})", rho),
+            Wavelet => write!(f, "Wavelet (Privelet)"),
+            Hierarchical => write!(f, "Hierarchical (binary tree)"),
+            Binomial { n } => write!(f, "Binomial (n = {})", n),
+            TruncatedLaplace { lower, upper } => write!(f, "Truncated Laplace [{}, {}]", lower, upper),
+        }
+    }
+}
+
+/// What the noise is added to: the raw bucket counts, or the relative frequencies of the
+/// buckets (always with Gaussian noise, see `Noiser::noised_frequencies`).
+#[derive(Clone, Copy, PartialEq)]
+pub enum NoiseTarget {
+    Counts,
+    Frequencies,
+}
+
+impl fmt::Display for NoiseTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Counts => write!(f, "Counts"),
+            Frequencies => write!(f, "Frequencies"),
         }
     }
 }
 
 pub trait NoiseApplier<'a> {
-    fn new(dataset: &'a CsvDataSet, aggregate_field: &'a String) -> Self;
+    fn new(dataset: &'a CsvDataSet, aggregate_field: &'a str) -> Self;
     fn toggle_noise_type(&mut self);
+    fn toggle_noise_target(&mut self);
+    fn toggle_condition(&mut self);
+    fn toggle_suppression(&mut self);
+    fn toggle_consistency(&mut self);
+    fn toggle_isotonic(&mut self);
+    fn toggle_multi_stat(&mut self);
+    fn toggle_median_comparison(&mut self);
+    fn toggle_bootstrap(&mut self);
+    fn toggle_svt_split(&mut self);
+    fn toggle_clip_norm(&mut self);
+    fn toggle_precommit(&mut self);
+    fn toggle_query_type(&mut self);
     fn increase_noise(&mut self);
     fn decrease_noise(&mut self);
+    fn reset_accuracy(&mut self);
+    fn reset(&mut self);
     fn refresh_data(&mut self);
 }
 
-const CSV_SEPARATOR: &'static str = ",";
+/// Running totals of `counts` with noise. Adding a record to bucket `i` moves the totals
+/// of buckets `i..n` by one each, so the L1 sensitivity of the CDF is the number of buckets
+/// `n`, not lower than that of the counts: each total gets `Laplace(n * scale)` for the
+/// epsilon that `Laplace(scale)` gives the counts.
+fn noised_cdf<R: Rng>(counts: &[u64], scale: f64, rng: &mut R) -> Vec<f64> {
+    let cdf_scale = counts.len() as f64 * scale;
+    running_totals(counts).iter().map(|total| total + laplace_sample(cdf_scale, rng)).collect()
+}
+
+fn running_totals(counts: &[u64]) -> Vec<f64> {
+    counts.iter()
+        .scan(0u64, |total, &count| {
+            *total += count;
+            Some(*total as f64)
+        })
+        .collect()
+}
+
+/// Counts from running totals clamped at zero. Non-decreasing totals, as isotonic
+/// regression leaves them, difference into valid counts; a total that drops below the one
+/// before it gives an empty bucket.
+fn cdf_to_counts(totals: &[f64]) -> Vec<u64> {
+    totals.iter()
+        .map(|total| total.max(0.0))
+        .scan(0.0, |previous, total| {
+            let count = total - *previous;
+            *previous = total;
+            Some(count.round().max(0.0) as u64)
+        })
+        .collect()
+}
+
+/// Threshold of `private_histogram_unknown_domain` for (`epsilon`, `delta`)-DP: a value
+/// only one neighbour holds has a count of one there, and `1 + Laplace(1 / epsilon)` passes
+/// `1 + ln(1 / (2 delta)) / epsilon` with probability `delta`.
+fn stability_threshold(epsilon: f64, delta: f64) -> f64 {
+    1.0 + (1.0 / (2.0 * delta)).ln() / epsilon
+}
+
+/// Number of records in buckets `a..=b` from running totals, `cdf[b] - cdf[a - 1]`.
+pub fn range_query(cdf: &[u64], a: usize, b: usize) -> u64 {
+    let before = if a == 0 { 0 } else { cdf[a - 1] };
+    cdf[b].saturating_sub(before)
+}
+
+/// Positions of the `k` largest `values`, largest first.
+fn top_k_indices<I: Iterator<Item = f64>>(values: I, k: usize) -> Vec<usize> {
+    let mut indexed = values.enumerate().collect::<Vec<_>>();
+    indexed.sort_by(|a, b| b.1.total_cmp(&a.1));
+    indexed.into_iter().take(k).map(|(pos, _)| pos).collect()
+}
+
+fn to_frequencies(counts: &[u64]) -> Vec<f64> {
+    let total = counts.iter().sum::<u64>().max(1) as f64;
+    counts.iter().map(|&count| count as f64 / total).collect()
+}
+
+/// Post-processing back onto the probability simplex: negative frequencies are clamped
+/// to zero and the rest rescaled to sum to one.
+fn renormalize(frequencies: &[f64]) -> Vec<f64> {
+    let clamped = frequencies.iter().map(|f| f.max(0.0)).collect::<Vec<_>>();
+    let total = clamped.iter().sum::<f64>();
+    if total == 0.0 {
+        return vec![1.0 / clamped.len() as f64; clamped.len()];
+    }
+    clamped.iter().map(|f| f / total).collect()
+}
 
-fn aggregate_data_chain(noiser: &Noiser, aggregate_field: &String) -> Option<Transformation<AllDomain<String>, VectorDomain<AllDomain<u64>>, SymmetricDistance, L2Distance<u8>>> {
+/// Of discrete Laplace (`Laplace`), the continuous Gaussian (`AnalyticGaussian`) and the
+/// discrete Gaussian (`Gaussian`), each calibrated to (`epsilon`, `delta`) for an integer
+/// query of `sensitivity`, the one with the lowest total noise variance over `n_buckets`
+/// and that variance. Discrete Laplace with `q = e^(-ε/Δ)` has variance `2q / (1 - q)²`,
+/// just under the `2 (Δ/ε)²` of continuous Laplace; the Gaussians take the analytic sigma,
+/// which the discrete Gaussian also meets (Canonne, Kamath, Steinke 2020) with a variance
+/// slightly below `σ²`. With one bucket per record the L1 and L2 sensitivities agree, so
+/// the Gaussians only win at small epsilon or large delta.
+pub fn recommend_mechanism(epsilon: f64, delta: f64, sensitivity: u64, n_buckets: usize) -> (NoiseType, f64) {
+    let q = (-epsilon / sensitivity as f64).exp();
+    let laplace = 2.0 * q / (1.0 - q).powi(2);
+    let sigma = analytic_gaussian_scale(epsilon, delta, sensitivity as f64);
+    let reach = (40.0 * sigma).ceil() as i64;
+    let weights = (-reach..=reach).map(|x| (x, (-(x * x) as f64 / (2.0 * sigma * sigma)).exp())).collect::<Vec<_>>();
+    // the two variances differ by less than the rounding error at large sigma
+    let discrete_gaussian = (weights.iter().map(|&(x, weight)| (x * x) as f64 * weight).sum::<f64>()
+        / weights.iter().map(|&(_, weight)| weight).sum::<f64>()).min(sigma * sigma);
+    [(Laplace, laplace), (Gaussian, discrete_gaussian), (AnalyticGaussian, sigma * sigma)]
+        .into_iter()
+        .map(|(noise_type, variance)| (noise_type, n_buckets as f64 * variance))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+type AggregateChain = Transformation<AllDomain<String>, VectorDomain<AllDomain<u64>>, SymmetricDistance, L2Distance<u8>>;
+
+fn aggregate_data_chain(noiser: &Noiser, aggregate_field: &str) -> Option<AggregateChain> {
     let aggregate_buckets = noiser.dataset.aggregate_buckets(aggregate_field);
     let column_names = noiser.dataset.columns().iter().map(|s| s.to_string()).collect();
 
     // transformers chain
-    let df_transformer = make_split_dataframe(Option::from(CSV_SEPARATOR), column_names).ok()?;
-    let aggregate_column = make_select_column::<String, String>(aggregate_field.clone()).ok()?;
+    let df_transformer = make_split_dataframe(Option::from(noiser.dataset.separator.to_string().as_str()), column_names).ok()?;
+    let aggregate_column = make_select_column::<String, String>(aggregate_field.to_string()).ok()?;
     let count_by_aggr_column = make_count_by_categories::<L2Distance<u8>, String, u64>(aggregate_buckets, true).ok()?;
     let chain = (df_transformer >> aggregate_column >> count_by_aggr_column).ok()?;
     Option::from(chain)
 }
 
-const ACCURACY_VALUES: [usize; 100] = ary![=> ..100: |i| i];
+const DEFAULT_ALPHA: f64 = 0.05;
+const DEFAULT_LAPLACE_WEIGHT: f64 = 0.5;
+const DEFAULT_PROPOSED_SENSITIVITY: u64 = 1;
+const DEFAULT_SUPPRESSION_K: u64 = 5;
+const DEFAULT_CORRELATION_RHO: f64 = 0.5;
+/// Trials of the binomial mechanism the noise keys step through, each doubling the noise.
+const BINOMIAL_TRIALS: [usize; 5] = [16, 64, 256, 1024, 4096];
+const DEFAULT_BINOMIAL_TRIALS: usize = 256;
+/// Largest log moment order the moments accountant tries, 32 as in Abadi et al.
+const MAX_MOMENT_ORDER: u32 = 32;
+
+/// A record added to or removed from the data set changes exactly one bucket count by one.
+const SENSITIVITY: f64 = 1.0;
+/// Buckets one added or removed record changes.
+const BUCKETS_PER_RECORD: usize = 1;
+
+const SENSITIVITY_TRIALS: usize = 50;
+const SENSITIVITY_SEED: u64 = 7;
+/// Workflow `budget_plan` splits the budget over: three histograms, planned as counts since
+/// a record moves one count of each by one, a mean and a max.
+const PLANNED_QUERIES: [QueryType; 5] = [QueryType::Count, QueryType::Count, QueryType::Count, QueryType::Average, QueryType::Max];
+/// Total `budget_plan` splits when no `budget` is configured.
+const DEFAULT_PLAN_BUDGET: f64 = 1.0;
+/// Share of the records `contaminated_sensitivity` corrupts.
+pub const CONTAMINATION_FRACTION: f64 = 0.1;
+const DEFAULT_JACKKNIFE_SAMPLES: usize = 100;
+const JACKKNIFE_SEED: u64 = 13;
+const ERROR_TRIALS: usize = 100;
+/// Number of buckets the top-k selections of `top_k_recovery` pick.
+pub const TOP_K: usize = 3;
+/// Target dimensions of `projection_mse_tradeoff`, as multiples of the number of buckets.
+const PROJECTION_FACTORS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const PROJECTION_SEED: u64 = 29;
+const TRADEOFF_POINTS: usize = 50;
+const PRIVACY_LOSS_POINTS: usize = 200;
+/// Smallest delta on the axis of `approx_dp_curve`.
+const APPROX_DP_MIN_DELTA: f64 = 1e-12;
+/// Field and proposed sensitivity of the Propose-Test-Release mean, see `noised_ptr_mean`.
+/// The proposal has to be fixed without looking at the data.
+const PTR_MEAN_FIELD: &str = "income";
+const PTR_MEAN_PROPOSED_SENSITIVITY: f64 = 1000.0;
+/// Share of epsilon `stability_extension_histogram` spends on the number of records.
+pub const PRIVATE_COUNT_SHARE: f64 = 0.2;
+/// Share of the records in every privacy tier of `individual_privacy_levels` and the share
+/// of `epsilon()` the tier accepts: a fifth of the records ask for four times the default
+/// protection, half for twice.
+const PRIVACY_TIERS: [(f64, f64); 3] = [(0.2, 0.25), (0.5, 0.5), (0.3, 1.0)];
+const PRIVACY_TIER_SEED: u64 = 31;
+const LDP_SEED: u64 = 37;
+const INPUT_PERTURBATION_SEED: u64 = 41;
+/// Runs `local_histogram_mse` averages over; each one reads the data set again.
+const LDP_TRIALS: usize = 20;
+/// Pseudo-count added to every bucket before the KL divergence of the synthetic twin.
+const KL_SMOOTHING: f64 = 0.5;
+/// Clip norms `toggle_clip_norm` cycles through, for the clients of `federated_gradients`.
+const CLIP_NORMS: [f64; 6] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0];
+const DEFAULT_CLIP_NORM: f64 = 5.0;
+/// Steps and learning rate of `dp_gradient_descent`.
+pub const DP_GD_STEPS: usize = 50;
+const DP_GD_LEARNING_RATE: f64 = 0.5;
+/// Clip norm of the per-record gradients of `dp_gradient_descent`, `|θ - e_b|`. While θ is a
+/// distribution it is at most √2, reached at θ = e_c for another bucket c, so clipping only
+/// touches the gradients the noise has pushed θ out of the simplex for.
+const DP_GD_CLIP_NORM: f64 = std::f64::consts::SQRT_2;
+/// Rounds the records arrive in for `dp_ftrl_histogram`, about one record per round, and
+/// the runs `running_average_errors` averages over.
+pub const FTRL_ROUNDS: usize = 1024;
+const FTRL_TRIALS: usize = 10;
+/// Number of buckets the sparse vector technique may report above its threshold.
+pub const SVT_MAX_ABOVE: usize = 3;
+/// Shares of epsilon for the SVT threshold to cycle through after the optimal split.
+const SVT_THRESHOLD_SHARES: [f64; 4] = [0.1, 0.25, 0.5, 0.75];
+const CALIBRATION_SAMPLES: usize = 2000;
+const MAX_RELEASES: usize = 100;
+/// Releases reserved at once by `toggle_precommit`.
+const PRECOMMIT_QUERIES: usize = 10;
+const MI_SHADOW_DATASETS: usize = 400;
+const MI_SEED: u64 = 11;
+/// Level of `proportion_intervals`, 95% intervals.
+const PROPORTION_CI_ALPHA: f64 = 0.05;
+const BOOTSTRAP_SAMPLES: usize = 1000;
+const BOOTSTRAP_SEED: u64 = 19;
+/// Share of `epsilon()` at which the hockey-stick divergence is shown; at the full epsilon
+/// it is zero or tiny and lost in the sampling noise.
+const HOCKEY_STICK_SHARE: f64 = 0.5;
+const HOCKEY_STICK_SEED: u64 = 23;
+
+/// Public upper bound on any count, a declared cap on the size of the data set; the number
+/// of records itself is private and cannot bound the noise.
+const PUBLIC_COUNT_BOUND: f64 = 1000.0;
+/// Number of accuracy settings the noise steps through, from 0 up.
+const ACCURACY_LEVELS: usize = 100;
+
+/// Contents of a `--config` file, e.g.
+///
+/// ```toml
+/// noise_type = "Gaussian"
+/// accuracy = 5
+/// alpha = 0.05
+/// aggregate_field = "income"
+/// budget = 1.0
+/// delta = 1e-6
+/// ```
+///
+/// A mixture is written as `noise_type = { Mixture = { laplace_weight = 0.5 } }`, PTR as
+/// `noise_type = { ProposeTestRelease = { proposed_sensitivity = 1 } }`, correlated Laplace
+/// as `noise_type = { CorrelatedLaplace = { rho = 0.5 } }` and the binomial mechanism as
+/// `noise_type = { Binomial = { n = 256 } }`. Truncated Laplace takes its bounds as
+/// `noise_type = { TruncatedLaplace = { lower = 0.0, upper = 1000.0 } }`.
+#[derive(Deserialize)]
+struct NoiserConfig {
+    noise_type: NoiseType,
+    accuracy: usize,
+    alpha: f64,
+    aggregate_field: String,
+    budget: Option<f64>,
+    /// Delta of every (epsilon, delta) guarantee, the analytic Gaussian's included.
+    delta: Option<f64>,
+}
 
 impl<'a> Noiser<'a> {
-    fn clear_previous_data(&mut self) {
-        self.aggregated_data.clear();
-        self.noised_data.clear();
+    /// Builds a noiser from a TOML config file instead of the interactive defaults.
+    pub fn from_config(path: &Path, dataset: &'a CsvDataSet) -> Result<Noiser<'a>, PriviError> {
+        let config: NoiserConfig = toml::from_str(&fs::read_to_string(path)?)?;
+        let aggregate_field = AGGREGATE_FIELDS.iter()
+            .find(|&&field| field == config.aggregate_field)
+            .ok_or_else(|| PriviError::InvalidConfig(format!(
+                "aggregate_field must be one of {:?}, got '{}'", AGGREGATE_FIELDS, config.aggregate_field)))?;
+        if config.accuracy >= ACCURACY_LEVELS {
+            return Err(PriviError::InvalidConfig(format!(
+                "accuracy must be below {}, got {}", ACCURACY_LEVELS, config.accuracy)));
+        }
+        if !(config.alpha > 0.0 && config.alpha < 1.0) {
+            return Err(PriviError::InvalidConfig(format!("alpha must be in (0, 1), got {}", config.alpha)));
+        }
+        if let Mixture { laplace_weight } = config.noise_type {
+            if !(0.0..=1.0).contains(&laplace_weight) {
+                return Err(PriviError::InvalidConfig(format!(
+                    "laplace_weight must be in [0, 1], got {}", laplace_weight)));
+            }
+        }
+        if let Some(delta) = config.delta.filter(|&delta| !(delta > 0.0 && delta < 1.0)) {
+            return Err(PriviError::InvalidConfig(format!("delta must be in (0, 1), got {}", delta)));
+        }
+        if let CorrelatedLaplace { rho } = config.noise_type {
+            if !(rho > -1.0 && rho < 1.0) {
+                return Err(PriviError::InvalidConfig(format!("rho must be in (-1, 1), got {}", rho)));
+            }
+        }
+        if let Binomial { n } = config.noise_type {
+            if n == 0 || n % 2 != 0 {
+                return Err(PriviError::InvalidConfig(format!("n must be even and positive, got {}", n)));
+            }
+        }
+        if let TruncatedLaplace { lower, upper } = config.noise_type {
+            if lower >= upper {
+                return Err(PriviError::InvalidConfig(format!(
+                    "lower must be below upper, got [{}, {}]", lower, upper)));
+            }
+        }
+        if let Some(budget) = config.budget.filter(|&budget| budget <= 0.0) {
+            return Err(PriviError::InvalidConfig(format!("budget must be positive, got {}", budget)));
+        }
+
+        let mut noiser = Noiser::new(dataset, aggregate_field);
+        noiser.noise_type = config.noise_type;
+        noiser.accuracy = config.accuracy;
+        noiser.alpha = config.alpha;
+        noiser.budget = config.budget;
+        if let Some(delta) = config.delta {
+            noiser.delta = delta;
+        }
+        Ok(noiser)
     }
 
-    fn aggregate_data(&self) -> Option<Vec<u64>> {
-        let chain = aggregate_data_chain(&self, self.aggregate_field)?;
-        let aggregated_data = chain.invoke(&self.dataset.data).ok()?;
-        Option::from(aggregated_data)
+    /// Noise scale (on counts) that meets the current accuracy at level `alpha`.
+    pub fn scale(&self) -> f64 {
+        let accuracy = self.accuracy as f64;
+        match (self.noise_target, self.noise_type) {
+            (Frequencies, _) => accuracy_to_gaussian_scale(accuracy, self.alpha),
+            (Counts, Laplace) | (Counts, ProposeTestRelease { .. }) => accuracy_to_discrete_laplacian_scale(accuracy, self.alpha),
+            (Counts, Gaussian) => accuracy_to_discrete_gaussian_scale(accuracy, self.alpha),
+            (Counts, Snapping) | (Counts, Mixture { .. }) | (Counts, CdfNoise) => {
+                accuracy_to_laplacian_scale(accuracy, self.alpha)
+            }
+            (Counts, AnalyticGaussian) => Ok(self.analytic_gaussian_scales().0),
+            (Counts, CorrelatedLaplace { rho }) => {
+                let inflation = self.correlation_factor(rho).map_or(f64::INFINITY, |lower| privacy_inflation(&lower));
+                accuracy_to_laplacian_scale(accuracy, self.alpha).map(|scale| inflation * scale)
+            }
+            (Counts, Wavelet) => {
+                let rho = generalized_sensitivity(self.aggregated_data.len());
+                accuracy_to_laplacian_scale(accuracy, self.alpha).map(|scale| rho * scale)
+            }
+            (Counts, Hierarchical) => {
+                let levels = tree_levels(self.aggregated_data.len()) as f64;
+                accuracy_to_laplacian_scale(accuracy, self.alpha).map(|scale| levels * scale)
+            }
+            // the standard deviation, the trials and not the accuracy set the noise
+            (Counts, Binomial { n }) => Ok((n as f64).sqrt() / 2.0),
+            (Counts, TruncatedLaplace { .. }) => accuracy_to_laplacian_scale(accuracy, self.alpha).map(|scale| 2.0 * scale),
+        }.unwrap()
     }
 
-    fn noised_data(&self, aggregated_data: &Vec<u64>) -> Option<Vec<u64>> {
-        match self.noise_type {
-            Laplace => {
-                // sensitivity / epsilon
-                let scale = accuracy_to_discrete_laplacian_scale(self.accuracy as f64, self.alpha).unwrap();
-                let discrete_lp = make_base_discrete_laplace::<VectorDomain<AllDomain<u64>>, _>(
+    /// Cholesky factor of `adjacent_correlation_matrix` over the current buckets.
+    fn correlation_factor(&self, rho: f64) -> Option<Vec<Vec<f64>>> {
+        cholesky(&adjacent_correlation_matrix(self.aggregated_data.len(), rho))
+    }
+
+    /// Epsilon the Laplace mechanism reaches at the current accuracy, used as the target
+    /// of the mechanisms calibrated to an epsilon rather than to an accuracy.
+    fn laplace_epsilon(&self) -> f64 {
+        let laplace_scale = accuracy_to_laplacian_scale(self.accuracy as f64, self.alpha).unwrap();
+        if laplace_scale == 0.0 {
+            return f64::INFINITY;
+        }
+        self.sensitivity() / laplace_scale
+    }
+
+    /// Epsilon of the binomial mechanism at `delta` and the pure epsilon of discrete Laplace
+    /// noise with the same variance `v = n / 4`, `None` for other noise. Discrete Laplace with
+    /// `q = e^(-1/scale)` has variance `2q / (1 - q)²`, so `q = (v + 1 - sqrt(2v + 1)) / v`.
+    pub fn binomial_laplace_epsilons(&self) -> Option<(f64, f64)> {
+        let (Counts, Binomial { n }) = (self.noise_target, self.noise_type) else {
+            return None;
+        };
+        let variance = n as f64 / 4.0;
+        let q = (variance + 1.0 - (2.0 * variance + 1.0).sqrt()) / variance;
+        Some((self.epsilon(), -self.sensitivity() * q.ln()))
+    }
+
+    /// Sigma of the analytic Gaussian mechanism and of the classic
+    /// `sqrt(2 ln(1.25 / delta)) * sensitivity / epsilon` calibration at the same
+    /// (epsilon, `delta`), with epsilon the one Laplace reaches at the current accuracy.
+    pub fn analytic_gaussian_scales(&self) -> (f64, f64) {
+        let epsilon = self.laplace_epsilon();
+        let analytic = analytic_gaussian_scale(epsilon, self.delta, self.l2_sensitivity());
+        let classic = (2.0 * (1.25 / self.delta).ln()).sqrt() * self.l2_sensitivity() / epsilon;
+        (analytic, classic)
+    }
+
+    /// Whether the current release is epsilon-DP with delta 0: not for Gaussian noise, the
+    /// binomial mechanism or PTR.
+    fn is_pure_dp(&self) -> bool {
+        self.delta_for_epsilon(self.epsilon()).is_none()
+            && !matches!((self.noise_target, self.noise_type), (Counts, Binomial { .. }) | (Counts, ProposeTestRelease { .. }))
+    }
+
+    /// `max_information` in bits of the release about the records behind it, whatever their
+    /// distribution: `n ε log2 e`, zero without a signal and growing with epsilon. A delta
+    /// above zero leaves it unbounded, as does zero noise.
+    pub fn max_information(&self) -> f64 {
+        if !self.is_pure_dp() {
+            return f64::INFINITY;
+        }
+        max_information(self.epsilon(), self.aggregated_data.iter().sum::<u64>() as usize)
+    }
+
+    /// `approximate_max_information` at `beta` for records drawn independently, pure DP only.
+    pub fn iid_max_information(&self, beta: f64) -> Option<f64> {
+        self.is_pure_dp()
+            .then(|| approximate_max_information(self.epsilon(), self.aggregated_data.iter().sum::<u64>() as usize, beta))
+    }
+
+    /// Total noise variance over the buckets of the current Gaussian noise, of the optimal
+    /// calibration at the same (epsilon, `delta`) and of the classic
+    /// `2 ln(1.25 / delta) sensitivity^2 / epsilon^2` per bucket. `None` for other noise.
+    pub fn gaussian_variances(&self) -> Option<(f64, f64, f64)> {
+        if (self.noise_target, self.noise_type) != (Counts, Gaussian) || self.scale() == 0.0 {
+            return None;
+        }
+        let d = self.aggregated_data.len();
+        let epsilon = self.epsilon();
+        let current = d as f64 * self.scale().powi(2);
+        let optimal = optimal_gaussian_variance(epsilon, self.delta, self.l2_sensitivity(), d);
+        let classic = d as f64 * 2.0 * (1.25 / self.delta).ln() * (self.l2_sensitivity() / epsilon).powi(2);
+        Some((current, optimal, classic))
+    }
+
+    /// Privacy loss of a single release: `sensitivity / scale` for the Laplace family, and
+    /// the (epsilon, `delta`)-DP bound implied by the zCDP guarantee for Gaussian noise.
+    /// PTR pays `1 / scale` for the test and again for the release, whose noise is scaled
+    /// up with the proposed sensitivity. Zero noise gives an infinite epsilon.
+    pub fn epsilon(&self) -> f64 {
+        let scale = self.scale();
+        if scale == 0.0 {
+            return f64::INFINITY;
+        }
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) | (Counts, Snapping) | (Counts, Mixture { .. }) | (Counts, CdfNoise) => {
+                self.sensitivity() / scale
+            }
+            // the test spends `1 / scale`, the release `sensitivity() / scale` as for Laplace
+            (Counts, ProposeTestRelease { .. }) => (1.0 + self.sensitivity()) / scale,
+            (Counts, Binomial { n }) => binomial_scale_to_epsilon(n, self.sensitivity() as u64, self.delta),
+            (Counts, AnalyticGaussian) | (Counts, CorrelatedLaplace { .. }) | (Counts, Wavelet)
+            | (Counts, Hierarchical) | (Counts, TruncatedLaplace { .. }) => self.laplace_epsilon(),
+            (Frequencies, _) | (Counts, Gaussian) => {
+                let rho = (self.l2_sensitivity() / scale).powi(2) / 2.0;
+                rho + 2.0 * (rho * (1.0 / self.delta).ln()).sqrt()
+            }
+        }
+    }
+
+    /// Error bound that holds for every bucket at once with probability `1 - alpha`, by a
+    /// union bound over the buckets: `scale * ln(n / alpha)` for the Laplace family and
+    /// `scale * sqrt(2 ln(2n / alpha))` for Gaussian noise.
+    pub fn worst_case_error(&self) -> f64 {
+        let n_buckets = self.aggregated_data.len().max(1) as f64;
+        let scale = self.scale();
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) | (Counts, Snapping) => scale * (n_buckets / self.alpha).ln(),
+            // cutting the noise off at bounds around the count only takes mass from the tails
+            (Counts, TruncatedLaplace { lower, upper }) => (scale * (n_buckets / self.alpha).ln()).min(upper - lower),
+            (Counts, ProposeTestRelease { proposed_sensitivity }) => {
+                proposed_sensitivity as f64 * scale * (n_buckets / self.alpha).ln()
+            }
+            // every running total is off by at most this with probability 1 - alpha, which
+            // isotonic regression preserves, and a count is the difference of two of them
+            (Counts, CdfNoise) => 2.0 * n_buckets * scale * (n_buckets / self.alpha).ln(),
+            // a count sums the mean and one coefficient per level, with noise `scale / weight`
+            // each, and the inverse weights along the path add up to one: a union bound over
+            // the `m` coefficients of the padded histogram is left
+            (Counts, Wavelet) => {
+                let padded = self.aggregated_data.len().max(1).next_power_of_two() as f64;
+                scale * (padded / self.alpha).ln()
+            }
+            // least squares mixes the noise of the whole tree into every leaf: a union bound
+            // over its `2m - 1` nodes, times the most noise any leaf collects from them
+            (Counts, Hierarchical) => {
+                let n = self.aggregated_data.len();
+                let levels = tree_levels(n);
+                let nodes = (2 * n.max(1).next_power_of_two() - 1) as f64;
+                worst_leaf_noise(n, &vec![scale; levels]) * (nodes / self.alpha).ln()
+            }
+            // Hoeffding, P(|X - n/2| ≥ t) ≤ 2 e^(-2t²/n), and never beyond the support
+            (Counts, Binomial { n }) => {
+                ((n as f64 / 2.0) * (2.0 * n_buckets / self.alpha).ln()).sqrt().min(n as f64 / 2.0)
+            }
+            // every |z_k| stays below `scale * ln(n / alpha)` and a bucket sums |L_i|₁ of them
+            (Counts, CorrelatedLaplace { rho }) => {
+                let amplification = self.correlation_factor(rho).map_or(f64::INFINITY, |lower| noise_amplification(&lower));
+                amplification * scale * (n_buckets / self.alpha).ln()
+            }
+            (Frequencies, _) | (Counts, Gaussian) | (Counts, AnalyticGaussian) => {
+                scale * (2.0 * (2.0 * n_buckets / self.alpha).ln()).sqrt()
+            }
+            (Counts, Mixture { .. }) => {
+                // bounded by whichever component has the heavier tail at this level
+                let (laplace_scale, sigma) = self.mixture_scales();
+                (laplace_scale * (n_buckets / self.alpha).ln())
+                    .max(sigma * (2.0 * (2.0 * n_buckets / self.alpha).ln()).sqrt())
+            }
+        }
+    }
+
+    /// Runs the mechanism `n` times on the current aggregate, without touching `noised_data`.
+    pub fn run_n_trials(&self, n: usize) -> Vec<Vec<u64>> {
+        (0..n).filter_map(|_| self.noised_data(&self.aggregated_data)).collect()
+    }
+
+    /// Share of `n_runs` fresh runs of the mechanism whose output no other run repeated.
+    /// Despite the name this grows with the noise, 0 without any since every run then gives
+    /// the true counts: it measures how spread out the outputs are, not how well a record
+    /// hides. The OpenDP samplers draw from their own generator, so the runs are not seeded.
+    pub fn reidentification_risk_estimate(&self, n_runs: usize) -> f64 {
+        let runs = self.run_n_trials(n_runs);
+        let mut seen = BTreeMap::<&[u64], usize>::new();
+        for run in &runs {
+            *seen.entry(run.as_slice()).or_default() += 1;
+        }
+        let unique = runs.iter().filter(|run| seen[run.as_slice()] == 1).count();
+        unique as f64 / runs.len().max(1) as f64
+    }
+
+    /// Largest bucket error seen over `n` fresh runs of the mechanism.
+    pub fn observed_worst_error(&self, n: usize) -> f64 {
+        self.run_n_trials(n).iter()
+            .flat_map(|noised| noised.iter().zip(self.aggregated_data.iter()))
+            .map(|(&noised, &count)| noised.abs_diff(count) as f64)
+            .fold(0.0, f64::max)
+    }
+
+    /// Scales of the Laplace and Gaussian components of `Mixture`. Choosing the component at
+    /// random does not depend on the data, so the mixture is (epsilon, `delta`)-DP as soon as
+    /// both components are: Laplace gets `sensitivity / epsilon` and Gaussian the classic
+    /// `sqrt(2 ln(1.25 / delta)) * sensitivity / epsilon`, with epsilon the one Laplace
+    /// reaches at the current accuracy.
+    pub fn mixture_scales(&self) -> (f64, f64) {
+        let laplace_scale = accuracy_to_laplacian_scale(self.accuracy as f64, self.alpha).unwrap();
+        let sigma = (2.0 * (1.25 / self.delta).ln()).sqrt() * laplace_scale;
+        (laplace_scale, sigma)
+    }
+
+    /// Continuous density of the per-bucket noise, `None` when no noise is added.
+    pub fn noise_density(&self) -> Option<NoiseDensity> {
+        let scale = self.scale();
+        if scale == 0.0 {
+            return None;
+        }
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) | (Counts, Snapping) => Some(NoiseDensity::Laplace { scale }),
+            // the release step: noise and sensitivity both scaled by the proposed sensitivity
+            (Counts, ProposeTestRelease { proposed_sensitivity: 0 }) => None,
+            (Counts, ProposeTestRelease { .. }) => Some(NoiseDensity::Laplace { scale }),
+            // the n running totals get n times the noise for n times the sensitivity; the
+            // curve shows the one-dimensional Laplace mechanism with the same epsilon
+            (Counts, CdfNoise) => Some(NoiseDensity::Laplace { scale }),
+            // likewise the Laplace mechanism that the correlated noise is calibrated against
+            (Counts, CorrelatedLaplace { .. }) | (Counts, Wavelet) | (Counts, Hierarchical)
+            | (Counts, TruncatedLaplace { .. }) => {
+                Some(NoiseDensity::Laplace { scale: self.sensitivity() / self.epsilon() })
+            }
+            // the binomial is close to the Gaussian of the same variance except in the tails,
+            // where its support ends and its delta comes from
+            (Frequencies, _) | (Counts, Gaussian) | (Counts, AnalyticGaussian) | (Counts, Binomial { .. }) => {
+                Some(NoiseDensity::Gaussian { sigma: scale })
+            }
+            (Counts, Mixture { laplace_weight }) => {
+                let (laplace_scale, sigma) = self.mixture_scales();
+                Some(NoiseDensity::Mixture { laplace_weight, laplace_scale, sigma })
+            }
+        }
+    }
+
+    /// Privacy loss of the output actually released rather than of the worst output,
+    /// averaged over the buckets: for bucket noise `d = o - c` the loss against the worse of
+    /// the neighbours `c ± Δ` is `max ln(p(d) / p(d ∓ Δ))`. For Laplace noise on integer
+    /// counts with `Δ = 1` this is always `Δ / scale`, the worst case is attained by every
+    /// output; the Gaussian loss depends on the noise drawn and exceeds epsilon only with
+    /// probability delta. Mechanisms without a per-bucket density fall back to `epsilon()`.
+    pub fn data_dependent_privacy_loss(&self) -> f64 {
+        let density = match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) | (Counts, Snapping) | (Counts, Gaussian) | (Counts, AnalyticGaussian)
+            | (Counts, Mixture { .. }) => self.noise_density(),
+            _ => None,
+        };
+        let Some(density) = density.filter(|_| !self.noised_data.is_empty()) else {
+            return self.epsilon();
+        };
+        let sensitivity = self.sensitivity();
+        let losses = self.aggregated_data.iter().zip(self.noised_data.iter())
+            .map(|(&count, &noised)| {
+                let noise = noised as f64 - count as f64;
+                [sensitivity, -sensitivity].iter()
+                    .map(|shift| (density.pdf(noise) / density.pdf(noise - shift)).ln())
+                    .fold(f64::NEG_INFINITY, f64::max)
+            })
+            .collect::<Vec<_>>();
+        losses.iter().sum::<f64>() / losses.len() as f64
+    }
+
+    /// f-DP trade-off function of the current mechanism, see `accounting::tradeoff_curve`.
+    /// Without noise the neighbouring outputs are perfectly distinguishable, so every type I
+    /// error rate comes with a zero type II error.
+    pub fn compute_f_dp_tradeoff(&self, n_points: usize) -> Vec<(f64, f64)> {
+        match self.noise_density() {
+            Some(density) => tradeoff_curve(density, self.sensitivity(), n_points),
+            None => (0..n_points).map(|i| (i as f64 / (n_points.max(2) - 1) as f64, 0.0)).collect(),
+        }
+    }
+
+    /// Privacy loss random variable of one bucket, `None` without noise and for the
+    /// mechanisms other than Laplace, Snapping and the Gaussians.
+    pub fn privacy_loss(&self) -> Option<PrivacyLoss> {
+        if self.scale() == 0.0 {
+            return None;
+        }
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) | (Counts, Snapping) => Some(PrivacyLoss::Laplace { epsilon: self.epsilon() }),
+            _ => self.gdp_mu().map(|mu| PrivacyLoss::Gaussian { mu }),
+        }
+    }
+
+    /// Density of the privacy loss at `n_points` evenly spaced values over its support,
+    /// empty when `privacy_loss` is `None`.
+    pub fn privacy_loss_distribution(&self, n_points: usize) -> Vec<(f64, f64)> {
+        let Some(privacy_loss) = self.privacy_loss() else {
+            return vec![];
+        };
+        let (lower, upper) = privacy_loss.support();
+        (0..n_points)
+            .map(|i| {
+                let z = lower + (upper - lower) * i as f64 / (n_points.max(2) - 1) as f64;
+                (z, privacy_loss.pdf(z))
+            })
+            .collect()
+    }
+
+    /// `|-log2 p_i + log2 q_i|`, the bits of Shannon information content that bucket `i`
+    /// gains or loses through the noise, with `p` and `q` the true and noised histograms
+    /// normalized. `None` for buckets empty on either side, whose information content is
+    /// infinite.
+    pub fn information_content_change(&self) -> Vec<Option<f64>> {
+        let (p, q) = match self.noise_target {
+            Counts => (to_frequencies(&self.aggregated_data), to_frequencies(&self.noised_data)),
+            Frequencies => (self.aggregated_frequencies.clone(), self.noised_frequencies.clone()),
+        };
+        p.iter().zip(q.iter())
+            .map(|(&p, &q)| (p > 0.0 && q > 0.0).then(|| (p.log2() - q.log2()).abs()))
+            .collect()
+    }
+
+    pub fn mean_squared_error(&self) -> f64 {
+        metrics::mean_squared_error(&self.aggregated_data, &self.noised_data)
+    }
+
+    /// Average MSE over `n` fresh runs of the mechanism, steadier than that of one release.
+    pub fn average_mean_squared_error(&self, n: usize) -> f64 {
+        let trials = self.run_n_trials(n);
+        trials.iter().map(|noised| metrics::mean_squared_error(&self.aggregated_data, noised)).sum::<f64>()
+            / trials.len().max(1) as f64
+    }
+
+    /// Per-bucket MSE of continuous Laplace noise at the current epsilon, `2 (Δ / ε)²`; the
+    /// total over the buckets is `n` times that. A yardstick rather than the optimum: the
+    /// discrete Laplace on integer counts already does better, as does the staircase
+    /// mechanism of Geng and Viswanath 2014 at large epsilon.
+    pub fn laplace_baseline_mse(&self) -> f64 {
+        2.0 * (self.sensitivity() / self.epsilon()).powi(2)
+    }
+
+    /// Whether the counts carry independent Laplace noise of scale `sensitivity / epsilon`,
+    /// the noise `private_z_test` and `proportion_intervals` are worked out for.
+    fn has_laplace_counts(&self) -> bool {
+        matches!((self.noise_target, self.noise_type), (Counts, Laplace) | (Counts, Snapping))
+    }
+
+    /// `noisy_counts_z_test` of whether buckets `bucket_a` and `bucket_b` are equally likely,
+    /// on the noised counts with the variance of Laplace noise at the current epsilon,
+    /// `laplace_baseline_mse`. It reads only the released counts, so it is post-processing:
+    /// the release stays exactly as private as it was, however many tests are run on it.
+    /// `None` for any other noise, whose variance this would misstate.
+    pub fn private_z_test(&self, bucket_a: usize, bucket_b: usize, alpha: f64) -> Option<HypothesisTestResult> {
+        if !self.has_laplace_counts() {
+            return None;
+        }
+        let noise_variance = if self.epsilon().is_finite() { self.laplace_baseline_mse() } else { 0.0 };
+        Some(noisy_counts_z_test(self.noised_data[bucket_a] as f64, self.noised_data[bucket_b] as f64, noise_variance, alpha))
+    }
+
+    /// `noised_proportion_ci` of every noised count at `alpha`, with the Laplace noise variance
+    /// at the current epsilon and the sum of the noised counts for the total, turned back
+    /// into counts. Like the z-test it reads only the release, and it is empty for noise
+    /// other than Laplace, whose tails the margin does not bound.
+    pub fn proportion_intervals(&self, alpha: f64) -> Vec<(u64, u64)> {
+        if !self.has_laplace_counts() {
+            return vec![];
+        }
+        let noise_variance = if self.epsilon().is_finite() { self.laplace_baseline_mse() } else { 0.0 };
+        let n = self.noised_data.iter().sum::<u64>();
+        self.noised_data.iter()
+            .map(|&count| {
+                let (lower, upper) = noised_proportion_ci(count, n, noise_variance, alpha);
+                ((lower * n as f64).floor() as u64, (upper * n as f64).ceil() as u64)
+            })
+            .collect()
+    }
+
+    /// `(average MSE - baseline) / baseline` in percent against `laplace_baseline_mse`, at
+    /// `n` runs; negative when the current mechanism beats continuous Laplace.
+    pub fn utility_regret(&self, n: usize) -> f64 {
+        let baseline = self.laplace_baseline_mse();
+        (self.average_mean_squared_error(n) - baseline) / baseline * 100.0
+    }
+
+    /// For Laplace noise with scale `b` this should hover around `b ln 2`, the median of
+    /// `|Laplace(b)|`, see `expected_median_absolute_deviation`.
+    pub fn median_absolute_deviation(&self) -> f64 {
+        metrics::median_absolute_deviation(&self.aggregated_data, &self.noised_data)
+    }
+
+    /// The noised histogram as a distribution, to compare against `aggregated_frequencies`.
+    fn noised_distribution(&self) -> Vec<f64> {
+        match self.noise_target {
+            Counts => to_frequencies(&self.noised_data),
+            Frequencies => self.noised_frequencies.clone(),
+        }
+    }
+
+    pub fn histogram_l1_distance(&self) -> f64 {
+        metrics::l1_distance(&self.aggregated_frequencies, &self.noised_distribution())
+    }
+
+    pub fn histogram_l2_distance(&self) -> f64 {
+        metrics::l2_distance(&self.aggregated_frequencies, &self.noised_distribution())
+    }
+
+    /// `earth_movers_distance` in buckets between the true and the noised distribution.
+    pub fn histogram_earth_movers_distance(&self) -> f64 {
+        metrics::earth_movers_distance(&self.aggregated_frequencies, &self.noised_distribution())
+    }
+
+    /// Share of the buckets of the histogram without a record.
+    pub fn sparsity(&self) -> f64 {
+        let empty = self.aggregated_data.iter().filter(|&&count| count == 0).count();
+        empty as f64 / self.aggregated_data.len().max(1) as f64
+    }
+
+    /// MSE per bucket, over `trials` runs, of `sparse_gaussian_mechanism` and of Gaussian
+    /// noise on every bucket at the same (`epsilon`, delta).
+    pub fn sparse_gaussian_mse(&self, epsilon: f64, trials: usize) -> (f64, f64) {
+        let mut rng = rand::thread_rng();
+        let counts = self.aggregated_data.iter().map(|&count| count as f64).collect::<Vec<_>>();
+        let sigma = analytic_gaussian_scale(epsilon, self.delta, 1.0);
+        let (sparse, dense) = (0..trials).fold((0.0, 0.0), |(sparse, dense), _| {
+            let noised = gaussian_noise(&counts, sigma, &mut rng).iter()
+                .map(|count| count.max(0.0).round() as u64)
+                .collect::<Vec<_>>();
+            (sparse + metrics::mean_squared_error(&self.aggregated_data, &sparse_gaussian_mechanism(&self.aggregated_data, epsilon, self.delta, &mut rng)),
+             dense + metrics::mean_squared_error(&self.aggregated_data, &noised))
+        });
+        (sparse / trials.max(1) as f64, dense / trials.max(1) as f64)
+    }
+
+    /// `distributional_epsilon` of the Laplace counts at `delta` for an adversary who only
+    /// knows the distribution the other records are drawn from: `"uniform"` over the buckets,
+    /// or `"empirical"` for the shares observed in the data, where the bucket with the least
+    /// cover sets the epsilon. It only holds while the other records stay unknown to the
+    /// adversary. Any other input distribution, or other noise, gives the worst-case
+    /// `epsilon()`.
+    pub fn distributional_dp_epsilon(&self, input_distribution: &str) -> f64 {
+        let epsilon = self.epsilon();
+        if (self.noise_target, self.noise_type) != (Counts, Laplace) {
+            return epsilon;
+        }
+        let others = self.aggregated_data.iter().sum::<u64>().saturating_sub(1) as usize;
+        let shares = match input_distribution {
+            "uniform" => vec![1.0 / self.buckets().len().max(1) as f64],
+            "empirical" => self.aggregated_frequencies.clone(),
+            _ => return epsilon,
+        };
+        shares.iter()
+            .map(|&p| distributional_epsilon(others, p, self.scale(), self.delta))
+            .fold(0.0, f64::max)
+            .min(epsilon)
+    }
+
+    /// Theoretical MAD of the Laplace family, `None` for the other mechanisms.
+    pub fn expected_median_absolute_deviation(&self) -> Option<f64> {
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) | (Counts, Snapping) => Some(self.scale() * std::f64::consts::LN_2),
+            (Counts, ProposeTestRelease { proposed_sensitivity }) => {
+                Some(proposed_sensitivity as f64 * self.scale() * std::f64::consts::LN_2)
+            }
+            _ => None,
+        }
+    }
+
+    /// The current noised counts, tagged with the time and the parameters they were released under.
+    pub fn dp_release_with_timestamp(&self) -> DpRelease {
+        DpRelease {
+            timestamp: SystemTime::now(),
+            field: self.aggregate_field.to_string(),
+            noise_type: self.noise_type,
+            epsilon: self.epsilon(),
+            noised_counts: self.noised_data.clone(),
+        }
+    }
+
+    pub fn release_history(&self) -> &VecDeque<DpRelease> {
+        &self.releases
+    }
+
+    /// Appends the current release to the history, dropping the oldest one past
+    /// `MAX_RELEASES`.
+    fn remember_release(&mut self) {
+        if self.releases.len() == MAX_RELEASES {
+            self.releases.pop_front();
+        }
+        self.releases.push_back(self.dp_release_with_timestamp());
+    }
+
+    /// Privacy loss on top of the current release for an adversary who also holds the
+    /// releases of other fields among `previous_releases`: every record is in every column,
+    /// so those can be joined with the current one record by record. By basic composition
+    /// the losses add up, so this is the sum of their epsilons. The noiseless views never
+    /// enter the history, and an infinite epsilon would say nothing anyway.
+    pub fn auxiliary_information_risk(&self, previous_releases: &[DpRelease]) -> f64 {
+        previous_releases.iter()
+            .filter(|release| release.field != self.aggregate_field && release.epsilon.is_finite())
+            .map(|release| release.epsilon)
+            .sum()
+    }
+
+    pub fn report_card(&self) -> ReportCard {
+        ReportCard::new(self.epsilon(), self.mean_squared_error(), self.accuracy)
+    }
+
+    /// Facts of the current release for its `NutritionLabel`, with delta as on the odometer.
+    /// The number of records is left out: under a condition it is a private count.
+    pub fn nutrition_label(&self) -> NutritionLabel {
+        let epsilon = self.epsilon();
+        let target = match self.noise_target {
+            Counts => "counts",
+            Frequencies => "relative frequencies",
+        };
+        let condition = self.condition.map_or(String::new(), |(field, value)| format!(" where {} = {}", field, value));
+        NutritionLabel {
+            mechanism: self.noise_type.to_string(),
+            epsilon,
+            delta: self.delta_for_epsilon(epsilon).unwrap_or(0.0),
+            sensitivity: self.sensitivity(),
+            query: format!("Histogram of {}, noised {}", self.aggregate_field, target),
+            data: format!("All records{}, {} buckets", condition, self.aggregated_data.len()),
+            error_bound: self.worst_case_error(),
+            alpha: self.alpha,
+        }
+    }
+
+    pub fn buckets(&self) -> Vec<String> {
+        self.dataset.aggregate_buckets(self.aggregate_field)
+    }
+
+    /// Buckets of the current field that hold at least one record under `condition`.
+    pub fn populated_buckets(&self) -> Option<Vec<String>> {
+        let (given_field, given_value) = self.condition?;
+        Some(self.dataset.aggregate_buckets_given(self.aggregate_field, given_field, given_value))
+    }
+
+    /// Checks OpenDP's accuracy-to-scale calibration for the discrete Laplace mechanism:
+    /// at the scale for `target_accuracy` and `target_alpha`, no more than a fraction
+    /// `target_alpha` of `n_samples` noise values may exceed `target_accuracy` in absolute
+    /// value, up to three standard errors of sampling slack.
+    pub fn laplace_calibration_test(&self, n_samples: usize, target_accuracy: usize, target_alpha: f64) -> bool {
+        let scale = match accuracy_to_discrete_laplacian_scale(target_accuracy as f64, target_alpha) {
+            Ok(scale) => scale,
+            Err(_) => return false,
+        };
+        let noise = make_base_discrete_laplace::<VectorDomain<AllDomain<i64>>, _>(scale)
+            .and_then(|measurement| measurement.invoke(&vec![0; n_samples]));
+        let noise = match noise {
+            Ok(noise) => noise,
+            Err(_) => return false,
+        };
+        let exceeded = noise.iter().filter(|&&x| x.unsigned_abs() as usize > target_accuracy).count();
+        let tolerance = 3.0 * (target_alpha * (1.0 - target_alpha) / n_samples.max(1) as f64).sqrt();
+        exceeded as f64 / n_samples.max(1) as f64 <= target_alpha + tolerance
+    }
+
+    /// Largest `ln(P(M(x) = o) / P(M(x') = o))` over neighbouring `x, x'` and outputs `o`,
+    /// worked out from the noise distribution: `Δ / scale` for Laplace counts, which every
+    /// output attains, and infinite for Gaussian noise, whose loss grows without bound in
+    /// the tails and which is only (epsilon, `delta`)-DP. `None` for the other mechanisms.
+    pub fn verify_worst_case_privacy_loss(&self) -> Option<f64> {
+        let scale = self.scale();
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) if scale > 0.0 => Some(self.sensitivity() / scale),
+            (Counts, Laplace) | (Frequencies, _) | (Counts, Gaussian) | (Counts, AnalyticGaussian) => {
+                Some(f64::INFINITY)
+            }
+            _ => None,
+        }
+    }
+
+    /// Worst-case privacy loss against an adversary who already knows that the target's
+    /// value falls in the buckets `known_bucket_range`, both ends included. The hypotheses
+    /// left are the target's absence and its presence in one of those buckets: one record in
+    /// one count apart, the loss of `verify_worst_case_privacy_loss`, or between two buckets
+    /// one record in two counts, twice that. Side information only rules hypotheses out, so
+    /// this never exceeds the loss of replacing the target's record without it, and knowing
+    /// the exact bucket leaves only the question of presence. `None` where
+    /// `verify_worst_case_privacy_loss` is and for ranges past the last bucket.
+    pub fn conditional_privacy_loss(&self, known_bucket_range: (usize, usize)) -> Option<f64> {
+        let (first, last) = known_bucket_range;
+        if first > last || last >= self.aggregated_data.len() {
+            return None;
+        }
+        let presence = self.verify_worst_case_privacy_loss()?;
+        Some(if first == last { presence } else { 2.0 * presence })
+    }
+
+    /// The privacy parameter of the current OpenDP measurement worked out here and as
+    /// OpenDP maps the sensitivity through the measurement: epsilon of the discrete
+    /// Laplace, and the zCDP `ρ = Δ² / (2σ²)` of the discrete Gaussian, the guarantee it is
+    /// built for. `None` for the mechanisms that do not come from OpenDP.
+    pub fn opendp_privacy_parameter(&self) -> Option<(f64, f64)> {
+        let scale = self.scale();
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) => {
+                let sensitivity = self.sensitivity();
+                let measurement = make_base_discrete_laplace::<VectorDomain<AllDomain<u64>>, _>(scale).ok()?;
+                Some((sensitivity / scale, measurement.map(&(sensitivity as u64)).ok()?))
+            }
+            (Counts, Gaussian) => {
+                let sensitivity = self.l2_sensitivity();
+                let measurement = make_base_discrete_gaussian::<VectorDomain<AllDomain<u64>>, ZeroConcentratedDivergence<f64>, f64>(
                     scale
                 ).ok()?;
-                Option::from(discrete_lp.invoke(&aggregated_data).unwrap())
+                Some(((sensitivity / scale).powi(2) / 2.0, measurement.map(&sensitivity).ok()?))
             }
-            Gaussian => {
-                let scale = accuracy_to_discrete_gaussian_scale(self.accuracy as f64, self.alpha).unwrap();
-                let discrete_gaussian =
-                    make_base_discrete_gaussian::<VectorDomain<AllDomain<u64>>, ZeroConcentratedDivergence<f64>, f64>(
-                        scale
-                    ).ok()?;
-                Option::from(discrete_gaussian.invoke(&aggregated_data).unwrap())
+            _ => None,
+        }
+    }
+
+    /// Total variation distances between `n_samples` draws of
+    /// `discrete_laplace_rejection_sample`, as many from OpenDP's discrete Laplace and the
+    /// exact pmf, at the current scale: (rejection vs OpenDP, rejection vs exact, OpenDP vs
+    /// exact). OpenDP samples with the linear-time geometric sampler up to scale 10 and the
+    /// algorithm of Canonne, Kamath, Steinke above, from system entropy that cannot be
+    /// seeded; only the distributions can agree, up to sampling error of order
+    /// `sqrt(support / n_samples)`.
+    pub fn compare_discrete_laplace_samplers(&self, n_samples: usize) -> Option<(f64, f64, f64)> {
+        let scale = self.scale();
+        let opendp = make_base_discrete_laplace::<VectorDomain<AllDomain<i64>>, _>(scale)
+            .and_then(|measurement| measurement.invoke(&vec![0; n_samples]))
+            .ok()?;
+        let rejection = (0..n_samples as u64)
+            .map(|seed| discrete_laplace_rejection_sample(scale, seed))
+            .collect::<Vec<_>>();
+        let frequencies = |samples: &[i64]| {
+            let mut frequencies = BTreeMap::new();
+            for &sample in samples {
+                *frequencies.entry(sample).or_insert(0.0) += 1.0 / samples.len().max(1) as f64;
+            }
+            frequencies
+        };
+        let (rejection, opendp) = (frequencies(&rejection), frequencies(&opendp));
+        let support = rejection.keys().chain(opendp.keys()).copied().collect::<BTreeSet<_>>();
+        let frequency = |frequencies: &BTreeMap<i64, f64>, x| frequencies.get(&x).copied().unwrap_or(0.0);
+        let between = support.iter().map(|&x| (frequency(&rejection, x) - frequency(&opendp, x)).abs()).sum::<f64>() / 2.0;
+        // the exact mass outside the samples counts in full
+        let to_exact = |frequencies: &BTreeMap<i64, f64>| {
+            let seen = support.iter().map(|&x| discrete_laplace_pmf(x, scale)).sum::<f64>();
+            (support.iter().map(|&x| (frequency(frequencies, x) - discrete_laplace_pmf(x, scale)).abs()).sum::<f64>()
+                + (1.0 - seen).max(0.0)) / 2.0
+        };
+        Some((between, to_exact(&rejection), to_exact(&opendp)))
+    }
+
+    /// Histogram of `field` over the values that occur in the data, for fields whose values
+    /// are not known in advance (the stability-based histogram of Korolova et al. 2009 and
+    /// Bun, Nissim, Stemmer 2016). There is no list of all possible values to noise, so
+    /// only the observed values get `Laplace(1 / epsilon)` noise and only the noised counts
+    /// above `threshold` are released. A value held by a single record shows up on one side
+    /// of a neighbouring pair only, which no amount of noise makes pure DP; with `threshold`
+    /// at least `stability_threshold(epsilon, delta)` it is (epsilon, delta)-DP.
+    pub fn private_histogram_unknown_domain(&self, field: &str, epsilon: f64, threshold: f64) -> BTreeMap<String, u64> {
+        let mut rng = rand::thread_rng();
+        self.dataset.value_counts(field).into_iter()
+            .map(|(value, count)| (value, count as f64 + laplace_sample(1.0 / epsilon, &mut rng)))
+            .filter(|&(_, noised)| noised > threshold)
+            .map(|(value, noised)| (value, noised.round() as u64))
+            .collect()
+    }
+
+    /// Entries of `unknown_domain_histogram` that are not among the fixed buckets.
+    pub fn values_outside_buckets(&self) -> Vec<(&str, u64)> {
+        let buckets = self.dataset.aggregate_buckets(self.aggregate_field);
+        self.unknown_domain_histogram.iter()
+            .filter(|(value, _)| !buckets.contains(value))
+            .map(|(value, &count)| (value.as_str(), count))
+            .collect()
+    }
+
+    /// Noised number of records matching `pred`, for questions the fixed buckets cannot
+    /// express. Adding or removing a record changes a count by at most 1, so the noise of
+    /// the current type and scale is used as is.
+    pub fn count_with_predicate<F>(&self, pred: F) -> Result<u64, PriviError>
+        where F: Fn(&Record) -> bool {
+        let records = read_data(&self.dataset.data, self.dataset.separator)
+            .map_err(|err| PriviError::InvalidData(err.to_string()))?;
+        let count = records.iter().filter(|record| pred(record)).count() as u64;
+        if let ProposeTestRelease { proposed_sensitivity } = self.noise_type {
+            if !self.ptr_test(proposed_sensitivity) {
+                return Err(PriviError::Mechanism(String::from("PTR test failed, nothing released")));
             }
         }
+        self.noised_data(&vec![count])
+            .and_then(|noised| noised.first().copied())
+            .ok_or_else(|| PriviError::Mechanism(format!("{} noise at scale {}", self.noise_type, self.scale())))
     }
-}
 
-impl<'a> NoiseApplier<'a> for Noiser<'a> {
-    fn new(dataset: &'a CsvDataSet, aggregate_field: &'a String) -> Self {
-        Noiser {
-            dataset,
-            aggregate_field,
-            noise_type: Laplace,
-            accuracy: 0,
-            alpha: 0.05,
-            aggregated_data: Vec::<u64>::new(),
-            noised_data: Vec::<u64>::new(),
+    /// Central epsilon of the randomized response answers of `local_dp_count` at the current
+    /// epsilon, had they passed through a shuffler, see `accounting::shuffler_amplification`.
+    pub fn shuffled_epsilon(&self) -> f64 {
+        shuffler_amplification(self.epsilon(), self.dataset.record_count(), self.delta)
+    }
+
+    /// Simulates every record's owner answering a yes/no question with randomized
+    /// response, `true_p` of them truthfully holding "yes", and returns the de-biased count.
+    /// Local noise is paid per person rather than once per count, so this is much noisier
+    /// than the central count at the same epsilon.
+    pub fn local_dp_count(&self, true_p: f64, epsilon: f64) -> u64 {
+        let n = self.dataset.record_count();
+        let true_yes = (true_p.clamp(0.0, 1.0) * n as f64).round() as usize;
+        let answers = (0..n).map(|i| i < true_yes).collect::<Vec<_>>();
+        let estimate = randomized_response_count(&answers, epsilon, &mut rand::thread_rng());
+        estimate.round().clamp(0.0, n as f64) as u64
+    }
+
+    /// Histogram of `aggregate_field` with the owner of every record, at its position
+    /// `values` from `bucket_indices`, reporting by `local_hashing` into `g` values at
+    /// `epsilon`, each report drawn from the generator of `seed`.
+    pub fn local_hashing_histogram(&self, values: &[usize], g: usize, epsilon: f64, seed: u64) -> Vec<u64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        local_hashing(values, self.buckets().len() + 1, g, epsilon, &mut rng).iter()
+            .map(|estimate| estimate.max(0.0).round() as u64)
+            .collect()
+    }
+
+    /// Exact histogram of `aggregate_field` after input perturbation: every record's bucket,
+    /// its position `values` from `bucket_indices`, is replaced before counting by
+    /// generalized randomized response at `epsilon`, drawn from the generator of `seed`. Sending a record only to a neighbouring bucket would not
+    /// do: a record could then never land far away, and seeing it there would rule out every
+    /// distant value, so every bucket has to stay reachable.
+    pub fn input_perturbation_histogram(&self, values: &[usize], epsilon: f64, seed: u64) -> Vec<u64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let domain = self.buckets().len() + 1;
+        let mut counts = vec![0; domain];
+        for value in randomized_values(values, domain, epsilon, &mut rng) {
+            counts[value] += 1;
         }
+        counts
     }
 
-    fn toggle_noise_type(&mut self) {
-        if self.noise_type == Laplace {
-            self.noise_type = Gaussian;
+    /// MSE per bucket, over `trials` runs at `epsilon`, of the `input_perturbation_histogram`
+    /// as counted, of the same with the bias of the perturbation taken out, and of output
+    /// perturbation, `Laplace(Δ / epsilon)` on the true counts. Perturbing every record
+    /// pays the noise `n` times over, so output perturbation wins by orders of magnitude.
+    /// `None` without noise.
+    pub fn perturbation_mse(&self, epsilon: f64, trials: usize) -> Option<(f64, f64, f64)> {
+        if !epsilon.is_finite() || self.aggregated_data.is_empty() {
+            return None;
+        }
+        let n = self.aggregated_data.iter().sum::<u64>() as usize;
+        let values = self.bucket_indices();
+        let mut rng = StdRng::seed_from_u64(INPUT_PERTURBATION_SEED);
+        let (counted, debiased, output) = (0..trials as u64).fold((0.0, 0.0, 0.0), |(counted, debiased, output), trial| {
+            let perturbed = self.input_perturbation_histogram(&values, epsilon, INPUT_PERTURBATION_SEED + trial);
+            let reported = perturbed.iter().map(|&count| count as f64).collect::<Vec<_>>();
+            let estimate = debiased_counts(&reported, n, perturbed.len(), epsilon).iter()
+                .map(|count| count.max(0.0).round() as u64)
+                .collect::<Vec<_>>();
+            let noised = self.aggregated_data.iter()
+                .map(|&count| (count as f64 + laplace_sample(SENSITIVITY / epsilon, &mut rng)).max(0.0).round() as u64)
+                .collect::<Vec<_>>();
+            (counted + metrics::mean_squared_error(&self.aggregated_data, &perturbed),
+             debiased + metrics::mean_squared_error(&self.aggregated_data, &estimate),
+             output + metrics::mean_squared_error(&self.aggregated_data, &noised))
+        });
+        let trials = trials.max(1) as f64;
+        Some((counted / trials, debiased / trials, output / trials))
+    }
+
+    /// `optimal_hash_range` at `epsilon` and the MSE per bucket, over `trials` runs, of the
+    /// `local_hashing_histogram` in that range and of generalized randomized response over
+    /// all the buckets, both `epsilon`-LDP. Randomized response loses as the buckets grow
+    /// past about `3 e^ε + 2`. `None` without noise.
+    pub fn local_histogram_mse(&self, epsilon: f64, trials: usize) -> Option<(usize, f64, f64)> {
+        if !epsilon.is_finite() || self.aggregated_data.is_empty() {
+            return None;
+        }
+        let g = optimal_hash_range(epsilon);
+        let values = self.bucket_indices();
+        let domain = self.buckets().len() + 1;
+        let mut rng = StdRng::seed_from_u64(LDP_SEED);
+        let (hashing, direct) = (0..trials as u64).fold((0.0, 0.0), |(hashing, direct), trial| {
+            let hashed = self.local_hashing_histogram(&values, g, epsilon, LDP_SEED + trial);
+            let responses = generalized_randomized_response(&values, domain, epsilon, &mut rng).iter()
+                .map(|estimate| estimate.max(0.0).round() as u64)
+                .collect::<Vec<_>>();
+            (hashing + metrics::mean_squared_error(&self.aggregated_data, &hashed),
+             direct + metrics::mean_squared_error(&self.aggregated_data, &responses))
+        });
+        Some((g, hashing / trials.max(1) as f64, direct / trials.max(1) as f64))
+    }
+
+    /// Number of records to add or remove before the local sensitivity of the histogram
+    /// exceeds `proposed`. Adding or removing a record moves exactly one count by one on
+    /// every data set, or by up to `k` under suppression, so the local sensitivity is
+    /// `sensitivity()` everywhere: the distance is infinite for any proposal of at least
+    /// that and zero below. Queries such as a median, whose local
+    /// sensitivity depends on the data, are where PTR saves budget.
+    pub fn distance_to_instability(&self, proposed: u64) -> f64 {
+        if proposed as f64 >= self.sensitivity() {
+            f64::INFINITY
         } else {
-            self.noise_type = Laplace;
+            0.0
         }
-        self.refresh_data()
     }
 
-    fn increase_noise(&mut self) {
-        self.accuracy = (self.accuracy + 1) % ACCURACY_VALUES.len();
-        self.refresh_data()
+    /// Distance to instability of the mean of `PTR_MEAN_FIELD` when records are added or
+    /// removed, so that `n` is private. Adding or removing one of `n'` records moves the
+    /// mean by at most `upper / (n' - 1)`, which stays within `proposed` while more than
+    /// `upper / proposed + 1` records are left; the floor keeps this a lower bound.
+    pub fn mean_distance_to_instability(&self, proposed: f64) -> f64 {
+        let upper = field_bounds(PTR_MEAN_FIELD).map_or(f64::INFINITY, |(_, upper)| upper);
+        (self.dataset.record_count() as f64 - 1.0 - upper / proposed).max(0.0).floor()
     }
 
-    fn decrease_noise(&mut self) {
-        self.accuracy = (self.accuracy + ACCURACY_VALUES.len() - 1) % ACCURACY_VALUES.len();
-        self.refresh_data()
+    /// Noise scale of the PTR mean, which spends half of `epsilon()` on the test and half
+    /// on the release, next to the scale of the global calibration: with `n` private a
+    /// single record can be the whole data set, so the global sensitivity is `upper`. Were
+    /// `n` public it would be `upper / n` and PTR would have nothing to gain.
+    pub fn ptr_mean_scales(&self) -> (f64, f64) {
+        let upper = field_bounds(PTR_MEAN_FIELD).map_or(f64::INFINITY, |(_, upper)| upper);
+        let epsilon = self.epsilon();
+        (PTR_MEAN_PROPOSED_SENSITIVITY / (epsilon / 2.0), upper / epsilon)
     }
 
-    fn refresh_data(&mut self) {
-        self.clear_previous_data();
-        self.aggregated_data = self.aggregate_data().unwrap();
-        self.noised_data = self.noised_data(&self.aggregated_data).unwrap();
+    /// Number of records and histogram of `aggregate_field` without taking the size of the
+    /// data set as known: `Laplace(Δ / (PRIVATE_COUNT_SHARE epsilon))` on the count and
+    /// `Laplace(Δ / ((1 - PRIVATE_COUNT_SHARE) epsilon))` on the buckets, `epsilon` in total
+    /// by sequential composition. Under adding or removing a record the counts never needed
+    /// the size, only frequencies and means divide by it; the sum of the noised counts
+    /// would also estimate it for free, with `k` buckets' worth of noise.
+    pub fn stability_extension_histogram(&self, epsilon: f64) -> (u64, Vec<u64>) {
+        let mut rng = rand::thread_rng();
+        let mut noised = |count: u64, share: f64| {
+            (count as f64 + laplace_sample(SENSITIVITY / (share * epsilon), &mut rng)).max(0.0).round() as u64
+        };
+        let count = noised(self.aggregated_data.iter().sum(), PRIVATE_COUNT_SHARE);
+        let histogram = self.aggregated_data.iter().map(|&bucket| noised(bucket, 1.0 - PRIVATE_COUNT_SHARE)).collect();
+        (count, histogram)
+    }
+
+    /// Mean of `PTR_MEAN_FIELD` by Propose-Test-Release at `epsilon()` and `delta`,
+    /// `None` without noise or when the test fails.
+    fn noised_ptr_mean(&self) -> Option<f64> {
+        let epsilon = self.epsilon();
+        if epsilon.is_infinite() {
+            return None;
+        }
+        let mut rng = rand::thread_rng();
+        let distance = self.mean_distance_to_instability(PTR_MEAN_PROPOSED_SENSITIVITY);
+        if !propose_test_release(distance, 2.0 / epsilon, self.delta, &mut rng) {
+            return None;
+        }
+        let (lower, upper) = field_bounds(PTR_MEAN_FIELD)?;
+        let records = read_data(&self.dataset.data, self.dataset.separator).ok()?;
+        let values = records.iter()
+            .filter_map(|record| record.field_value(PTR_MEAN_FIELD))
+            .map(|value| value.clamp(lower, upper))
+            .collect::<Vec<_>>();
+        let (release_scale, _) = self.ptr_mean_scales();
+        Some(values.iter().sum::<f64>() / values.len().max(1) as f64 + laplace_sample(release_scale, &mut rng))
+    }
+
+    fn ptr_test(&self, proposed_sensitivity: u64) -> bool {
+        let distance = self.distance_to_instability(proposed_sensitivity);
+        propose_test_release(distance, self.scale(), self.delta, &mut rand::thread_rng())
+    }
+
+    /// L2 sensitivity the Gaussian noise is calibrated to. A record sits in a single bucket,
+    /// so adding or removing it changes one count and the L2 sensitivity equals the L1
+    /// sensitivity of `sensitivity()`; it is not `sqrt(number of buckets)` times that. The
+    /// frequencies divide by the number of records, which only stays fixed if a record is
+    /// replaced rather than added or removed: that moves two buckets, for `sqrt(2)` times more.
+    pub fn l2_sensitivity(&self) -> f64 {
+        match self.noise_target {
+            Counts => l2_sensitivity(self.sensitivity(), BUCKETS_PER_RECORD),
+            Frequencies => l2_sensitivity(self.sensitivity(), 2 * BUCKETS_PER_RECORD),
+        }
+    }
+
+    /// L2 sensitivity of OpenDP's count transformation for one added or removed record, as
+    /// its stability map reports it, `None` when the transformation cannot be built.
+    pub fn opendp_l2_sensitivity(&self) -> Option<f64> {
+        let chain = aggregate_data_chain(self, self.aggregate_field)?;
+        chain.map(&1).ok().map(f64::from)
+    }
+
+    /// Suppression lets a single record move a bucket between 0 and `k`, so it raises the
+    /// sensitivity of the counts from 1 to `k`.
+    pub fn sensitivity(&self) -> f64 {
+        match (self.noise_target, self.suppression_k) {
+            (Counts, Some(k)) => (k as f64).max(SENSITIVITY),
+            _ => SENSITIVITY,
+        }
+    }
+
+    /// Sensitivity of `query_type` over `aggregate_field`, from the field's bounds.
+    pub fn query_sensitivity(&self) -> f64 {
+        let bounds = field_bounds(self.aggregate_field).unwrap_or((0.0, 1.0));
+        self.query_type.sensitivity(bounds, self.dataset.record_count())
+    }
+
+    /// The budget, or `DEFAULT_PLAN_BUDGET` without one, and every query of `PLANNED_QUERIES`
+    /// over the current field with its sensitivity and its epsilon under an equal split and
+    /// under `allocate_budget`.
+    pub fn budget_plan(&self) -> (f64, Vec<(QueryType, f64, f64, f64)>) {
+        let total = self.budget.unwrap_or(DEFAULT_PLAN_BUDGET);
+        let bounds = field_bounds(self.aggregate_field).unwrap_or((0.0, 1.0));
+        let n = self.dataset.record_count();
+        let equal = total / PLANNED_QUERIES.len() as f64;
+        let plan = PLANNED_QUERIES.iter().zip(allocate_budget(total, &PLANNED_QUERIES, bounds, n))
+            .map(|(&query, optimal)| (query, query.sensitivity(bounds, n), equal, optimal))
+            .collect();
+        (total, plan)
+    }
+
+    /// Laplace scale that gives `query_type` the same epsilon as the histogram.
+    pub fn query_scale(&self) -> f64 {
+        if let QueryType::BoundedSum { lower, upper } = self.query_type {
+            return bounded_laplace_scale(self.epsilon(), lower, upper);
+        }
+        self.query_sensitivity() / self.epsilon()
+    }
+
+    /// Noised answer of `query_type` over `aggregate_field`, values clamped to the field's
+    /// bounds first so that `query_sensitivity` holds.
+    fn noised_query_answer(&self) -> Option<f64> {
+        let (lower, upper) = field_bounds(self.aggregate_field)?;
+        let records = read_data(&self.dataset.data, self.dataset.separator).ok()?;
+        let values = records.iter()
+            .filter_map(|record| record.field_value(self.aggregate_field))
+            .map(|value| value.clamp(lower, upper))
+            .collect::<Vec<_>>();
+        Some(self.query_type.evaluate(&values) + laplace_sample(self.query_scale(), &mut rand::thread_rng()))
+    }
+
+    /// Every statistic of `multi_stat` over `aggregate_field` with Laplace noise for its share
+    /// of `epsilon`, values clamped to the field's bounds first.
+    fn noised_multi_stats(&self) -> Vec<(Statistic, f64, f64)> {
+        let (Some((lower, upper)), Ok(records)) =
+            (field_bounds(self.aggregate_field), read_data(&self.dataset.data, self.dataset.separator)) else {
+            return vec![];
+        };
+        let values = records.iter()
+            .filter_map(|record| record.field_value(self.aggregate_field))
+            .map(|value| value.clamp(lower, upper))
+            .collect::<Vec<_>>();
+        let n = self.dataset.record_count();
+        let epsilon = self.multi_stat.epsilon_per_statistic(self.epsilon());
+        let mut rng = rand::thread_rng();
+        self.multi_stat.statistics().into_iter()
+            .map(|statistic| {
+                let scale = statistic.sensitivity(upper, n) / epsilon;
+                (statistic, statistic.evaluate(&values, n) + laplace_sample(scale, &mut rng), epsilon)
+            })
+            .collect()
+    }
+
+    /// Median of `aggregate_field` with Laplace noise of scale `2 S_β / ε` at `epsilon()`
+    /// and `delta`, next to that scale and the `(upper - lower) / ε` of the global
+    /// sensitivity. `None` without noise.
+    fn noised_smooth_median(&self) -> Option<(f64, f64, f64)> {
+        let epsilon = self.epsilon();
+        if epsilon.is_infinite() {
+            return None;
+        }
+        let (lower, upper) = field_bounds(self.aggregate_field)?;
+        let beta = laplace_beta(epsilon, self.delta);
+        let smooth_scale = 2.0 * smooth_sensitivity(self.dataset, self.aggregate_field, beta) / epsilon;
+        let noised = median(self.dataset, self.aggregate_field)? + laplace_sample(smooth_scale, &mut rand::thread_rng());
+        Some((noised, smooth_scale, (upper - lower) / epsilon))
+    }
+
+    /// Median of `field` by the inverse sensitivity mechanism, NaN for an unknown field.
+    pub fn private_median(&self, field: &str, epsilon: f64) -> f64 {
+        sorted_values(self.dataset, field).map_or(f64::NAN, |(values, bounds)| {
+            inverse_sensitivity_median(&values, bounds, epsilon, &mut rand::thread_rng())
+        })
+    }
+
+    /// Median of `field` with Laplace noise for its global sensitivity, the whole range of
+    /// the field: one record can move the median of a gapped data set from end to end.
+    fn laplace_median(&self, field: &str, epsilon: f64) -> f64 {
+        match (median(self.dataset, field), field_bounds(field)) {
+            (Some(median), Some((lower, upper))) => {
+                median + laplace_sample((upper - lower) / epsilon, &mut rand::thread_rng())
+            }
+            _ => f64::NAN,
+        }
+    }
+
+    /// (ε₁, ε₂) of the sparse vector technique with `c = max_above` positives at `epsilon`.
+    pub fn svt_epsilon_split(&self, epsilon: f64, max_above: usize) -> (f64, f64) {
+        match self.svt_threshold_share {
+            Some(share) => (share * epsilon, (1.0 - share) * epsilon),
+            None => svt_optimal_epsilon_split(epsilon, max_above),
+        }
+    }
+
+    /// Sparse vector technique (AboveThreshold with `c = max_above` positives, Lyu, Su, Li,
+    /// VLDB 2017) over the bucket counts: ε₁ of `svt_epsilon_split` noises the threshold
+    /// once, ε₂ the comparisons at scale `2 c Δ / ε₂`. Only the positive answers cost
+    /// privacy, so however many buckets are tested this stays `epsilon`-DP; the run stops
+    /// after `max_above` positives and every bucket after that is reported below.
+    pub fn sparse_vector_above_threshold(&self, threshold: f64, max_above: usize, epsilon: f64) -> Vec<bool> {
+        let mut rng = rand::thread_rng();
+        let sensitivity = self.sensitivity();
+        let (threshold_epsilon, query_epsilon) = self.svt_epsilon_split(epsilon, max_above);
+        let noised_threshold = threshold + laplace_sample(sensitivity / threshold_epsilon, &mut rng);
+        let query_scale = 2.0 * max_above as f64 * sensitivity / query_epsilon;
+        let mut above = 0;
+        self.aggregated_data.iter()
+            .map(|&count| {
+                if above == max_above {
+                    return false;
+                }
+                let is_above = count as f64 + laplace_sample(query_scale, &mut rng) >= noised_threshold;
+                above += is_above as usize;
+                is_above
+            })
+            .collect()
+    }
+
+    /// Index of the largest bucket, the private mode: only the argmax of the noised counts
+    /// is released, not the counts. With Gumbel noise of scale `2Δ / epsilon` this is the
+    /// exponential mechanism with the counts as utility, `epsilon`-DP however many buckets
+    /// there are. Counts only ever move in one direction for a neighbour, so `Δ / epsilon`
+    /// would already do; the general scale is kept.
+    pub fn report_noisy_max(&self, epsilon: f64) -> usize {
+        gumbel_max_selection(&self.mode_scores(), self.sensitivity(), epsilon, rand::random())
+    }
+
+    /// Probability of every bucket to be `report_noisy_max` at `epsilon`.
+    pub fn mode_probabilities(&self, epsilon: f64) -> Vec<f64> {
+        exponential_mechanism_probabilities(&self.mode_scores(), self.sensitivity(), epsilon)
+    }
+
+    fn mode_scores(&self) -> Vec<f64> {
+        self.aggregated_data.iter().map(|&count| count as f64).collect()
+    }
+
+    /// Running totals of the counts with Gaussian noise calibrated to (`epsilon`, `delta`),
+    /// made non-decreasing by isotonic regression and rounded. A record in bucket `i` moves
+    /// the totals of `i..n` by one each, so the L2 sensitivity is up to `sqrt(n)` times that
+    /// of the counts, not lower. What the totals buy is that a range is the difference of
+    /// two of them, with the same error whatever its length. That is a variance of `2 n σ²`
+    /// for a count's `σ²`, while summing noised counts costs `σ²` per bucket in the range:
+    /// on a flat histogram the sum wins for every range, and only the isotonic fit narrows it.
+    pub fn private_cdf(&self, epsilon: f64) -> Vec<u64> {
+        let n = self.aggregated_data.len();
+        if n == 0 {
+            return vec![];
+        }
+        let sigma = analytic_gaussian_scale(epsilon, self.delta, l2_sensitivity(self.sensitivity(), n));
+        let noised = gaussian_noise(&running_totals(&self.aggregated_data), sigma, &mut rand::thread_rng());
+        isotonic_regression(&noised).iter().map(|total| total.max(0.0).round() as u64).collect()
+    }
+
+    /// Mean squared error per query of every range of buckets answered by `subspace_noise`
+    /// on the range workload: measured on one draw, and expected, `σ² rank(W) / m` over the
+    /// `m` ranges. Then the expected error without the projection, `σ²`, and of the Gaussian
+    /// mechanism on the counts with ranges summed from them, `σ₁² ‖W‖²_F / m`. The workload
+    /// sensitivity is the column norm of the middle bucket, in about a quarter of all ranges,
+    /// so `σ` is far above `σ₁` and the projection, though it cuts the noise by `m / rank(W)`,
+    /// only brings it close to the plain counts.
+    pub fn subspace_range_errors(&self, epsilon: f64) -> Option<(f64, f64, f64, f64)> {
+        let n = self.aggregated_data.len();
+        if n == 0 || epsilon.is_infinite() {
+            return None;
+        }
+        let workload = range_workload(n);
+        let m = workload.len() as f64;
+        let noise = subspace_noise(&workload, epsilon, self.delta, self.sensitivity(), &mut rand::thread_rng());
+        let measured = noise.iter().map(|z| z * z).sum::<f64>() / m;
+        let sigma = analytic_gaussian_scale(epsilon, self.delta, self.sensitivity() * workload_l2_sensitivity(&workload));
+        let rank = column_space_basis(&workload).len() as f64;
+        let sigma_counts = analytic_gaussian_scale(epsilon, self.delta, self.sensitivity());
+        let frobenius = workload.iter().flatten().map(|w| w * w).sum::<f64>();
+        Some((measured, sigma * sigma * rank / m, sigma * sigma, sigma_counts * sigma_counts * frobenius / m))
+    }
+
+    /// Epsilon every record of the data set asks for, in the order of its rows: `epsilon`
+    /// times the share of a `PRIVACY_TIERS` tier drawn by the tier's share of the records.
+    pub fn individual_privacy_levels(&self, epsilon: f64) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(PRIVACY_TIER_SEED);
+        let rows = self.conditioned_data().lines().filter(|line| !line.is_empty()).count();
+        (0..rows)
+            .map(|_| {
+                let draw = rng.gen::<f64>();
+                let mut cumulative = 0.0;
+                let tier = PRIVACY_TIERS.iter()
+                    .find(|(share, _)| {
+                        cumulative += share;
+                        draw < cumulative
+                    })
+                    .unwrap_or(&PRIVACY_TIERS[PRIVACY_TIERS.len() - 1]);
+                tier.1 * epsilon
+            })
+            .collect()
+    }
+
+    /// Histogram of `aggregate_field` with the record in row `i` protected at
+    /// `privacy_levels[i]`, by the sample mechanism of Jorgensen, Yu and Cormode (ICDE 2015):
+    /// with `t` the largest level, a record at `ε_i` is kept with probability
+    /// `(e^ε_i - 1) / (e^t - 1)` and the counts of the kept records get `Laplace(Δ / t)`. The
+    /// counts are divided by the mean probability, public as the levels are, to estimate the
+    /// whole histogram. Every record gets its own level, the data set as a whole the smallest.
+    pub fn heterogeneous_dp_histogram(&self, privacy_levels: &[f64]) -> Vec<u64> {
+        let threshold = privacy_levels.iter().copied().fold(0.0, f64::max);
+        if threshold == 0.0 || threshold.is_infinite() {
+            return vec![];
+        }
+        let keep = |level: f64| level.exp_m1() / threshold.exp_m1();
+        let mean_keep = privacy_levels.iter().map(|&level| keep(level)).sum::<f64>() / privacy_levels.len() as f64;
+        let mut rng = rand::thread_rng();
+        let mut counts = vec![0.0; self.buckets().len() + 1];
+        for (pos, &level) in self.bucket_indices().into_iter().zip(privacy_levels) {
+            if rng.gen_bool(keep(level).min(1.0)) {
+                counts[pos] += 1.0;
+            }
+        }
+        let scale = self.sensitivity() / threshold;
+        counts.iter()
+            .map(|count| ((count + laplace_sample(scale, &mut rng)) / mean_keep).max(0.0).round() as u64)
+            .collect()
+    }
+
+    /// Indices of `k` large buckets by the one-shot mechanism of Durfee & Rogers (NeurIPS
+    /// 2019): Gumbel noise of scale `2kΔ / epsilon` on every count and the `k` largest noised
+    /// counts, in order. This picks the same as `k` rounds of `report_noisy_max` at
+    /// `epsilon / k` each, peeling off the winner every time, and so is `epsilon`-DP while
+    /// drawing the noise once.
+    pub fn one_shot_top_k(&self, k: usize, epsilon: f64) -> Vec<usize> {
+        let mut rng = rand::thread_rng();
+        let scale = 2.0 * k as f64 * self.sensitivity() / epsilon;
+        top_k_indices(self.aggregated_data.iter().map(|&count| count as f64 + gumbel_sample(scale, &mut rng)), k)
+    }
+
+    /// Indices of the `k` largest counts after `Laplace(Δ / epsilon)` noise on all of them.
+    /// Adding or removing a record changes one count, so the whole noised histogram is
+    /// `epsilon`-DP and its top `k` come for free; with a scale `2k` times below that of the
+    /// one-shot mechanism this wins here. The one-shot mechanism pays off when a record can change
+    /// many counts and the L1 sensitivity of the histogram grows with them.
+    pub fn laplace_top_k(&self, k: usize, epsilon: f64) -> Vec<usize> {
+        let mut rng = rand::thread_rng();
+        let scale = self.sensitivity() / epsilon;
+        top_k_indices(self.aggregated_data.iter().map(|&count| count as f64 + laplace_sample(scale, &mut rng)), k)
+    }
+
+    /// Average share of the true top `k` buckets that `one_shot_top_k` and `laplace_top_k`
+    /// recover over `n` runs each.
+    pub fn top_k_recovery(&self, k: usize, epsilon: f64, n: usize) -> (f64, f64) {
+        let truth = top_k_indices(self.aggregated_data.iter().map(|&count| count as f64), k);
+        let recovered = |selection: Vec<usize>| {
+            selection.iter().filter(|pos| truth.contains(pos)).count() as f64 / truth.len().max(1) as f64
+        };
+        let average = |select: &dyn Fn() -> Vec<usize>| (0..n).map(|_| recovered(select())).sum::<f64>() / n.max(1) as f64;
+        (average(&|| self.one_shot_top_k(k, epsilon)), average(&|| self.laplace_top_k(k, epsilon)))
+    }
+
+    /// Zeroes the buckets with fewer than `k` records, as k-anonymity style suppression
+    /// does, then adds the usual noise on top. Noise at full scale is still needed since
+    /// which buckets got suppressed depends on the data, see `sensitivity`.
+    pub fn noised_with_suppression(&self, k: u64) -> Vec<u64> {
+        let suppressed = self.aggregated_data.iter()
+            .map(|&count| if count < k { 0 } else { count })
+            .collect::<Vec<_>>();
+        self.noised_data(&suppressed).unwrap_or_default()
+    }
+
+    /// Number of non-empty buckets that `noised_with_suppression` zeroes.
+    pub fn suppressed_buckets(&self, k: u64) -> usize {
+        self.aggregated_data.iter().filter(|&&count| count > 0 && count < k).count()
+    }
+
+    /// Smallest epsilon at which the Gaussian noise is (epsilon, `delta`)-DP: noise of
+    /// standard deviation σ on a query of L2 sensitivity Δ is Δ/σ-GDP. `None` for pure DP.
+    pub fn epsilon_for_delta(&self, delta: f64) -> Option<f64> {
+        self.delta_for_epsilon(0.0)?;
+        Some(gdp_to_epsilon_delta(self.l2_sensitivity() / self.scale(), delta))
+    }
+
+    /// `n_points` of the (epsilon, delta) curve of the Gaussian noise by
+    /// `gaussian_approx_dp_curve`, from epsilon 0 to where delta reaches `APPROX_DP_MIN_DELTA`,
+    /// with delta as its log10. Empty for pure DP and without noise.
+    pub fn approx_dp_curve(&self, n_points: usize) -> Vec<(f64, f64)> {
+        let Some(last) = self.epsilon_for_delta(APPROX_DP_MIN_DELTA).filter(|epsilon| epsilon.is_finite()) else {
+            return vec![];
+        };
+        let epsilons = (0..n_points).map(|i| last * i as f64 / (n_points - 1).max(1) as f64).collect::<Vec<_>>();
+        let deltas = gaussian_approx_dp_curve(self.scale(), self.l2_sensitivity(), &epsilons);
+        epsilons.into_iter().zip(deltas).map(|(epsilon, delta)| (epsilon, delta.max(APPROX_DP_MIN_DELTA).log10())).collect()
+    }
+
+    /// Smallest delta for which the Gaussian mechanism at the current scale is
+    /// (`target_epsilon`, delta)-DP. Only meaningful for Gaussian noise, `None` otherwise.
+    pub fn delta_for_epsilon(&self, target_epsilon: f64) -> Option<f64> {
+        match (self.noise_target, self.noise_type) {
+            (Frequencies, _) | (Counts, Gaussian) | (Counts, AnalyticGaussian) => {
+                Some(gaussian_delta_for_epsilon(self.scale(), self.l2_sensitivity(), target_epsilon))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parameter of the Gaussian DP guarantee, `mu = sensitivity / sigma`: telling the
+    /// neighbouring outputs apart is as hard as telling N(0, 1) from N(mu, 1). `None` for
+    /// mechanisms that are not Gaussian.
+    pub fn gdp_mu(&self) -> Option<f64> {
+        match (self.noise_target, self.noise_type) {
+            (Frequencies, _) | (Counts, Gaussian) | (Counts, AnalyticGaussian) => {
+                Some(self.l2_sensitivity() / self.scale())
+            }
+            _ => None,
+        }
+    }
+
+    /// Epsilon of the GDP guarantee at `delta`, tighter than the zCDP-based `epsilon()`.
+    pub fn gdp_epsilon(&self) -> Option<f64> {
+        self.gdp_mu().map(|mu| gdp_to_epsilon_delta(mu, self.delta))
+    }
+
+    /// Rényi DP epsilon at `order` (the α of Mironov 2017, not the significance level
+    /// `alpha`). `None` for the mixture and PTR, which have no closed form; Snapping is
+    /// treated as the Laplace mechanism it approximates.
+    pub fn rdp_epsilon(&self, order: f64) -> Option<f64> {
+        let scale = self.scale();
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) | (Counts, Snapping) => Some(laplace_rdp(scale, self.sensitivity(), order)),
+            (Frequencies, _) | (Counts, Gaussian) | (Counts, AnalyticGaussian) => {
+                Some(gaussian_rdp(scale, self.l2_sensitivity(), order))
+            }
+            (Counts, Mixture { .. }) | (Counts, ProposeTestRelease { .. }) | (Counts, CdfNoise)
+            | (Counts, CorrelatedLaplace { .. }) | (Counts, Wavelet) | (Counts, Hierarchical)
+            | (Counts, Binomial { .. }) | (Counts, TruncatedLaplace { .. }) => None,
+        }
+    }
+
+    pub fn rdp_to_approx_dp(&self, order: f64, delta: f64) -> Option<f64> {
+        self.rdp_epsilon(order).map(|rdp_epsilon| rdp_to_approx_dp(rdp_epsilon, order, delta))
+    }
+
+    /// Epsilon at `delta` of the releases on the odometer so far (at least one), as if all
+    /// had the current Gaussian noise: the moments accountant at its best integer `λ` up to
+    /// `MAX_MOMENT_ORDER` together with that `λ`, RDP composition at `order`, and basic
+    /// composition of the classic `sqrt(2 ln(1.25 T / δ)) Δ / σ` per release. `None` for
+    /// mechanisms that are not Gaussian.
+    pub fn composed_gaussian_bounds(&self, order: f64) -> Option<(u32, f64, f64, f64)> {
+        self.gdp_mu()?;
+        let (sigma, sensitivity) = (self.scale(), self.l2_sensitivity());
+        let steps = self.odometer.release_count().max(1);
+        let (lambda, moments) = (1..=MAX_MOMENT_ORDER)
+            .map(|lambda| (lambda, gaussian_moments_accountant(sigma, sensitivity, steps, lambda, self.delta)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        let rdp = rdp_to_approx_dp(steps as f64 * gaussian_rdp(sigma, sensitivity, order), order, self.delta);
+        let classic = steps as f64 * (2.0 * (1.25 * steps as f64 / self.delta).ln()).sqrt() * sensitivity / sigma;
+        Some((lambda, moments, rdp, classic))
+    }
+
+    /// Checks the nominal sensitivity against the data: builds `n_trials` neighbouring data
+    /// sets of the rows behind the histogram (a random row removed, or a random row
+    /// duplicated), reruns the aggregation on each and returns the largest L1 change seen. This can only ever under-estimate the
+    /// global sensitivity, so a value above `sensitivity()` means the assumption is wrong.
+    pub fn estimate_sensitivity_empirically(&self, n_trials: usize, seed: u64) -> f64 {
+        let chain = match aggregate_data_chain(self, self.aggregate_field) {
+            Some(chain) => chain,
+            None => return f64::NAN,
+        };
+        let data = self.conditioned_data();
+        let rows = data.lines().filter(|line| !line.is_empty()).collect::<Vec<_>>();
+        if rows.is_empty() {
+            return 0.0;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut max_change = 0.0f64;
+        for _ in 0..n_trials {
+            let mut neighbour = rows.clone();
+            let pos = rng.gen_range(0..rows.len());
+            if rng.gen::<bool>() {
+                neighbour.remove(pos);
+            } else {
+                neighbour.push(rows[pos]);
+            }
+            if let Ok(counts) = chain.invoke(&neighbour.join("\n")) {
+                let change = counts.iter().zip(self.aggregated_data.iter())
+                    .map(|(&a, &b)| a.abs_diff(b) as f64)
+                    .sum::<f64>();
+                max_change = max_change.max(change);
+            }
+        }
+        max_change
+    }
+
+    /// Empirical sensitivity once `contamination_fraction` of the records behind the histogram
+    /// are replaced by copies of the first of them, all piling into its bucket.
+    pub fn contaminated_sensitivity(&self, contamination_fraction: f64) -> f64 {
+        let data = self.conditioned_data();
+        let rows = data.lines().filter(|line| !line.is_empty()).collect::<Vec<_>>();
+        let corrupted = ((contamination_fraction * rows.len() as f64).ceil() as usize).min(rows.len());
+        let data = rows.iter().enumerate()
+            .map(|(i, &row)| if i < corrupted { rows[0] } else { row })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let contaminated = CsvDataSet { data, separator: self.dataset.separator };
+        let mut shadow = self.clone();
+        shadow.dataset = &contaminated;
+        let Some(counts) = shadow.aggregate_data() else {
+            return f64::NAN;
+        };
+        shadow.aggregated_data = counts;
+        shadow.estimate_sensitivity_empirically(SENSITIVITY_TRIALS, SENSITIVITY_SEED)
+    }
+
+    /// Epsilon of the current mechanism on the data corrupted by `contaminated_sensitivity`:
+    /// the nominal epsilon, raised by however much the empirical sensitivity on the corrupted
+    /// data exceeds the assumed one. DP is a worst case over all data sets, so a mechanism
+    /// calibrated to its global sensitivity keeps its epsilon under any corruption, and this
+    /// only rises when the sensitivity assumption fails; what corruption does erode is accuracy.
+    pub fn robust_privacy_loss(&self) -> f64 {
+        self.epsilon() * (self.contaminated_sensitivity / self.sensitivity()).max(1.0)
+    }
+
+    /// Simplified membership inference: for `n_shadow_datasets` releases the attacker sees
+    /// the noised histogram of either the current data set or the data set with
+    /// `target_record` added (a fair coin decides), and looks only at the bucket the
+    /// record falls into. A threshold on that bucket is fitted on the first half of the
+    /// releases and scored on the second half. Returns the accuracy above guessing (0.5);
+    /// without noise it approaches 0.5, under epsilon-DP it stays below
+    /// `(e^ε - 1) / (e^ε + 1) / 2`.
+    pub fn membership_inference_attack(&self, target_record: &Record, n_shadow_datasets: usize, seed: u64) -> f64 {
+        let mut with_target = CsvDataSet { data: self.dataset.data.clone(), separator: self.dataset.separator };
+        with_target.append_row(target_record);
+        let mut shadow = self.clone();
+        shadow.dataset = &with_target;
+        let (without_counts, with_counts) = match (self.aggregate_data(), shadow.aggregate_data()) {
+            (Some(without_counts), Some(with_counts)) => (without_counts, with_counts),
+            _ => return f64::NAN,
+        };
+        let bucket = match without_counts.iter().zip(with_counts.iter()).position(|(a, b)| a != b) {
+            Some(bucket) => bucket,
+            // the record does not show up in the histogram at all, e.g. excluded by `condition`
+            None => return 0.0,
+        };
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let observations = (0..n_shadow_datasets)
+            .filter_map(|_| {
+                let member = rng.gen::<bool>();
+                let counts = if member { &with_counts } else { &without_counts };
+                self.noised_data(counts).map(|noised| (noised[bucket], member))
+            })
+            .collect::<Vec<_>>();
+        let (train, test) = observations.split_at(observations.len() / 2);
+        if test.is_empty() {
+            return 0.0;
+        }
+        let accuracy = |data: &[(u64, bool)], threshold: u64| {
+            data.iter().filter(|&&(count, member)| (count >= threshold) == member).count() as f64 / data.len() as f64
+        };
+        let threshold = train.iter()
+            .map(|&(count, _)| count)
+            .max_by(|&a, &b| accuracy(train, a).partial_cmp(&accuracy(train, b)).unwrap())
+            .unwrap_or(with_counts[bucket]);
+        accuracy(test, threshold) - 0.5
+    }
+
+    /// Histogram of the neighbouring data set without the record at `row`.
+    fn counts_without_row(&self, row: usize) -> Option<Vec<u64>> {
+        let rows = self.dataset.data.lines().collect::<Vec<_>>();
+        if row >= rows.len() {
+            return None;
+        }
+        let without_row = CsvDataSet {
+            data: rows.iter().enumerate()
+                .filter(|&(i, _)| i != row)
+                .map(|(_, line)| *line)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            separator: self.dataset.separator,
+        };
+        let mut neighbour = self.clone();
+        neighbour.dataset = &without_row;
+        neighbour.aggregate_data()
+    }
+
+    /// Empirical hockey-stick divergence `H_ε(P || Q) = sup_S P(S) - e^ε Q(S)
+    /// = Σ_x max(0, P(x) - e^ε Q(x))` between the releases on the data set and on the
+    /// neighbour without a record picked by `seed`, the smallest delta for which this pair
+    /// is (`epsilon`, delta)-indistinguishable; the larger of both directions is returned.
+    /// The other buckets are noised alike on both sides, so only the bucket the record is
+    /// in is sampled, `n_samples` times each. The plug-in estimate is biased upwards by
+    /// sampling noise in the tails, more so for small `n_samples`.
+    pub fn hockey_stick_divergence(&self, epsilon: f64, n_samples: usize, seed: u64) -> f64 {
+        let rows = self.dataset.record_count();
+        if rows == 0 {
+            return f64::NAN;
+        }
+        let row = StdRng::seed_from_u64(seed).gen_range(0..rows);
+        let Some(without_counts) = self.counts_without_row(row) else {
+            return f64::NAN;
+        };
+        let Some(bucket) = self.aggregated_data.iter().zip(without_counts.iter()).position(|(a, b)| a != b) else {
+            // the record is not in the histogram, e.g. excluded by `condition`
+            return 0.0;
+        };
+        let frequencies = |count: u64| {
+            let mut frequencies = BTreeMap::new();
+            for noised in self.noised_data(&vec![count; n_samples]).unwrap_or_default() {
+                *frequencies.entry(noised).or_insert(0.0) += 1.0 / n_samples.max(1) as f64;
+            }
+            frequencies
+        };
+        let (p, q) = (frequencies(self.aggregated_data[bucket]), frequencies(without_counts[bucket]));
+        let divergence = |p: &BTreeMap<u64, f64>, q: &BTreeMap<u64, f64>| {
+            p.iter()
+                .map(|(x, p_x)| (p_x - epsilon.exp() * q.get(x).copied().unwrap_or(0.0)).max(0.0))
+                .sum::<f64>()
+        };
+        divergence(&p, &q).max(divergence(&q, &p))
+    }
+
+    /// Exact hockey-stick divergence of the current mechanism at `epsilon`, from the
+    /// discrete Laplace pmf and from the privacy profile of the continuous Gaussian, which
+    /// the discrete Gaussian follows closely. `None` for the other mechanisms.
+    pub fn theoretical_hockey_stick(&self, epsilon: f64) -> Option<f64> {
+        let scale = self.scale();
+        let sensitivity = self.sensitivity();
+        match (self.noise_target, self.noise_type) {
+            (Counts, Laplace) if scale == 0.0 => Some(1.0),
+            (Counts, Laplace) => {
+                let shift = sensitivity.round() as i64;
+                let reach = shift + (50.0 * scale).ceil() as i64;
+                Some((-reach..=reach)
+                    .map(|x| (discrete_laplace_pmf(x, scale) - epsilon.exp() * discrete_laplace_pmf(x - shift, scale)).max(0.0))
+                    .sum())
+            }
+            (Counts, Gaussian) | (Counts, AnalyticGaussian) => {
+                Some(gaussian_delta_for_epsilon(scale, sensitivity, epsilon))
+            }
+            (Counts, Binomial { n }) => Some(binomial_delta_for_epsilon(n, sensitivity as u64, epsilon)),
+            _ => None,
+        }
+    }
+
+    /// Per-instance privacy loss (Wang, "Per-instance differential privacy", 2017) of the
+    /// record at `target_row` under Laplace noise calibrated to `epsilon_noise`: the
+    /// histogram is built with and without the record, and the KL divergence between the
+    /// two noised outputs, `Σ |d| / b + e^(-|d| / b) - 1` over the bucket differences `d`
+    /// at scale `b`, is how much the release reveals about this record on average. The
+    /// worst case over all outputs is `Σ |d| / b`, at most `epsilon_noise`; a record that
+    /// `condition` leaves out loses nothing at all. NaN when there is no such row.
+    pub fn per_instance_dp(&self, target_row: usize, epsilon_noise: f64) -> f64 {
+        let (with_counts, without_counts) = match (self.aggregate_data(), self.counts_without_row(target_row)) {
+            (Some(with_counts), Some(without_counts)) => (with_counts, without_counts),
+            _ => return f64::NAN,
+        };
+        let scale = self.sensitivity() / epsilon_noise;
+        with_counts.iter().zip(without_counts.iter())
+            .map(|(&a, &b)| a.abs_diff(b) as f64 / scale)
+            .map(|distance| distance + (-distance).exp() - 1.0)
+            .sum()
+    }
+
+    /// Leave-one-out estimate of the sensitivity: drops single records and returns the
+    /// largest L1 change of the histogram. Every record is tried when there are at most
+    /// `jackknife_samples`, a seeded random subset of that size otherwise.
+    pub fn jackknife_sensitivity(&self) -> f64 {
+        let chain = match aggregate_data_chain(self, self.aggregate_field) {
+            Some(chain) => chain,
+            None => return f64::NAN,
+        };
+        let data = self.conditioned_data();
+        let rows = data.lines().filter(|line| !line.is_empty()).collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(JACKKNIFE_SEED);
+        let left_out = sample(&mut rng, rows.len(), self.jackknife_samples.min(rows.len()));
+
+        left_out.iter()
+            .filter_map(|pos| {
+                let mut neighbour = rows.clone();
+                neighbour.remove(pos);
+                chain.invoke(&neighbour.join("\n")).ok()
+            })
+            .map(|counts| {
+                counts.iter().zip(self.aggregated_data.iter())
+                    .map(|(&a, &b)| a.abs_diff(b) as f64)
+                    .sum::<f64>()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// 5th and 95th percentile of every bucket count over `n_samples` bootstrap samples of
+    /// the records behind the histogram. This is sampling error, not privacy: it is how far
+    /// the true histogram itself could be from that of the population the records came from.
+    pub fn bootstrap_intervals(&self, n_samples: usize) -> Vec<(u64, u64)> {
+        let Some(chain) = aggregate_data_chain(self, self.aggregate_field) else {
+            return vec![];
+        };
+        let records = CsvDataSet { data: self.conditioned_data(), separator: self.dataset.separator };
+        let samples = (0..n_samples as u64)
+            .filter_map(|i| chain.invoke(&records.bootstrap_sample(BOOTSTRAP_SEED + i).data).ok())
+            .collect::<Vec<_>>();
+        if samples.is_empty() {
+            return vec![];
+        }
+        let percentile = |sorted: &[u64], p: f64| sorted[(p * (sorted.len() - 1) as f64).round() as usize];
+        (0..self.aggregated_data.len())
+            .map(|bucket| {
+                let mut counts = samples.iter().map(|counts| counts[bucket]).collect::<Vec<_>>();
+                counts.sort_unstable();
+                (percentile(&counts, 0.05), percentile(&counts, 0.95))
+            })
+            .collect()
+    }
+
+    /// Average MSE of the running totals of `CdfNoise` against the true ones before and
+    /// after isotonic regression, over `n` releases. The true CDF is non-decreasing, so
+    /// projecting onto the non-decreasing sequences can only bring the totals closer to it.
+    pub fn compare_isotonic_cdf(&self, n: usize) -> (f64, f64) {
+        let truth = running_totals(&self.aggregated_data);
+        let mse = |totals: &[f64]| metrics::l2_distance(&truth, totals).powi(2) / truth.len().max(1) as f64;
+        let mut rng = rand::thread_rng();
+        let (before, after) = (0..n)
+            .map(|_| {
+                let totals = noised_cdf(&self.aggregated_data, self.scale(), &mut rng);
+                (mse(&totals), mse(&isotonic_regression(&totals)))
+            })
+            .fold((0.0, 0.0), |(before, after), (b, a)| (before + b, after + a));
+        (before / n.max(1) as f64, after / n.max(1) as f64)
+    }
+
+    /// Squared error of every range of buckets `i..=j` of `noised`, averaged over the ranges.
+    fn range_query_mse(&self, noised: &[u64]) -> f64 {
+        let errors = self.aggregated_data.iter().zip(noised)
+            .map(|(&count, &noised)| noised as f64 - count as f64)
+            .collect::<Vec<_>>();
+        let mut total = 0.0;
+        let mut ranges = 0;
+        for i in 0..errors.len() {
+            let mut error = 0.0;
+            for e in &errors[i..] {
+                error += e;
+                total += error * error;
+                ranges += 1;
+            }
+        }
+        total / ranges.max(1) as f64
+    }
+
+    /// `range_query_mse` of the current noise and of direct Laplace noise at the same
+    /// epsilon, averaged over `n` releases each.
+    pub fn compare_range_queries(&self, n: usize) -> (f64, f64) {
+        let mut direct = self.clone();
+        direct.noise_type = Laplace;
+        let average = |noiser: &Noiser| {
+            let trials = noiser.run_n_trials(n);
+            trials.iter().map(|noised| self.range_query_mse(noised)).sum::<f64>() / trials.len().max(1) as f64
+        };
+        (average(self), average(&direct))
+    }
+
+    /// Every record of `twin` on the top two principal components of all six columns, found
+    /// privately at `epsilon` by `pca::private_principal_components`, and the angle in
+    /// degrees between the first of them and the exact first component. With `twin` the
+    /// synthetic twin of the same release, the points are post-processing too; the records
+    /// themselves would give away far more than the components.
+    pub fn private_pca(&self, epsilon: f64, twin: &CsvDataSet) -> Option<(Vec<(f64, f64)>, f64)> {
+        let records = read_data(&self.dataset.data, self.dataset.separator).ok()?;
+        let rows = records.iter().map(unit_ball_features).collect::<Vec<_>>();
+        let components = private_principal_components(&rows, 2, epsilon, &mut rand::thread_rng());
+        let (_, exact) = symmetric_eigen(&second_moment_matrix(&rows)).into_iter().next()?;
+        let cosine = components.first()?.iter().zip(&exact).map(|(a, b)| a * b).sum::<f64>();
+        let twin_rows = read_data(&twin.data, twin.separator).ok()?.iter().map(unit_ball_features).collect::<Vec<_>>();
+        let points = project_onto(&twin_rows, &components).iter()
+            .filter_map(|coordinates| Some((*coordinates.first()?, *coordinates.get(1)?)))
+            .collect();
+        // an eigenvector's sign is arbitrary
+        Some((points, cosine.abs().min(1.0).acos().to_degrees()))
+    }
+
+    /// MSE per bucket, over `n` releases, of Gaussian noise added to a random projection of
+    /// the counts to `PROJECTION_FACTORS` times the number of buckets and projected back,
+    /// for every target dimension, and the MSE of Gaussian noise on the counts. Both are
+    /// calibrated to the current epsilon and `delta`, the projection to its own sensitivity.
+    pub fn projection_mse_tradeoff(&self, n: usize) -> (Vec<(f64, f64)>, f64) {
+        let epsilon = self.epsilon();
+        let sensitivity = self.sensitivity();
+        let counts = self.aggregated_data.iter().map(|&count| count as f64).collect::<Vec<_>>();
+        let dim = counts.len().max(1);
+        let mut rng = rand::thread_rng();
+        let tradeoff = PROJECTION_FACTORS.iter()
+            .map(|factor| {
+                let target_dim = ((factor * dim as f64).round() as usize).max(1);
+                let matrix = projection_matrix(counts.len(), target_dim, PROJECTION_SEED);
+                let sigma = analytic_gaussian_scale(epsilon, self.delta, sensitivity * projection_sensitivity(&matrix));
+                let projected = random_projection(&counts, target_dim, PROJECTION_SEED);
+                let mse = (0..n)
+                    .map(|_| {
+                        let back = project_back(&gaussian_noise(&projected, sigma, &mut rng), &matrix);
+                        back.iter().zip(&counts).map(|(estimate, count)| (estimate - count).powi(2)).sum::<f64>()
+                            / dim as f64
+                    })
+                    .sum::<f64>() / n.max(1) as f64;
+                (target_dim as f64, mse)
+            })
+            .collect();
+        (tradeoff, analytic_gaussian_scale(epsilon, self.delta, sensitivity).powi(2))
+    }
+
+    /// Average MSE of the current noise and of direct Laplace noise at the same epsilon
+    /// over `n` releases each.
+    pub fn compare_to_direct_laplace(&self, n: usize) -> (f64, f64) {
+        let mut direct = self.clone();
+        direct.noise_type = Laplace;
+        (self.average_mean_squared_error(n), direct.average_mean_squared_error(n))
+    }
+
+    /// Commits to the next `n_future_queries` releases at up to the cost of the last refresh:
+    /// `n_future_queries` times `release_cost()` is reserved now, and the releases are paid from the
+    /// reservation instead of the rest of the budget. Knowing the number of queries in
+    /// advance is also what advanced composition needs, the odometer's online bound pays
+    /// for not knowing it.
+    pub fn precommit_query_sequence(&mut self, n_future_queries: usize) {
+        self.odometer.precommit(n_future_queries, self.release_cost.0);
+    }
+
+    /// Privacy cost `(epsilon, delta)` of the last refresh by basic composition, as its
+    /// outputs were drawn: every draw from the data `spend`s its own share. The DP-FTRL,
+    /// local hashing and perturbation simulations and the error comparisons only illustrate
+    /// the mechanisms and spend nothing.
+    pub fn release_cost(&self) -> (f64, f64) {
+        self.release_cost
+    }
+
+    /// Adds the privacy cost of an output just drawn to that of the refresh.
+    fn spend(&mut self, epsilon: f64, delta: f64) {
+        self.release_cost = (self.release_cost.0 + epsilon, self.release_cost.1 + delta);
+    }
+
+    /// Adds the release just made to the odometer at `release_cost()`.
+    fn record_on_odometer(&mut self) {
+        let (epsilon, delta) = self.release_cost;
+        self.odometer.record(epsilon, delta);
+    }
+
+    /// Privacy filter (Rogers, Roth, Ullman, Vadhan 2016): answers `query` and adds it to
+    /// the odometer only if what it spent fits into `budget` together with everything spent
+    /// or reserved so far, and halts with `None` otherwise. The query runs on a draft that
+    /// is thrown away when it does not fit, so the release about to be made is counted
+    /// before anything of it is shown and the total stays within the budget however the
+    /// releases were chosen; stopping only once the spent epsilon alone reached the budget
+    /// could overshoot it by a whole release.
+    pub fn privacy_filter<T, F>(&mut self, budget: f64, query: F) -> Option<T> where F: FnOnce(&mut Self) -> T {
+        let mut draft = self.clone();
+        draft.release_cost = (0.0, 0.0);
+        let answer = query(&mut draft);
+        if !self.within_budget(budget, draft.release_cost.0) {
+            self.release_cost = draft.release_cost;
+            return None;
+        }
+        *self = draft;
+        self.record_on_odometer();
+        Some(answer)
+    }
+
+    /// Whether one more release at `epsilon` keeps the epsilon spent and still reserved for
+    /// committed queries within `budget`.
+    fn within_budget(&self, budget: f64, epsilon: f64) -> bool {
+        self.odometer.committed_after(epsilon) <= budget
+    }
+
+    /// Draws every output of a refresh from the data: the noised histogram or frequencies
+    /// and all the releases around them, each spending its share of `release_cost()`.
+    fn release(&mut self) {
+        self.release_cost = (0.0, 0.0);
+        let epsilon = self.epsilon();
+        let (delta, histogram_delta) = (self.delta, self.delta_for_epsilon(epsilon).unwrap_or(0.0));
+        // the part of `epsilon()` the histogram itself spends
+        let mut histogram_epsilon = epsilon;
+        if let (Counts, ProposeTestRelease { proposed_sensitivity }) = (self.noise_target, self.noise_type) {
+            let passed = self.ptr_test(proposed_sensitivity);
+            self.ptr_passed = Some(passed);
+            self.spend(1.0 / self.scale(), 0.0);
+            histogram_epsilon -= 1.0 / self.scale();
+            if !passed {
+                // nothing else is released
+                return;
+            }
+        }
+        self.query_answer = self.noised_query_answer();
+        if self.query_answer.is_some() {
+            self.spend(epsilon, 0.0);
+        }
+        self.multi_stat_answers = self.noised_multi_stats();
+        if !self.multi_stat_answers.is_empty() {
+            self.spend(epsilon, 0.0);
+        }
+        self.smooth_median = self.noised_smooth_median();
+        if self.smooth_median.is_some() {
+            self.spend(epsilon, delta);
+        }
+        self.private_medians = epsilon.is_finite().then(|| {
+            (self.private_median(self.aggregate_field, epsilon), self.laplace_median(self.aggregate_field, epsilon))
+        });
+        if self.private_medians.is_some() {
+            self.spend(2.0 * epsilon, 0.0);
+        }
+        // the test runs whether or not the mean is released
+        self.ptr_mean = self.noised_ptr_mean();
+        self.spend(epsilon, delta);
+        let twin = self.dataset.generate_synthetic_twin(epsilon, &mut rand::thread_rng());
+        self.spend(epsilon, 0.0);
+        (self.synthetic_counts, self.synthetic_kl, self.synthetic_correlation) = self.synthetic_twin_counts(&twin).unwrap_or_default();
+        let gradients = self.federated_gradients();
+        let noise_multiplier = self.federated_noise_multiplier();
+        let gradient_noiser = SimulatedGradientNoiser { clip_norm: self.clip_norm, noise_multiplier };
+        (self.federated_sum, self.federated_clipped) = gradient_noiser.noised_sum(&gradients, &mut rand::thread_rng());
+        self.spend(epsilon, delta);
+        self.dp_gd_curve = self.dp_gradient_descent(DP_GD_STEPS);
+        if !self.dp_gd_curve.is_empty() {
+            self.spend(epsilon, delta);
+        }
+        (self.pca_points, self.pca_angle) = match self.private_pca(epsilon, &twin).filter(|_| epsilon.is_finite()) {
+            Some((points, angle)) => (points, Some(angle)),
+            None => (vec![], None),
+        };
+        if self.pca_angle.is_some() {
+            self.spend(epsilon, 0.0);
+        }
+        // a failed PTR test spends as much as a count
+        self.married_count = self.count_with_predicate(|record| record.married == 1).ok();
+        self.spend(epsilon, histogram_delta);
+        if epsilon.is_finite() {
+            self.unknown_domain_histogram =
+                self.private_histogram_unknown_domain(self.aggregate_field, epsilon, stability_threshold(epsilon, delta));
+            self.spend(epsilon, delta);
+        }
+        self.central_educ_count = self.count_with_predicate(|record| record.educ > 10).ok();
+        self.spend(epsilon, histogram_delta);
+        self.local_educ_count = read_data(&self.dataset.data, self.dataset.separator).ok().map(|records| {
+            let true_p = records.iter().filter(|record| record.educ > 10).count() as f64
+                / records.len().max(1) as f64;
+            self.local_dp_count(true_p, self.epsilon())
+        });
+        if self.local_educ_count.is_some() {
+            self.spend(epsilon, 0.0);
+        }
+        match self.noise_target {
+            Counts => {
+                self.noised_data = match self.suppression_k {
+                    Some(k) => self.noised_with_suppression(k),
+                    None => self.noised_data(&self.aggregated_data).unwrap(),
+                };
+                self.spend(histogram_epsilon, histogram_delta);
+                if self.consistency {
+                    // the total number of records is treated as public, as in Hay et al.
+                    let total = self.aggregated_data.iter().sum();
+                    let noised = self.noised_data.iter().map(|&count| count as f64).collect::<Vec<_>>();
+                    let before = self.mean_squared_error();
+                    self.noised_data = project_nonneg_sum_constraint(&noised, total);
+                    self.consistency_mse = Some((before, self.mean_squared_error()));
+                }
+                if epsilon.is_finite() {
+                    self.remember_release();
+                }
+                self.proportion_intervals = self.proportion_intervals(PROPORTION_CI_ALPHA);
+                // a bucket at the average size is as large as a uniform histogram would make it
+                self.svt_threshold = self.dataset.record_count() as f64 / self.aggregated_data.len().max(1) as f64;
+                self.svt_selected = self.sparse_vector_above_threshold(self.svt_threshold, SVT_MAX_ABOVE, self.epsilon());
+                self.private_mode = Some(self.report_noisy_max(self.epsilon()));
+                self.spend(2.0 * epsilon, 0.0);
+                self.top_k_recovery = epsilon.is_finite().then(|| self.top_k_recovery(TOP_K, epsilon, ERROR_TRIALS));
+                if epsilon.is_finite() {
+                    self.range_cdf = self.private_cdf(epsilon);
+                    self.spend(epsilon, delta);
+                    self.subspace_range_errors = self.subspace_range_errors(epsilon);
+                    self.sparse_gaussian = Some(self.sparse_gaussian_mse(epsilon, ERROR_TRIALS));
+                    let (count, histogram) = self.stability_extension_histogram(epsilon);
+                    self.private_count = Some((count, histogram.iter().sum()));
+                    self.spend(epsilon, 0.0);
+                    let levels = self.individual_privacy_levels(epsilon);
+                    let smallest = levels.iter().copied().fold(f64::INFINITY, f64::min);
+                    let uniform = 2.0 * (self.sensitivity() / smallest).powi(2);
+                    let measured = metrics::mean_squared_error(&self.aggregated_data, &self.heterogeneous_dp_histogram(&levels));
+                    self.heterogeneous_dp = Some((measured, uniform, smallest));
+                    self.spend(epsilon, 0.0);
+                    self.individual_epsilons = PRIVACY_TIERS.iter()
+                        .map(|(_, share)| (share * epsilon, levels.iter().filter(|&&level| level == share * epsilon).count() as u64))
+                        .collect();
+                }
+                self.per_instance_kl = epsilon.is_finite().then(|| self.per_instance_dp(0, epsilon));
+                let hockey_stick_epsilon = HOCKEY_STICK_SHARE * epsilon;
+                self.hockey_stick = self.theoretical_hockey_stick(hockey_stick_epsilon)
+                    .filter(|_| epsilon.is_finite())
+                    .map(|exact| {
+                        let empirical = self.hockey_stick_divergence(hockey_stick_epsilon, CALIBRATION_SAMPLES, HOCKEY_STICK_SEED);
+                        (hockey_stick_epsilon, empirical, exact)
+                    });
+                // allocating by the true counts would leak them, the noised ones are free to use
+                self.bucket_epsilons = adaptive_epsilon_allocation(&self.noised_data, self.epsilon());
+                self.observed_worst_error = self.observed_worst_error(ERROR_TRIALS);
+                self.unique_output_share = Some(self.reidentification_risk_estimate(ERROR_TRIALS));
+                self.utility_regret = epsilon.is_finite().then(|| self.utility_regret(ERROR_TRIALS));
+                if let Some(target) = read_data(&self.dataset.data, self.dataset.separator).ok()
+                    .and_then(|records| records.first().cloned()) {
+                    self.mi_advantage = Some(self.membership_inference_attack(&target, MI_SHADOW_DATASETS, MI_SEED));
+                }
+                if self.noise_type == CdfNoise {
+                    self.isotonic_mse = Some(self.compare_isotonic_cdf(ERROR_TRIALS));
+                }
+                if matches!(self.noise_type, CdfNoise | Wavelet | Hierarchical) {
+                    self.range_query_comparison = Some(self.compare_range_queries(ERROR_TRIALS));
+                }
+                if matches!(self.noise_type, CdfNoise | CorrelatedLaplace { .. } | Wavelet | Hierarchical | TruncatedLaplace { .. }) {
+                    self.laplace_comparison = Some(self.compare_to_direct_laplace(ERROR_TRIALS));
+                }
+                if self.noise_type == Laplace {
+                    self.calibration_verified =
+                        Some(self.laplace_calibration_test(CALIBRATION_SAMPLES, self.accuracy, self.alpha));
+                    self.sampler_comparison = self.compare_discrete_laplace_samplers(CALIBRATION_SAMPLES);
+                }
+            }
+            Frequencies => {
+                self.noised_frequencies = self.noised_frequencies().unwrap();
+                self.spend(epsilon, histogram_delta);
+            }
+        }
+    }
+
+    fn clear_previous_data(&mut self) {
+        self.noised_data.clear();
+        self.unique_output_share = None;
+        self.noised_frequencies.clear();
+        self.mi_advantage = None;
+        self.calibration_verified = None;
+        self.sampler_comparison = None;
+        self.laplace_comparison = None;
+        self.range_query_comparison = None;
+        self.consistency_mse = None;
+        self.isotonic_mse = None;
+        self.svt_selected.clear();
+        self.bucket_epsilons.clear();
+        self.private_mode = None;
+        self.range_cdf.clear();
+        self.heterogeneous_dp = None;
+        self.individual_epsilons.clear();
+        self.subspace_range_errors = None;
+        self.sparse_gaussian = None;
+        self.private_count = None;
+        self.proportion_intervals.clear();
+        self.top_k_recovery = None;
+        self.per_instance_kl = None;
+        self.hockey_stick = None;
+        self.utility_regret = None;
+        self.ptr_passed = None;
+        self.query_rejected = false;
+        self.query_answer = None;
+        self.multi_stat_answers.clear();
+        self.smooth_median = None;
+        self.private_medians = None;
+        self.ptr_mean = None;
+        self.synthetic_counts.clear();
+        self.federated_sum.clear();
+        self.dp_gd_curve.clear();
+        self.pca_points.clear();
+        self.pca_angle = None;
+        self.married_count = None;
+        self.unknown_domain_histogram.clear();
+        self.central_educ_count = None;
+        self.local_educ_count = None;
+    }
+
+    /// The rows the histogram is built from, i.e. only those matching `condition` if set.
+    fn conditioned_data(&self) -> String {
+        match self.condition {
+            Some((given_field, given_value)) => self.dataset.filter_rows(given_field, given_value).data,
+            None => self.dataset.data.clone(),
+        }
+    }
+
+    /// Noise of the federated sum in clip norms: the exact calibration for an L2
+    /// sensitivity of one clip norm at the current epsilon.
+    fn federated_noise_multiplier(&self) -> f64 {
+        analytic_gaussian_scale(self.epsilon(), self.delta, 1.0)
+    }
+
+    /// `(step, epsilon at delta)` of the record used at each of `n_iterations` steps of noisy
+    /// gradient descent with the noise of the federated sum, releasing only the last iterate:
+    /// `amplification_by_iteration` of the Gaussian RDP at `order`. Empty without noise.
+    pub fn iteration_amplification(&self, n_iterations: usize, order: f64) -> Vec<(f64, f64)> {
+        if !self.epsilon().is_finite() {
+            return vec![];
+        }
+        let one_step = gaussian_rdp(self.federated_noise_multiplier(), 1.0, order);
+        (1..=n_iterations)
+            .map(|step| {
+                let amplified = amplification_by_iteration(one_step, n_iterations, step);
+                (step as f64, rdp_to_approx_dp(amplified, order, self.delta))
+            })
+            .collect()
+    }
+
+    /// MSE per bucket, in counts, of the histogram learnt by `steps` of DP gradient descent:
+    /// every record is the one-hot vector of its bucket, θ descends on the mean of
+    /// `½ |θ - e_b|²` towards the distribution of the buckets, and each step clips the
+    /// per-record gradients to `DP_GD_CLIP_NORM` and adds Gaussian noise to their sum. The
+    /// noise multiplier is `sqrt(steps)` times the analytic one at the current (epsilon,
+    /// delta) and `l2_sensitivity()`, since `steps` Gaussian releases compose exactly like one
+    /// with `sqrt(steps)` times less noise; under suppression a record counts as many times
+    /// as it does for the counts. Empty without noise.
+    pub fn dp_gradient_descent(&self, steps: usize) -> Vec<(f64, f64)> {
+        let epsilon = self.epsilon();
+        let n_buckets = self.aggregated_data.len();
+        if !epsilon.is_finite() || n_buckets == 0 {
+            return vec![];
+        }
+        let examples = self.bucket_indices().into_iter()
+            .map(|pos| (0..n_buckets).map(|i| if i == pos { 1.0 } else { 0.0 }).collect())
+            .collect::<Vec<Vec<f64>>>();
+        let noise_multiplier = (steps as f64).sqrt() * analytic_gaussian_scale(epsilon, self.delta, self.l2_sensitivity());
+        let gradient_noiser = SimulatedGradientNoiser { clip_norm: DP_GD_CLIP_NORM, noise_multiplier };
+        let n = examples.len() as f64;
+        gradient_noiser.gradient_descent(&examples, steps, DP_GD_LEARNING_RATE, &mut rand::thread_rng()).iter()
+            .enumerate()
+            .map(|(step, theta)| {
+                let squared_error = theta.iter().zip(&self.aggregated_data)
+                    .map(|(t, &count)| (n * t - count as f64).powi(2))
+                    .sum::<f64>();
+                ((step + 1) as f64, squared_error / n_buckets as f64)
+            })
+            .collect()
+    }
+
+    /// The histogram of the records arriving in each of `n_rounds` rounds, the rows split in
+    /// order into rounds of as equal size as they go.
+    fn round_histograms(&self, n_rounds: usize) -> Vec<Vec<f64>> {
+        let indices = self.bucket_indices();
+        let mut rounds = vec![vec![0.0; self.buckets().len() + 1]; n_rounds];
+        for (row, &pos) in indices.iter().enumerate() {
+            rounds[row * n_rounds / indices.len()][pos] += 1.0;
+        }
+        rounds
+    }
+
+    /// Running totals of the histogram after each of the `round_histograms` of arriving
+    /// records, released by the binary mechanism of `continual`, the correlated noise of
+    /// DP-FTRL. Every record arrives in one round, so `epsilon_per_round` is also what every
+    /// record pays for all the totals together.
+    pub fn dp_ftrl_histogram(&self, rounds: &[Vec<f64>], epsilon_per_round: f64) -> Vec<Vec<u64>> {
+        let scale = binary_mechanism_levels(rounds.len()) as f64 * SENSITIVITY / epsilon_per_round;
+        binary_mechanism(rounds, scale, &mut rand::thread_rng()).iter()
+            .map(|total| total.iter().map(|count| count.max(0.0).round() as u64).collect())
+            .collect()
+    }
+
+    /// MSE per bucket, over `trials` runs, of the running average of the counts after every
+    /// round, from `dp_ftrl_histogram` and from `Laplace(Δ / epsilon)` on every round's
+    /// histogram added up. The independent noise piles up with the rounds, the tree's only
+    /// with the ones in the binary digits of the round but at `binary_mechanism_levels` times
+    /// the scale, so the tree wins only after a few hundred rounds. Empty without noise.
+    pub fn running_average_errors(&self, n_rounds: usize, epsilon: f64, trials: usize) -> Vec<(f64, f64, f64)> {
+        if !epsilon.is_finite() || self.aggregated_data.is_empty() {
+            return vec![];
+        }
+        let rounds = self.round_histograms(n_rounds);
+        let mut rng = rand::thread_rng();
+        let mut errors = vec![(0.0, 0.0); n_rounds];
+        for _ in 0..trials {
+            let tree = self.dp_ftrl_histogram(&rounds, epsilon);
+            let (mut exact, mut independent) = (vec![0.0; rounds[0].len()], vec![0.0; rounds[0].len()]);
+            for (t, round) in rounds.iter().enumerate() {
+                for (i, count) in round.iter().enumerate() {
+                    exact[i] += count;
+                    independent[i] += count + laplace_sample(SENSITIVITY / epsilon, &mut rng);
+                }
+                let average_error = |totals: Vec<f64>| totals.iter().zip(&exact)
+                    .map(|(total, count)| ((total - count) / (t + 1) as f64).powi(2))
+                    .sum::<f64>() / exact.len() as f64;
+                errors[t].0 += average_error(tree[t].iter().map(|&count| count as f64).collect());
+                errors[t].1 += average_error(independent.iter().map(|count| count.max(0.0).round()).collect());
+            }
+        }
+        errors.iter()
+            .enumerate()
+            .map(|(t, (tree, independent))| ((t + 1) as f64, tree / trials.max(1) as f64, independent / trials.max(1) as f64))
+            .collect()
+    }
+
+    /// Everything the simulations of `refresh_data` depend on. They average over many runs
+    /// on the raw records and only rerun when this changes.
+    fn simulation_key(&self) -> SimulationKey<'a> {
+        (self.aggregate_field, self.condition, self.noise_target, self.noise_type, self.epsilon(), self.scale(), self.delta)
+    }
+
+    /// Everything the outputs of `release` depend on. Drawing them again for the same
+    /// parameters would only average the noise away, so they are kept until this changes.
+    fn release_key(&self) -> ReleaseKey<'a> {
+        (self.simulation_key(), self.suppression_k, self.consistency, self.isotonic, self.query_type,
+         self.multi_stat, self.svt_threshold_share, self.clip_norm)
+    }
+
+    /// Position of every record's `aggregate_field` value among `buckets()`, in the order of
+    /// the rows, with values outside the buckets placed last as in `aggregated_data`.
+    fn bucket_indices(&self) -> Vec<usize> {
+        let buckets = self.buckets();
+        read_data(&self.conditioned_data(), self.dataset.separator).unwrap_or_default().iter()
+            .map(|record| {
+                let value = record.field_value(self.aggregate_field).map(|value| (value as u64).to_string());
+                value.and_then(|value| buckets.iter().position(|bucket| *bucket == value)).unwrap_or(buckets.len())
+            })
+            .collect()
+    }
+
+    /// Histogram of `aggregate_field` per client, with the records of each age standing in
+    /// for the data of one client. Their sum is the histogram itself.
+    pub fn federated_gradients(&self) -> Vec<Vec<f64>> {
+        let buckets = self.buckets();
+        let Ok(records) = read_data(&self.conditioned_data(), self.dataset.separator) else {
+            return vec![];
+        };
+        let mut clients = std::collections::BTreeMap::<u8, Vec<f64>>::new();
+        for record in records {
+            let value = record.field_value(self.aggregate_field).map(|value| (value as u64).to_string());
+            if let Some(pos) = value.and_then(|value| buckets.iter().position(|bucket| *bucket == value)) {
+                clients.entry(record.age).or_insert_with(|| vec![0.0; buckets.len()])[pos] += 1.0;
+            }
+        }
+        clients.into_values().collect()
+    }
+
+    /// Histogram of `aggregate_field` over the synthetic `twin` and its divergence from the
+    /// true histogram, and the educ-income correlation of the data set and of the twin.
+    fn synthetic_twin_counts(&self, twin: &CsvDataSet) -> Option<(Vec<u64>, f64, (f64, f64))> {
+        let divergence = self.dataset.kl_divergence(twin, self.aggregate_field, KL_SMOOTHING);
+        let [a, b] = [AGGREGATE_FIELDS[0], AGGREGATE_FIELDS[1]];
+        let correlation = (self.dataset.column_correlation(a, b), twin.column_correlation(a, b));
+        Some((aggregate_data_chain(self, self.aggregate_field)?.invoke(&twin.data).ok()?, divergence, correlation))
+    }
+
+    fn aggregate_data(&self) -> Option<Vec<u64>> {
+        let chain = aggregate_data_chain(self, self.aggregate_field)?;
+        Option::from(chain.invoke(&self.conditioned_data()).ok()?)
+    }
+
+    fn noised_data(&self, aggregated_data: &Vec<u64>) -> Option<Vec<u64>> {
+        let scale = self.scale();
+        match self.noise_type {
+            Laplace => {
+                let discrete_lp = make_base_discrete_laplace::<VectorDomain<AllDomain<u64>>, _>(
+                    scale
+                ).ok()?;
+                Option::from(discrete_lp.invoke(aggregated_data).unwrap())
+            }
+            Gaussian => {
+                let discrete_gaussian =
+                    make_base_discrete_gaussian::<VectorDomain<AllDomain<u64>>, ZeroConcentratedDivergence<f64>, f64>(
+                        scale
+                    ).ok()?;
+                Option::from(discrete_gaussian.invoke(aggregated_data).unwrap())
+            }
+            Mixture { laplace_weight } => {
+                let (laplace_scale, sigma) = self.mixture_scales();
+                let counts = aggregated_data.iter().map(|&count| count as f64).collect::<Vec<_>>();
+                let noised = mixture_noise(&counts, laplace_weight, laplace_scale, sigma, &mut rand::thread_rng());
+                Option::from(noised.iter().map(|v| v.round().max(0.0) as u64).collect::<Vec<_>>())
+            }
+            ProposeTestRelease { proposed_sensitivity } => {
+                let discrete_lp = make_base_discrete_laplace::<VectorDomain<AllDomain<u64>>, _>(
+                    proposed_sensitivity as f64 * scale
+                ).ok()?;
+                Option::from(discrete_lp.invoke(aggregated_data).unwrap())
+            }
+            CdfNoise => {
+                let totals = noised_cdf(aggregated_data, scale, &mut rand::thread_rng());
+                Option::from(cdf_to_counts(&if self.isotonic { isotonic_regression(&totals) } else { totals }))
+            }
+            Wavelet => {
+                let noised = privelet_noise(aggregated_data, scale, &mut rand::thread_rng());
+                Option::from(noised.iter().map(|v| v.round().max(0.0) as u64).collect::<Vec<_>>())
+            }
+            Hierarchical => {
+                let uniform = vec![1.0; tree_levels(aggregated_data.len())];
+                let noised = hierarchical_mechanism(aggregated_data, self.epsilon(), self.sensitivity(), &uniform, &mut rand::thread_rng());
+                Option::from(noised.iter().map(|v| v.round().max(0.0) as u64).collect::<Vec<_>>())
+            }
+            TruncatedLaplace { lower, upper } => {
+                let mut rng = rand::thread_rng();
+                Option::from(aggregated_data.iter()
+                    .map(|&count| truncated_laplace_sample(count as f64, scale, lower, upper, rng.gen()).round().max(0.0) as u64)
+                    .collect::<Vec<_>>())
+            }
+            Binomial { n } => {
+                let mut rng = rand::thread_rng();
+                Option::from(aggregated_data.iter()
+                    .map(|&count| (count as i64 + binomial_noise(n, &mut rng)).max(0) as u64)
+                    .collect::<Vec<_>>())
+            }
+            CorrelatedLaplace { rho } => {
+                let counts = aggregated_data.iter().map(|&count| count as f64).collect::<Vec<_>>();
+                let noised = correlated_laplace_noise(&counts, &self.correlation_factor(rho)?, scale, &mut rand::thread_rng());
+                Option::from(noised.iter().map(|v| v.round().max(0.0) as u64).collect::<Vec<_>>())
+            }
+            AnalyticGaussian => {
+                let counts = aggregated_data.iter().map(|&count| count as f64).collect::<Vec<_>>();
+                let noised = gaussian_noise(&counts, scale, &mut rand::thread_rng());
+                Option::from(noised.iter().map(|v| v.round().max(0.0) as u64).collect::<Vec<_>>())
+            }
+            Snapping => {
+                // counts can never leave [0, PUBLIC_COUNT_BOUND]
+                let mut rng = rand::thread_rng();
+                Option::from(aggregated_data.iter()
+                    .map(|&count| snapping_sample(count as f64, scale, PUBLIC_COUNT_BOUND, &mut rng).max(0.0) as u64)
+                    .collect::<Vec<_>>())
+            }
+        }
+    }
+
+    /// Standard deviation of the noise on the relative frequencies: the count scale divided
+    /// by the `n` records the frequencies are normalized by. Replacing one record shifts two
+    /// frequencies by `1/n` each, an L2 change of `sqrt(2) / n` against the `sqrt(2)` of
+    /// `l2_sensitivity()` for the counts, so the privacy guarantee is the same.
+    fn frequency_scale(&self) -> f64 {
+        self.scale() / self.aggregated_data.iter().sum::<u64>().max(1) as f64
+    }
+
+    /// Gaussian noise of `frequency_scale()` on the relative frequencies, renormalized.
+    fn noised_frequencies(&self) -> Option<Vec<f64>> {
+        let noised = gaussian_noise(&self.aggregated_frequencies, self.frequency_scale(), &mut rand::thread_rng());
+        Option::from(renormalize(&noised))
+    }
+}
+
+impl<'a> NoiseApplier<'a> for Noiser<'a> {
+    fn new(dataset: &'a CsvDataSet, aggregate_field: &'a str) -> Self {
+        Noiser {
+            dataset,
+            aggregate_field,
+            condition: None,
+            suppression_k: None,
+            consistency: false,
+            isotonic: true,
+            query_type: QueryType::Count,
+            query_answer: None,
+            multi_stat: MultiStatQuery { count: true, mean: true, variance: true },
+            multi_stat_answers: vec![],
+            smooth_median: None,
+            median_comparison: false,
+            private_medians: None,
+            ptr_mean: None,
+            synthetic_counts: vec![],
+            synthetic_kl: 0.0,
+            synthetic_correlation: (0.0, 0.0),
+            clip_norm: DEFAULT_CLIP_NORM,
+            federated_sum: vec![],
+            federated_clipped: 0,
+            dp_gd_curve: vec![],
+            dp_gd_one_shot_mse: 0.0,
+            ftrl_errors: vec![],
+            projection_tradeoff: vec![],
+            projection_direct_mse: 0.0,
+            pca_points: vec![],
+            pca_angle: None,
+            noise_type: Laplace,
+            noise_target: Counts,
+            accuracy: 0,
+            alpha: DEFAULT_ALPHA,
+            delta: 1e-6,
+            budget: None,
+            aggregated_data: Vec::<u64>::new(),
+            noised_data: Vec::<u64>::new(),
+            aggregated_frequencies: Vec::<f64>::new(),
+            noised_frequencies: Vec::<f64>::new(),
+            empirical_sensitivity: 0.0,
+            contaminated_sensitivity: 0.0,
+            jackknife_samples: DEFAULT_JACKKNIFE_SAMPLES,
+            jackknife_estimate: 0.0,
+            observed_worst_error: 0.0,
+            unique_output_share: None,
+            tradeoff: Vec::<(f64, f64)>::new(),
+            show_bootstrap: false,
+            bootstrap_intervals: Vec::new(),
+            proportion_intervals: Vec::new(),
+            bootstrap_key: None,
+            sensitivity_key: None,
+            simulation_key: None,
+            release_key: None,
+            release_cost: (0.0, 0.0),
+            odometer: PrivacyOdometer::default(),
+            bucket_epsilons: vec![],
+            private_mode: None,
+            range_cdf: vec![],
+            heterogeneous_dp: None,
+            sparse_gaussian: None,
+            distributional_epsilons: None,
+            private_count: None,
+            individual_epsilons: vec![],
+            subspace_range_errors: None,
+            top_k_recovery: None,
+            per_instance_kl: None,
+            hockey_stick: None,
+            utility_regret: None,
+            svt_selected: vec![],
+            svt_threshold: 0.0,
+            svt_threshold_share: None,
+            privacy_loss_pdf: Vec::<(f64, f64)>::new(),
+            approx_dp_curve: vec![],
+            married_count: None,
+            unknown_domain_histogram: BTreeMap::new(),
+            central_educ_count: None,
+            local_educ_count: None,
+            local_histogram_mse: None,
+            perturbation_mse: None,
+            mi_advantage: None,
+            calibration_verified: None,
+            sampler_comparison: None,
+            laplace_comparison: None,
+            range_query_comparison: None,
+            consistency_mse: None,
+            isotonic_mse: None,
+            ptr_passed: None,
+            query_rejected: false,
+            releases: VecDeque::new(),
+        }
+    }
+
+    fn toggle_noise_type(&mut self) {
+        self.noise_type = match self.noise_type {
+            Laplace => Gaussian,
+            Gaussian => Snapping,
+            Snapping => Mixture { laplace_weight: DEFAULT_LAPLACE_WEIGHT },
+            Mixture { .. } => ProposeTestRelease { proposed_sensitivity: DEFAULT_PROPOSED_SENSITIVITY },
+            ProposeTestRelease { .. } => AnalyticGaussian,
+            AnalyticGaussian => CdfNoise,
+            CdfNoise => CorrelatedLaplace { rho: DEFAULT_CORRELATION_RHO },
+            CorrelatedLaplace { .. } => Wavelet,
+            Wavelet => Hierarchical,
+            Hierarchical => Binomial { n: DEFAULT_BINOMIAL_TRIALS },
+            Binomial { .. } => TruncatedLaplace { lower: 0.0, upper: PUBLIC_COUNT_BOUND },
+            TruncatedLaplace { .. } => Laplace,
+        };
+        self.refresh_data()
+    }
+
+    fn toggle_noise_target(&mut self) {
+        self.noise_target = match self.noise_target {
+            Counts => Frequencies,
+            Frequencies => Counts,
+        };
+        self.refresh_data()
+    }
+
+    fn increase_noise(&mut self) {
+        if let Binomial { n } = self.noise_type {
+            let index = BINOMIAL_TRIALS.iter().position(|&trials| trials == n).unwrap_or(0);
+            self.noise_type = Binomial { n: BINOMIAL_TRIALS[(index + 1) % BINOMIAL_TRIALS.len()] };
+        } else {
+            self.accuracy = (self.accuracy + 1) % ACCURACY_LEVELS;
+        }
+        self.refresh_data()
+    }
+
+    fn decrease_noise(&mut self) {
+        if let Binomial { n } = self.noise_type {
+            let index = BINOMIAL_TRIALS.iter().position(|&trials| trials == n).unwrap_or(0);
+            self.noise_type = Binomial { n: BINOMIAL_TRIALS[(index + BINOMIAL_TRIALS.len() - 1) % BINOMIAL_TRIALS.len()] };
+        } else {
+            self.accuracy = (self.accuracy + ACCURACY_LEVELS - 1) % ACCURACY_LEVELS;
+        }
+        self.refresh_data()
+    }
+
+    fn toggle_condition(&mut self) {
+        self.condition = match self.condition {
+            None => Some(("sex", "0")),
+            Some(("sex", "0")) => Some(("sex", "1")),
+            Some(_) => None,
+        };
+        self.refresh_data()
+    }
+
+    fn toggle_suppression(&mut self) {
+        self.suppression_k = match self.suppression_k {
+            None => Some(DEFAULT_SUPPRESSION_K),
+            Some(_) => None,
+        };
+        self.refresh_data()
+    }
+
+    fn toggle_consistency(&mut self) {
+        self.consistency = !self.consistency;
+        self.refresh_data()
+    }
+
+    fn toggle_isotonic(&mut self) {
+        self.isotonic = !self.isotonic;
+        self.refresh_data()
+    }
+
+    fn toggle_multi_stat(&mut self) {
+        self.multi_stat = self.multi_stat.next();
+        self.refresh_data()
+    }
+
+    fn toggle_bootstrap(&mut self) {
+        self.show_bootstrap = !self.show_bootstrap;
+        self.refresh_data()
+    }
+
+    fn toggle_median_comparison(&mut self) {
+        self.median_comparison = !self.median_comparison;
+        self.refresh_data()
+    }
+
+    fn toggle_svt_split(&mut self) {
+        self.svt_threshold_share = match self.svt_threshold_share {
+            None => Some(SVT_THRESHOLD_SHARES[0]),
+            Some(share) => SVT_THRESHOLD_SHARES.iter().find(|&&next| next > share).copied(),
+        };
+        self.refresh_data()
+    }
+
+    /// Commits to `PRECOMMIT_QUERIES` releases, or drops the commitment; without noise there
+    /// is nothing to reserve for. Nothing is released, so unlike the other toggles this does
+    /// not refresh.
+    fn toggle_precommit(&mut self) {
+        let queries = if self.odometer.commitment.is_some() || self.epsilon().is_infinite() { 0 } else { PRECOMMIT_QUERIES };
+        self.precommit_query_sequence(queries);
+    }
+
+    fn toggle_clip_norm(&mut self) {
+        let pos = CLIP_NORMS.iter().position(|&norm| norm == self.clip_norm).unwrap_or(0);
+        self.clip_norm = CLIP_NORMS[(pos + 1) % CLIP_NORMS.len()];
+        self.refresh_data()
+    }
+
+    fn toggle_query_type(&mut self) {
+        self.query_type = self.query_type.next(field_bounds(self.aggregate_field).unwrap_or((0.0, 1.0)));
+        self.refresh_data()
+    }
+
+    fn reset_accuracy(&mut self) {
+        self.accuracy = 0;
+        self.refresh_data()
+    }
+
+    fn reset(&mut self) {
+        self.accuracy = 0;
+        self.noise_type = Laplace;
+        self.alpha = DEFAULT_ALPHA;
+        self.refresh_data()
+    }
+
+    fn refresh_data(&mut self) {
+        // the bounds follow the field when it is switched
+        if let (QueryType::BoundedSum { .. }, Some((lower, upper))) = (self.query_type, field_bounds(self.aggregate_field)) {
+            self.query_type = QueryType::BoundedSum { lower, upper };
+        }
+        self.aggregated_data = self.aggregate_data().unwrap();
+        self.aggregated_frequencies = to_frequencies(&self.aggregated_data);
+        // the estimates rerun the aggregation on many neighbours, and only move with the data
+        let sensitivity_key = Some((self.aggregate_field, self.condition, self.suppression_k));
+        if self.sensitivity_key != sensitivity_key {
+            self.empirical_sensitivity = self.estimate_sensitivity_empirically(SENSITIVITY_TRIALS, SENSITIVITY_SEED);
+            self.contaminated_sensitivity = self.contaminated_sensitivity(CONTAMINATION_FRACTION);
+            self.jackknife_estimate = self.jackknife_sensitivity();
+            self.sensitivity_key = sensitivity_key;
+        }
+        self.tradeoff = self.compute_f_dp_tradeoff(TRADEOFF_POINTS);
+        // resampling is slow, and only the field and the condition move the intervals
+        let bootstrap_key = Some((self.aggregate_field, self.condition));
+        if !self.show_bootstrap {
+            self.bootstrap_intervals.clear();
+            self.bootstrap_key = None;
+        } else if self.bootstrap_key != bootstrap_key {
+            self.bootstrap_intervals = self.bootstrap_intervals(BOOTSTRAP_SAMPLES);
+            self.bootstrap_key = bootstrap_key;
+        }
+        // the simulations are slow, and only move with the field, the condition and the noise
+        let simulation_key = Some(self.simulation_key());
+        if self.simulation_key != simulation_key {
+            self.local_histogram_mse = self.local_histogram_mse(self.epsilon(), LDP_TRIALS);
+            self.ftrl_errors = self.running_average_errors(FTRL_ROUNDS, self.epsilon(), FTRL_TRIALS);
+            self.perturbation_mse = self.perturbation_mse(self.epsilon(), LDP_TRIALS);
+            self.distributional_epsilons = ((self.noise_target, self.noise_type) == (Counts, Laplace) && self.epsilon().is_finite())
+                .then(|| (self.distributional_dp_epsilon("uniform"), self.distributional_dp_epsilon("empirical")));
+            self.simulation_key = simulation_key;
+        }
+        self.privacy_loss_pdf = self.privacy_loss_distribution(PRIVACY_LOSS_POINTS);
+        self.approx_dp_curve = self.approx_dp_curve(PRIVACY_LOSS_POINTS);
+        self.dp_gd_one_shot_mse = analytic_gaussian_scale(self.epsilon(), self.delta, self.l2_sensitivity()).powi(2);
+        (self.projection_tradeoff, self.projection_direct_mse) = if self.epsilon().is_finite() {
+            self.projection_mse_tradeoff(ERROR_TRIALS)
+        } else {
+            (vec![], 0.0)
+        };
+        // a new release is only drawn, and paid for, when something it depends on changed
+        let release_key = Some(self.release_key());
+        if self.release_key == release_key {
+            return;
+        }
+        self.release_key = release_key;
+        self.clear_previous_data();
+        match self.budget {
+            // without noise the view is the ground truth, not a DP release: nothing to charge
+            _ if self.epsilon().is_infinite() => self.release(),
+            Some(budget) => self.query_rejected = self.privacy_filter(budget, Self::release).is_none(),
+            None => {
+                self.release();
+                self.record_on_odometer();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_data() -> String {
+        (0..500).map(|i| format!("30,1,{},1,50000,1", i % 20 + 1)).collect::<Vec<_>>().join("\n")
+    }
+
+    fn test_dataset() -> CsvDataSet {
+        CsvDataSet { data: test_data(), separator: ',' }
+    }
+
+    /// Noiser over educ in `dataset` at `accuracy`, refreshed; accuracy 0 adds no noise.
+    fn refreshed_noiser(dataset: &CsvDataSet, accuracy: usize) -> Noiser<'_> {
+        let mut noiser = Noiser::new(dataset, "educ");
+        noiser.accuracy = accuracy;
+        noiser.refresh_data();
+        noiser
+    }
+
+    #[test]
+    fn laplace_is_recommended_for_histograms_at_small_delta() {
+        let (noise_type, mse) = recommend_mechanism(1.0, 1e-6, 1, 10);
+        let q = (-1.0f64).exp();
+        assert!(noise_type == Laplace);
+        assert!((mse - 10.0 * 2.0 * q / (1.0 - q).powi(2)).abs() < 1e-9);
+        // a delta this large buys the Gaussians enough, the discrete one has less variance
+        assert!(recommend_mechanism(1.0, 0.3, 1, 10).0 == Gaussian);
+    }
+
+    #[test]
+    fn unknown_domain_histogram_drops_rare_values() {
+        let dataset = CsvDataSet { data: format!("{}\n30,1,21,1,50000,1", test_data()), separator: ',' };
+        let noiser = Noiser::new(&dataset, "educ");
+        let histogram = noiser.private_histogram_unknown_domain("educ", 10.0, stability_threshold(10.0, 1e-6));
+        assert_eq!(histogram.len(), 20);
+        assert!(!histogram.contains_key("21"));
+        assert!(histogram.values().all(|&count| count.abs_diff(25) <= 2));
+    }
+
+    #[test]
+    fn release_history_keeps_the_newest() {
+        let dataset = test_dataset();
+        let mut noiser = Noiser::new(&dataset, "educ");
+        for i in 0..=MAX_RELEASES as u64 {
+            noiser.noised_data = vec![i];
+            noiser.remember_release();
+        }
+        let history = noiser.release_history();
+        assert_eq!(history.len(), MAX_RELEASES);
+        assert_eq!(history.front().unwrap().noised_counts, vec![1]);
+        assert_eq!(history.back().unwrap().noised_counts, vec![MAX_RELEASES as u64]);
+    }
+
+    #[test]
+    fn unique_outputs_grow_with_the_noise() {
+        let dataset = test_dataset();
+        let mut noiser = refreshed_noiser(&dataset, 0);
+        assert_eq!(noiser.reidentification_risk_estimate(20), 0.0);
+        noiser.accuracy = 5;
+        assert!(noiser.reidentification_risk_estimate(20) > 0.9);
+    }
+
+    #[test]
+    fn budget_plan_spends_the_total_with_less_error_than_an_equal_split() {
+        let dataset = test_dataset();
+        let noiser = Noiser::new(&dataset, "income");
+        let (total, plan) = noiser.budget_plan();
+        let mse = |epsilon: fn(&(QueryType, f64, f64, f64)) -> f64| {
+            plan.iter().map(|step| 2.0 * (step.1 / epsilon(step)).powi(2)).sum::<f64>()
+        };
+        assert!((plan.iter().map(|step| step.3).sum::<f64>() - total).abs() < 1e-12);
+        assert!(mse(|step| step.3) < mse(|step| step.2));
+    }
+
+    #[test]
+    fn privacy_filter_stops_before_the_budget_is_exceeded() {
+        let dataset = test_dataset();
+        let mut noiser = Noiser::new(&dataset, "educ");
+        noiser.accuracy = 5;
+        let epsilon = noiser.epsilon();
+        let budget = 2.5 * epsilon;
+        let query = |noiser: &mut Noiser| {
+            noiser.spend(epsilon, 0.0);
+            noiser.aggregated_data.clone()
+        };
+        let answers = (0..4).map(|_| noiser.privacy_filter(budget, query)).collect::<Vec<_>>();
+        assert_eq!(answers.iter().filter(|answer| answer.is_some()).count(), 2);
+        assert!(answers[2].is_none() && answers[3].is_none());
+        assert!(noiser.odometer.spent() <= budget);
+    }
+
+    #[test]
+    fn auxiliary_risk_adds_up_the_releases_of_other_fields() {
+        let dataset = test_dataset();
+        let mut noiser = refreshed_noiser(&dataset, 0);
+        let history = |noiser: &Noiser| Vec::from(noiser.release_history().clone());
+        assert_eq!(noiser.auxiliary_information_risk(&history(&noiser)), 0.0);
+        noiser.accuracy = 5;
+        noiser.refresh_data();
+        let educ_epsilon = noiser.epsilon();
+        assert_eq!(noiser.auxiliary_information_risk(&history(&noiser)), 0.0);
+        noiser.aggregate_field = "age";
+        noiser.accuracy = 10;
+        noiser.refresh_data();
+        assert_eq!(noiser.auxiliary_information_risk(&history(&noiser)), educ_epsilon);
+        noiser.aggregate_field = "educ";
+        noiser.refresh_data();
+        assert_eq!(noiser.auxiliary_information_risk(&history(&noiser)), noiser.epsilon());
+    }
+
+    #[test]
+    fn empirical_sensitivity_stays_within_the_condition() {
+        let data = (0..500).map(|i| format!("30,{},{},1,50000,1", i % 2, i % 20 + 1)).collect::<Vec<_>>().join("\n");
+        let dataset = CsvDataSet { data, separator: ',' };
+        let mut noiser = Noiser::new(&dataset, "educ");
+        noiser.condition = Some(("sex", "0"));
+        noiser.aggregated_data = noiser.aggregate_data().unwrap();
+        assert_eq!(noiser.aggregated_data.iter().sum::<u64>(), 250);
+        assert_eq!(noiser.estimate_sensitivity_empirically(SENSITIVITY_TRIALS, SENSITIVITY_SEED), 1.0);
+    }
+
+    #[test]
+    fn contamination_leaves_the_global_sensitivity_mechanisms_alone() {
+        let data = (0..500).map(|i| format!("30,{},{},1,50000,1", i % 2, i % 20 + 1)).collect::<Vec<_>>().join("\n");
+        let dataset = CsvDataSet { data, separator: ',' };
+        let mut noiser = Noiser::new(&dataset, "educ");
+        noiser.accuracy = 5;
+        for condition in [None, Some(("sex", "0"))] {
+            for noise_type in [Laplace, Gaussian] {
+                noiser.condition = condition;
+                noiser.noise_type = noise_type;
+                noiser.aggregated_data = noiser.aggregate_data().unwrap();
+                noiser.contaminated_sensitivity = noiser.contaminated_sensitivity(CONTAMINATION_FRACTION);
+                assert_eq!(noiser.robust_privacy_loss(), noiser.epsilon());
+            }
+        }
+    }
+
+    #[test]
+    fn frequencies_are_noised_for_the_conditioned_records() {
+        let data = (0..500).map(|i| format!("30,{},{},1,50000,1", i % 2, i % 20 + 1)).collect::<Vec<_>>().join("\n");
+        let dataset = CsvDataSet { data, separator: ',' };
+        let mut noiser = Noiser::new(&dataset, "educ");
+        noiser.accuracy = 5;
+        noiser.noise_target = Frequencies;
+        noiser.condition = Some(("sex", "0"));
+        noiser.aggregated_data = noiser.aggregate_data().unwrap();
+        assert_eq!(noiser.frequency_scale(), noiser.scale() / 250.0);
+        assert_eq!(noiser.gdp_mu(), Some(std::f64::consts::SQRT_2 / noiser.scale()));
+    }
+
+    #[test]
+    fn the_noiseless_view_is_not_charged() {
+        let dataset = test_dataset();
+        let mut noiser = refreshed_noiser(&dataset, 0);
+        assert_eq!(noiser.noised_data, noiser.aggregated_data);
+        assert_eq!((noiser.odometer.release_count(), noiser.odometer.current_bound()), (0, (0.0, 0.0)));
+        assert!(noiser.release_history().is_empty());
+        noiser.budget = Some(1.0);
+        noiser.aggregate_field = "age";
+        noiser.refresh_data();
+        assert!(!noiser.query_rejected && !noiser.noised_data.is_empty());
+        assert_eq!(noiser.odometer.release_count(), 0);
+        noiser.budget = None;
+        noiser.accuracy = 5;
+        noiser.refresh_data();
+        assert_eq!(noiser.odometer.spent(), noiser.release_cost().0);
+        assert!(noiser.odometer.spent().is_finite());
+    }
+
+    #[test]
+    fn failed_ptr_test_is_charged_for_the_test_alone() {
+        let dataset = test_dataset();
+        let mut noiser = Noiser::new(&dataset, "educ");
+        noiser.accuracy = 5;
+        // below the sensitivity of 1 the distance to instability is zero
+        noiser.noise_type = ProposeTestRelease { proposed_sensitivity: 0 };
+        noiser.refresh_data();
+        assert_eq!(noiser.ptr_passed, Some(false));
+        assert!(noiser.noised_data.is_empty());
+        assert_eq!(noiser.odometer.spent(), 1.0 / noiser.scale());
+        // a record can move a count by 3 under suppression, a proposal of 1 no longer holds
+        noiser.suppression_k = Some(3);
+        assert_eq!(noiser.epsilon(), 4.0 / noiser.scale());
+        noiser.noise_type = ProposeTestRelease { proposed_sensitivity: 1 };
+        noiser.refresh_data();
+        assert_eq!(noiser.ptr_passed, Some(false));
+        assert_eq!(noiser.odometer.spent(), 2.0 / noiser.scale());
+        noiser.noise_type = ProposeTestRelease { proposed_sensitivity: 3 };
+        noiser.refresh_data();
+        assert_eq!(noiser.ptr_passed, Some(true));
+        assert!(!noiser.noised_data.is_empty());
+    }
+
+    #[test]
+    fn refused_refresh_releases_nothing() {
+        let dataset = test_dataset();
+        let mut noiser = refreshed_noiser(&dataset, 5);
+        let spent = noiser.odometer.spent();
+        assert!((spent - noiser.release_cost().0).abs() < 1e-9);
+        noiser.refresh_data();
+        assert_eq!(noiser.odometer.spent(), spent);
+        noiser.budget = Some(spent);
+        noiser.accuracy = 4;
+        noiser.refresh_data();
+        assert!(noiser.query_rejected);
+        assert_eq!(noiser.odometer.spent(), spent);
+        assert!(noiser.noised_data.is_empty() && noiser.query_answer.is_none() && noiser.married_count.is_none());
+        assert!(noiser.pca_points.is_empty() && noiser.private_mode.is_none() && noiser.synthetic_counts.is_empty());
+    }
+
+    #[test]
+    fn heterogeneous_dp_keeps_every_record_at_a_common_level() {
+        let dataset = test_dataset();
+        let noiser = refreshed_noiser(&dataset, 0);
+        let levels = noiser.individual_privacy_levels(1.0);
+        assert_eq!(levels.len(), 500);
+        assert!(levels.iter().all(|level| PRIVACY_TIERS.iter().any(|(_, share)| share == level)));
+        // at a common level every record is kept, and at ε = 50 the noise rounds away
+        assert_eq!(noiser.heterogeneous_dp_histogram(&[50.0; 500]), noiser.aggregated_data);
+    }
+
+    #[test]
+    fn side_information_never_raises_the_privacy_loss() {
+        let dataset = test_dataset();
+        let noiser = refreshed_noiser(&dataset, 5);
+        let presence = noiser.verify_worst_case_privacy_loss().unwrap();
+        assert_eq!(noiser.conditional_privacy_loss((3, 3)), Some(presence));
+        assert_eq!(noiser.conditional_privacy_loss((0, 4)), Some(2.0 * presence));
+        assert_eq!(noiser.conditional_privacy_loss((0, noiser.aggregated_data.len())), None);
+    }
+
+    #[test]
+    fn range_query_differences_the_private_cdf() {
+        assert_eq!(range_query(&[2, 5, 5, 9], 0, 1), 5);
+        assert_eq!(range_query(&[2, 5, 5, 9], 2, 3), 4);
+        let dataset = test_dataset();
+        let noiser = refreshed_noiser(&dataset, 0);
+        let cdf = noiser.private_cdf(1000.0);
+        assert!(cdf.windows(2).all(|pair| pair[0] <= pair[1]));
+        let total = noiser.aggregated_data.iter().sum::<u64>();
+        assert!(range_query(&cdf, 0, cdf.len() - 1).abs_diff(total) <= 2);
+    }
+
+    #[test]
+    fn worst_case_privacy_loss_agrees_with_opendp() {
+        let dataset = test_dataset();
+        let mut noiser = Noiser::new(&dataset, "educ");
+        for accuracy in [2, 5, 10] {
+            noiser.accuracy = accuracy;
+            for noise_type in [Laplace, Gaussian] {
+                noiser.noise_type = noise_type;
+                let (ours, opendp) = noiser.opendp_privacy_parameter().unwrap();
+                assert!((ours - opendp).abs() <= 1e-9 * ours, "{} vs {} for {}", ours, opendp, noise_type);
+            }
+            noiser.noise_type = Laplace;
+            assert_eq!(noiser.verify_worst_case_privacy_loss(), Some(noiser.epsilon()));
+        }
+    }
+
+    #[test]
+    fn worst_case_error_bounds_empirical_errors() {
+        let dataset = test_dataset();
+        let noiser = refreshed_noiser(&dataset, 10);
+        let bound = noiser.worst_case_error();
+        let trials = noiser.run_n_trials(1000);
+        let exceeded = trials.iter()
+            .filter(|noised| {
+                noised.iter().zip(noiser.aggregated_data.iter())
+                    .any(|(&n, &t)| n.abs_diff(t) as f64 > bound)
+            })
+            .count();
+        // the bound may fail with probability alpha, allow for sampling error on top
+        assert!((exceeded as f64 / trials.len() as f64) <= noiser.alpha + 0.02);
+    }
+
+    #[test]
+    fn laplace_l1_distance_matches_expected_absolute_noise() {
+        let dataset = test_dataset();
+        let noiser = refreshed_noiser(&dataset, 10);
+        let trials = noiser.run_n_trials(2000);
+        let empirical = trials.iter()
+            .map(|noised| metrics::l1_distance(&noiser.aggregated_frequencies, &to_frequencies(noised)))
+            .sum::<f64>() / trials.len() as f64;
+        // E|X| = b for Laplace noise X, so the counts move by n * b in L1 and the
+        // distribution by n * b / N, to first order in the noise on the total N
+        let n_buckets = noiser.aggregated_data.len() as f64;
+        let total = noiser.aggregated_data.iter().sum::<u64>() as f64;
+        let expected = n_buckets * noiser.scale() / total;
+        assert!((empirical - expected).abs() / expected < 0.1,
+                "empirical {} vs expected {}", empirical, expected);
+    }
+
+    #[test]
+    fn opendp_laplace_calibration_holds() {
+        let dataset = test_dataset();
+        let noiser = Noiser::new(&dataset, "educ");
+        for &(accuracy, alpha) in &[(1, 0.05), (5, 0.05), (10, 0.01), (50, 0.2)] {
+            assert!(noiser.laplace_calibration_test(20000, accuracy, alpha),
+                    "calibration off at accuracy {} and alpha {}", accuracy, alpha);
+        }
+    }
+
+    #[test]
+    fn per_instance_loss_is_the_laplace_kl_of_one_bucket() {
+        let dataset = test_dataset();
+        let mut noiser = refreshed_noiser(&dataset, 0);
+        // the record moves a single count by one, at scale 1 / epsilon
+        assert!((noiser.per_instance_dp(0, 1.0) - (-1.0f64).exp()).abs() < 1e-9);
+        noiser.condition = Some(("sex", "0"));
+        noiser.refresh_data();
+        assert_eq!(noiser.per_instance_dp(0, 1.0), 0.0);
     }
 }
@@ -0,0 +1,140 @@
+//! Privacy odometer (Rogers, Roth, Ullman, Vadhan, "Privacy Odometers and Filters", NeurIPS
+//! 2016): a running bound on the privacy spent so far, valid even though every release is
+//! chosen after seeing the previous ones and no total was fixed in advance.
+
+/// Share of the odometer's delta that is not spent by the releases themselves.
+const ODOMETER_DELTA: f64 = 1e-6;
+/// Constant of the odometer fixed in advance, chosen as if the total epsilon was about 1.
+const ODOMETER_SCALE: f64 = 28.04;
+
+/// Queries committed to before they are asked, each reserving `epsilon` of the budget.
+#[derive(Clone, Copy)]
+pub struct QueryCommitment {
+    pub epsilon: f64,
+    pub queries: usize,
+    pub used: usize,
+    /// Epsilon of the releases that were paid from the reservation.
+    pub spent: f64,
+}
+
+impl QueryCommitment {
+    pub fn remaining(&self) -> usize {
+        self.queries - self.used
+    }
+
+    /// Whether a release at `epsilon` is paid from the reservation.
+    pub fn covers(&self, epsilon: f64) -> bool {
+        self.remaining() > 0 && epsilon <= self.epsilon
+    }
+
+    /// Epsilon still held back for the committed queries.
+    pub fn reserved(&self) -> f64 {
+        self.remaining() as f64 * self.epsilon
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PrivacyOdometer {
+    pub epsilon_consumed: Vec<f64>,
+    pub delta_consumed: Vec<f64>,
+    pub commitment: Option<QueryCommitment>,
+}
+
+impl PrivacyOdometer {
+    /// Pays a release from the commitment while it has queries left and the release costs
+    /// no more than was committed, from the general budget otherwise.
+    pub fn record(&mut self, epsilon: f64, delta: f64) {
+        self.epsilon_consumed.push(epsilon);
+        self.delta_consumed.push(delta);
+        if let Some(commitment) = self.commitment.as_mut().filter(|commitment| commitment.covers(epsilon)) {
+            commitment.used += 1;
+            commitment.spent += epsilon;
+        }
+    }
+
+    /// Reserves `queries * epsilon` for the next `queries` releases at up to `epsilon` each,
+    /// replacing any earlier commitment; no queries drops the commitment.
+    pub fn precommit(&mut self, queries: usize, epsilon: f64) {
+        self.commitment = (queries > 0).then_some(QueryCommitment { epsilon, queries, used: 0, spent: 0.0 });
+    }
+
+    /// Epsilon of all releases by basic composition, whether committed or not.
+    pub fn spent(&self) -> f64 {
+        self.epsilon_consumed.iter().sum()
+    }
+
+    /// Epsilon spent or still reserved once a release at `epsilon` is recorded: one paid
+    /// from the commitment takes the place of a reserved query, any other comes on top.
+    pub fn committed_after(&self, epsilon: f64) -> f64 {
+        let reserved = self.commitment.map_or(0.0, |commitment| commitment.reserved());
+        let replaced = self.commitment.filter(|commitment| commitment.covers(epsilon)).map_or(0.0, |commitment| commitment.epsilon);
+        self.spent() + reserved - replaced + epsilon
+    }
+
+    pub fn release_count(&self) -> usize {
+        self.epsilon_consumed.len()
+    }
+
+    /// Tightest (epsilon, delta) that holds for all releases so far: basic composition, or
+    /// the online advanced composition of Rogers et al. when it is smaller,
+    /// `Σ ε_i (e^ε_i - 1) / 2 + sqrt(2 (V + H) (1 + ln(V / H + 1) / 2) ln(2 / δ_g))` with
+    /// `V = Σ ε_i^2` and `H = 1 / (28.04 ln(1 / δ_g))`, at delta `δ_g + Σ δ_i`.
+    pub fn current_bound(&self) -> (f64, f64) {
+        let basic_epsilon = self.epsilon_consumed.iter().sum::<f64>();
+        let basic_delta = self.delta_consumed.iter().sum::<f64>();
+        if self.epsilon_consumed.is_empty() || basic_epsilon.is_infinite() {
+            return (basic_epsilon, basic_delta);
+        }
+        let variance = self.epsilon_consumed.iter().map(|epsilon| epsilon * epsilon).sum::<f64>();
+        let h = 1.0 / (ODOMETER_SCALE * (1.0 / ODOMETER_DELTA).ln());
+        let drift = self.epsilon_consumed.iter().map(|epsilon| epsilon * epsilon.exp_m1() / 2.0).sum::<f64>();
+        let online_epsilon = drift
+            + (2.0 * (variance + h) * (1.0 + (variance / h + 1.0).ln() / 2.0) * (2.0 / ODOMETER_DELTA).ln()).sqrt();
+        if online_epsilon < basic_epsilon {
+            (online_epsilon, basic_delta + ODOMETER_DELTA)
+        } else {
+            (basic_epsilon, basic_delta)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odometer_beats_basic_composition_over_many_small_releases() {
+        let mut odometer = PrivacyOdometer::default();
+        odometer.record(0.5, 0.0);
+        assert_eq!(odometer.current_bound(), (0.5, 0.0));
+        for _ in 0..1000 {
+            odometer.record(0.01, 0.0);
+        }
+        let (epsilon, delta) = odometer.current_bound();
+        assert!(epsilon < 0.6 * 10.5);
+        assert_eq!(delta, ODOMETER_DELTA);
+    }
+
+    #[test]
+    fn committed_releases_are_paid_from_the_reservation() {
+        let mut odometer = PrivacyOdometer::default();
+        odometer.precommit(2, 0.5);
+        odometer.record(0.5, 0.0);
+        // costs more than was committed
+        odometer.record(1.0, 0.0);
+        odometer.record(0.25, 0.0);
+        odometer.record(0.5, 0.0);
+        let commitment = odometer.commitment.unwrap();
+        assert_eq!((commitment.remaining(), commitment.reserved(), commitment.spent), (0, 0.0, 0.75));
+        assert_eq!(odometer.spent(), 2.25);
+    }
+
+    #[test]
+    fn reserved_epsilon_counts_as_committed() {
+        let mut odometer = PrivacyOdometer::default();
+        odometer.record(1.0, 0.0);
+        odometer.precommit(2, 0.5);
+        assert_eq!(odometer.committed_after(0.5), 2.0);
+        assert_eq!(odometer.committed_after(0.75), 2.75);
+    }
+}
@@ -0,0 +1,134 @@
+//! Private PCA after "Analyze Gauss" (Dwork, Talwar, Thakurta, Zhang, STOC 2014), with
+//! Laplace instead of Gaussian noise: every record, centred on the middle of the feature
+//! bounds and scaled into the unit ball, adds `x xᵀ` to a second moment matrix. Noise on
+//! that matrix is the only private step; its eigenvectors are post-processing.
+
+use rand::Rng;
+
+use crate::csv_reader::Record;
+use crate::mechanisms::laplace_sample;
+
+const JACOBI_SWEEPS: usize = 50;
+
+/// Features of `record` moved from `[0, 1]` to `[-1, 1]` and divided by the square root of
+/// their number, so that no record lies outside the unit ball.
+pub fn unit_ball_features(record: &Record) -> Vec<f64> {
+    let features = record.to_feature_vector();
+    let norm = (features.len() as f64).sqrt();
+    features.iter().map(|feature| (2.0 * feature - 1.0) / norm).collect()
+}
+
+/// `Σ x xᵀ` over the `rows`.
+pub fn second_moment_matrix(rows: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let d = rows.first().map_or(0, |row| row.len());
+    let mut matrix = vec![vec![0.0; d]; d];
+    for row in rows {
+        for i in 0..d {
+            for j in 0..d {
+                matrix[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    matrix
+}
+
+/// L1 sensitivity of the upper triangle of the second moment matrix over `d` features
+/// for records in the unit ball: `Σ_{i≤j} |x_i x_j| = ((Σ |x_i|)² + |x|²) / 2`, at most
+/// `(d + 1) / 2` since `(Σ |x_i|)² ≤ d |x|²`.
+pub fn second_moment_sensitivity(d: usize) -> f64 {
+    (d as f64 + 1.0) / 2.0
+}
+
+/// `matrix` with `Laplace(scale)` on every entry of the upper triangle, mirrored below
+/// the diagonal so that it stays symmetric.
+pub fn noised_symmetric<R: Rng>(matrix: &[Vec<f64>], scale: f64, rng: &mut R) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let mut noised = matrix.to_vec();
+    for (i, j) in (0..n).flat_map(|i| (i..n).map(move |j| (i, j))) {
+        noised[i][j] += laplace_sample(scale, rng);
+        noised[j][i] = noised[i][j];
+    }
+    noised
+}
+
+/// Eigenvalues and unit eigenvectors of a symmetric `matrix`, largest eigenvalue first, by
+/// cyclic Jacobi rotations until the off-diagonal entries vanish.
+pub fn symmetric_eigen(matrix: &[Vec<f64>]) -> Vec<(f64, Vec<f64>)> {
+    let d = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut vectors = (0..d).map(|i| (0..d).map(|j| if i == j { 1.0 } else { 0.0 }).collect::<Vec<_>>()).collect::<Vec<_>>();
+    for _ in 0..JACOBI_SWEEPS {
+        let off_diagonal = (0..d).flat_map(|i| (0..d).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum::<f64>();
+        if off_diagonal < 1e-24 {
+            break;
+        }
+        for p in 0..d {
+            for q in p + 1..d {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let (c, s) = (1.0 / (t * t + 1.0).sqrt(), t / (t * t + 1.0).sqrt());
+                // A ← Jᵀ A J and V ← V J for the rotation J in the (p, q) plane
+                for row in a.iter_mut().chain(vectors.iter_mut()) {
+                    let (xp, xq) = (row[p], row[q]);
+                    row[p] = c * xp - s * xq;
+                    row[q] = s * xp + c * xq;
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                a[p] = row_p.iter().zip(&row_q).map(|(xp, xq)| c * xp - s * xq).collect();
+                a[q] = row_p.iter().zip(&row_q).map(|(xp, xq)| s * xp + c * xq).collect();
+            }
+        }
+    }
+    // column k of the accumulated rotations is the eigenvector of a[k][k]
+    let mut pairs = (0..d)
+        .map(|k| (a[k][k], vectors.iter().map(|row| row[k]).collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    pairs.sort_by(|x, y| y.0.total_cmp(&x.0));
+    pairs
+}
+
+/// The top `k` eigenvectors of the second moment matrix of `rows` after
+/// `Laplace(second_moment_sensitivity / epsilon)` on its upper triangle, epsilon-DP for
+/// rows in the unit ball.
+pub fn private_principal_components<R: Rng>(rows: &[Vec<f64>], k: usize, epsilon: f64, rng: &mut R) -> Vec<Vec<f64>> {
+    let matrix = second_moment_matrix(rows);
+    let scale = second_moment_sensitivity(matrix.len()) / epsilon;
+    symmetric_eigen(&noised_symmetric(&matrix, scale, rng)).into_iter()
+        .take(k)
+        .map(|(_, vector)| vector)
+        .collect()
+}
+
+/// Coordinates of every row along each of the `components`.
+pub fn project_onto(rows: &[Vec<f64>], components: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    rows.iter()
+        .map(|row| components.iter().map(|component| component.iter().zip(row).map(|(c, x)| c * x).sum()).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jacobi_finds_the_eigenvectors() {
+        let pairs = symmetric_eigen(&[vec![2.0, 1.0, 0.0], vec![1.0, 2.0, 0.0], vec![0.0, 0.0, 5.0]]);
+        let values = pairs.iter().map(|(value, _)| *value).collect::<Vec<_>>();
+        assert!(values.iter().zip([5.0, 3.0, 1.0]).all(|(value, expected)| (value - expected).abs() < 1e-12));
+        let second = &pairs[1].1;
+        assert!((second[0].abs() - 0.5f64.sqrt()).abs() < 1e-12 && (second[0] - second[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn one_record_moves_the_upper_triangle_by_at_most_the_sensitivity() {
+        let corner = vec![1.0 / 6f64.sqrt(); 6];
+        let matrix = second_moment_matrix(&[corner]);
+        let moved = (0..6).flat_map(|i| (i..6).map(move |j| (i, j))).map(|(i, j)| matrix[i][j].abs()).sum::<f64>();
+        assert!((moved - second_moment_sensitivity(6)).abs() < 1e-12);
+    }
+}
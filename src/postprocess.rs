@@ -0,0 +1,75 @@
+//! Post-processing of noised releases. Anything computed from a DP output alone keeps its
+//! privacy guarantee, so these only ever trade bias for variance.
+
+/// Least-squares fit of a non-decreasing sequence to `values`, by the pool adjacent
+/// violators algorithm: neighbouring blocks that break the order are merged into their mean.
+pub fn isotonic_regression(values: &[f64]) -> Vec<f64> {
+    // (mean, size) of every block so far
+    let mut blocks: Vec<(f64, usize)> = Vec::with_capacity(values.len());
+    for &value in values {
+        let mut block = (value, 1);
+        while let Some(&(mean, size)) = blocks.last() {
+            if mean <= block.0 {
+                break;
+            }
+            blocks.pop();
+            let merged_size = size + block.1;
+            block = ((mean * size as f64 + block.0 * block.1 as f64) / merged_size as f64, merged_size);
+        }
+        blocks.push(block);
+    }
+    blocks.iter().flat_map(|&(mean, size)| std::iter::repeat_n(mean, size)).collect()
+}
+
+/// Least-squares projection of `noised` onto the non-negative vectors summing to `total`,
+/// the consistency step of Hay et al. 2010 for a histogram whose total is public. The
+/// projection shifts every value by the same threshold and clips at zero; the result is
+/// rounded to counts by largest remainder so that it still sums to `total`.
+pub fn project_nonneg_sum_constraint(noised: &[f64], total: u64) -> Vec<u64> {
+    if noised.is_empty() {
+        return Vec::new();
+    }
+    let mut sorted = noised.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    // the threshold is set by the largest values that stay positive after the shift
+    let mut prefix_sum = 0.0;
+    let mut threshold = 0.0;
+    for (i, &value) in sorted.iter().enumerate() {
+        prefix_sum += value;
+        let candidate = (prefix_sum - total as f64) / (i + 1) as f64;
+        if value > candidate {
+            threshold = candidate;
+        }
+    }
+    let projected = noised.iter().map(|&value| (value - threshold).max(0.0)).collect::<Vec<_>>();
+
+    let mut counts = projected.iter().map(|value| value.floor() as u64).collect::<Vec<_>>();
+    let remaining = total.saturating_sub(counts.iter().sum());
+    let mut by_remainder = (0..projected.len()).collect::<Vec<_>>();
+    by_remainder.sort_by(|&a, &b| projected[b].fract().total_cmp(&projected[a].fract()));
+    for &i in by_remainder.iter().cycle().take(remaining as usize) {
+        counts[i] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isotonic_regression_pools_violators() {
+        assert_eq!(isotonic_regression(&[1.0, 3.0, 2.0, 4.0]), vec![1.0, 2.5, 2.5, 4.0]);
+        assert_eq!(isotonic_regression(&[3.0, 2.0, 1.0]), vec![2.0, 2.0, 2.0]);
+        assert_eq!(isotonic_regression(&[0.0, 1.0, 2.0]), vec![0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn projection_is_non_negative_and_sums_to_total() {
+        assert_eq!(project_nonneg_sum_constraint(&[12.0, 6.0, 3.0], 18), vec![11, 5, 2]);
+        assert_eq!(project_nonneg_sum_constraint(&[10.0, -4.0, 2.0], 10), vec![9, 0, 1]);
+        assert_eq!(project_nonneg_sum_constraint(&[1.5, 1.5], 3), vec![2, 1]);
+        let projected = project_nonneg_sum_constraint(&[3.2, -1.0, 7.9, 0.4, 5.5], 20);
+        assert_eq!(projected.iter().sum::<u64>(), 20);
+    }
+}
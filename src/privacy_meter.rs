@@ -0,0 +1,65 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::{Color, Style};
+use tui::widgets::Widget;
+
+const GRADIENT: [(char, Color); 4] = [
+    ('░', Color::Green),
+    ('▒', Color::LightGreen),
+    ('▓', Color::Yellow),
+    ('█', Color::Red),
+];
+
+/// Maps epsilon onto [0, 1] on a logarithmic scale, so that the interesting range of small
+/// epsilons takes up most of the gauge. Anything at or above `max_displayed_epsilon`
+/// (including infinity, i.e. no noise at all) pins the gauge to 1.
+pub fn epsilon_to_gauge_position(epsilon: f64, max_displayed_epsilon: f64) -> f64 {
+    if epsilon.is_nan() || epsilon <= 0.0 {
+        return 0.0;
+    }
+    (epsilon.ln_1p() / max_displayed_epsilon.ln_1p()).min(1.0)
+}
+
+/// One-line gauge from "Max Privacy" (epsilon close to zero) to "No Privacy" (no noise).
+pub struct PrivacyMeter {
+    pub epsilon: f64,
+    pub max_displayed_epsilon: f64,
+}
+
+impl PrivacyMeter {
+    fn label(&self, position: f64) -> (&'static str, Color) {
+        if self.epsilon.is_infinite() {
+            ("No Privacy", Color::Red)
+        } else if position < 1.0 / 3.0 {
+            ("Max Privacy", Color::Green)
+        } else if position < 2.0 / 3.0 {
+            ("Balanced", Color::Yellow)
+        } else {
+            ("Utility-maximizing", Color::Red)
+        }
+    }
+}
+
+impl Widget for PrivacyMeter {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 || area.width == 0 {
+            return;
+        }
+        let position = epsilon_to_gauge_position(self.epsilon, self.max_displayed_epsilon);
+        let (label, label_color) = self.label(position);
+
+        let bar_width = area.width.saturating_sub(label.len() as u16 + 1);
+        let filled = (position * bar_width as f64).round() as u16;
+        for x in 0..bar_width {
+            let (symbol, color) = GRADIENT[(x as usize * GRADIENT.len()) / bar_width as usize];
+            let cell = buf.get_mut(area.x + x, area.y);
+            if x < filled {
+                cell.set_char(symbol).set_style(Style::default().fg(color));
+            } else {
+                cell.set_char(' ');
+            }
+        }
+        let label_width = area.width.saturating_sub(bar_width + 1) as usize;
+        buf.set_stringn(area.x + bar_width + 1, area.y, label, label_width, Style::default().fg(label_color));
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+/// Scalar aggregate over one field, to contrast with the histogram (a count per bucket).
+/// Each has its own sensitivity, so the same epsilon costs very different amounts of noise.
+#[derive(Clone, Copy, PartialEq)]
+pub enum QueryType {
+    Count,
+    Sum,
+    Average,
+    Max,
+    /// Sum of the values clamped to `[lower, upper]`, for a data set of public size whose
+    /// neighbours replace a record rather than add or remove one. Replacing moves the sum
+    /// by at most `upper - lower`, less than the `max(|lower|, |upper|)` of `Sum` when
+    /// `lower > 0`, but only when the record count itself may be released; `Sum` does not
+    /// need that.
+    BoundedSum { lower: f64, upper: f64 },
+}
+
+impl fmt::Display for QueryType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryType::Count => write!(f, "Count"),
+            QueryType::Sum => write!(f, "Sum"),
+            QueryType::Average => write!(f, "Average"),
+            QueryType::Max => write!(f, "Max"),
+            QueryType::BoundedSum { lower, upper } => write!(f, "Bounded sum [{}, {}]", lower, upper),
+        }
+    }
+}
+
+impl QueryType {
+    /// The query after this one, a bounded sum over `(lower, upper)` after the max.
+    pub fn next(&self, (lower, upper): (f64, f64)) -> QueryType {
+        match self {
+            QueryType::Count => QueryType::Sum,
+            QueryType::Sum => QueryType::Average,
+            QueryType::Average => QueryType::Max,
+            QueryType::Max => QueryType::BoundedSum { lower, upper },
+            QueryType::BoundedSum { .. } => QueryType::Count,
+        }
+    }
+
+    /// L1 sensitivity over `n` records of a field clamped to `[lower, upper]`, with
+    /// `lower >= 0`: one record more or less moves a count by 1, a sum by at most `upper`
+    /// and the maximum by up to `upper - lower`. The average assumes `n` is public, so it
+    /// moves by `(upper - lower) / n` when one record is replaced.
+    pub fn sensitivity(&self, (lower, upper): (f64, f64), n: usize) -> f64 {
+        match self {
+            QueryType::Count => 1.0,
+            QueryType::Sum => upper.abs().max(lower.abs()),
+            QueryType::Average => (upper - lower) / n.max(1) as f64,
+            QueryType::Max => upper - lower,
+            QueryType::BoundedSum { lower, upper } => upper - lower,
+        }
+    }
+
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        match self {
+            QueryType::Count => values.len() as f64,
+            QueryType::Sum => values.iter().sum(),
+            QueryType::Average => values.iter().sum::<f64>() / values.len().max(1) as f64,
+            QueryType::Max => values.iter().cloned().fold(0.0, f64::max),
+            QueryType::BoundedSum { lower, upper } => values.iter().map(|value| value.clamp(*lower, *upper)).sum(),
+        }
+    }
+}
+
+/// Shares of `total` epsilon for Laplace answers to `queries` over a field in `bounds` and
+/// `n` records that minimize the summed expected squared error `Σ 2 (Δ_i / ε_i)²` while
+/// spending `total` by basic composition: setting the derivatives equal gives
+/// `ε_i ∝ Δ_i^(2/3)`. Squared errors add across units here, so a query on a wide domain,
+/// such as a sum of incomes, draws most of the budget; an equal split treats the queries alike.
+pub fn allocate_budget(total: f64, queries: &[QueryType], bounds: (f64, f64), n: usize) -> Vec<f64> {
+    let weights = queries.iter().map(|query| query.sensitivity(bounds, n).powf(2.0 / 3.0)).collect::<Vec<_>>();
+    let total_weight = weights.iter().sum::<f64>();
+    weights.iter().map(|weight| total * weight / total_weight).collect()
+}
+
+/// Laplace scale for a sum over `[lower, upper]` under replacement, see `QueryType::BoundedSum`.
+/// Unlike the histogram counts, each of sensitivity 1 whatever the values, the noise here grows
+/// with the width of the domain: the histogram suits questions about the distribution, the
+/// bounded sum a single total over a field whose range is known and narrow.
+pub fn bounded_laplace_scale(epsilon: f64, lower: f64, upper: f64) -> f64 {
+    (upper - lower) / epsilon
+}
+
+/// One statistic of a `MultiStatQuery`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Count,
+    Mean,
+    Variance,
+}
+
+impl fmt::Display for Statistic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Statistic::Count => write!(f, "count"),
+            Statistic::Mean => write!(f, "mean"),
+            Statistic::Variance => write!(f, "variance"),
+        }
+    }
+}
+
+impl Statistic {
+    /// L1 sensitivity over `n` records (`n` public) with values in `[0, max_value]`, when
+    /// one record is replaced: the mean moves by at most `max_value / n` and the variance
+    /// by at most `max_value^2 / n`.
+    pub fn sensitivity(&self, max_value: f64, n: usize) -> f64 {
+        match self {
+            Statistic::Count => 1.0,
+            Statistic::Mean => max_value / n.max(1) as f64,
+            Statistic::Variance => max_value * max_value / n.max(1) as f64,
+        }
+    }
+
+    pub fn evaluate(&self, values: &[f64], n: usize) -> f64 {
+        let mean = values.iter().sum::<f64>() / n.max(1) as f64;
+        match self {
+            Statistic::Count => values.len() as f64,
+            Statistic::Mean => mean,
+            Statistic::Variance => values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / n.max(1) as f64,
+        }
+    }
+}
+
+/// Statistics of one field published together under one epsilon. By basic composition
+/// their epsilons add up, so each of the requested statistics gets an even share.
+#[derive(Clone, Copy, PartialEq)]
+pub struct MultiStatQuery {
+    pub count: bool,
+    pub mean: bool,
+    pub variance: bool,
+}
+
+impl fmt::Display for MultiStatQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = self.statistics().iter().map(|statistic| statistic.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", names.join(" + "))
+    }
+}
+
+impl MultiStatQuery {
+    pub fn statistics(&self) -> Vec<Statistic> {
+        [(self.count, Statistic::Count), (self.mean, Statistic::Mean), (self.variance, Statistic::Variance)]
+            .iter()
+            .filter(|&&(requested, _)| requested)
+            .map(|&(_, statistic)| statistic)
+            .collect()
+    }
+
+    /// Share of `epsilon` that each requested statistic gets.
+    pub fn epsilon_per_statistic(&self, epsilon: f64) -> f64 {
+        epsilon / self.statistics().len().max(1) as f64
+    }
+
+    /// Cycles through every non-empty combination of the three statistics.
+    pub fn next(&self) -> MultiStatQuery {
+        let bits = self.count as u8 | (self.mean as u8) << 1 | (self.variance as u8) << 2;
+        let next = bits % 7 + 1;
+        MultiStatQuery { count: next & 1 != 0, mean: next & 2 != 0, variance: next & 4 != 0 }
+    }
+}
@@ -0,0 +1,60 @@
+//! Johnson-Lindenstrauss random projections: a `k × d` matrix `P` of independent
+//! `N(0, 1/k)` entries keeps `|P x|` within a factor `1 ± O(sqrt(ln(1/β) / k))` of `|x|`.
+//! Noise added to `P x` and mapped back with `Pᵀ` is private for the L2 sensitivity of `P`,
+//! the largest column norm, which is close to one. That noise comes back with a total
+//! variance of `σ² Σ |P e_j|² ≈ d σ²`, no less than noise added to `x` directly, and
+//! `Pᵀ P x` misses `x` by about `|x| sqrt(d / k)` on top: for histograms the projection
+//! only costs accuracy.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+
+/// `target_dim` rows of `dim` independent `N(0, 1 / target_dim)` entries, the same for the same `seed`.
+pub fn projection_matrix(dim: usize, target_dim: usize, seed: u64) -> Vec<Vec<f64>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let normal = Normal::new(0.0, 1.0 / (target_dim.max(1) as f64).sqrt()).unwrap();
+    (0..target_dim)
+        .map(|_| (0..dim).map(|_| normal.sample(&mut rng)).collect())
+        .collect()
+}
+
+/// `P data` for the `projection_matrix` of `seed`.
+pub fn random_projection(data: &[f64], target_dim: usize, seed: u64) -> Vec<f64> {
+    projection_matrix(data.len(), target_dim, seed).iter()
+        .map(|row| row.iter().zip(data).map(|(p, x)| p * x).sum())
+        .collect()
+}
+
+/// `Pᵀ projected`, an unbiased estimate of the original data since `E[Pᵀ P] = I`.
+pub fn project_back(projected: &[f64], projection_matrix: &[Vec<f64>]) -> Vec<f64> {
+    let dim = projection_matrix.first().map_or(0, |row| row.len());
+    (0..dim)
+        .map(|j| projection_matrix.iter().zip(projected).map(|(row, y)| row[j] * y).sum())
+        .collect()
+}
+
+/// L2 sensitivity of `P x` when one entry of `x` moves by one: the largest column norm.
+pub fn projection_sensitivity(projection_matrix: &[Vec<f64>]) -> f64 {
+    let dim = projection_matrix.first().map_or(0, |row| row.len());
+    (0..dim)
+        .map(|j| projection_matrix.iter().map(|row| row[j] * row[j]).sum::<f64>().sqrt())
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projection_preserves_norms_and_transposes_back() {
+        let data = (0..20).map(|i| i as f64).collect::<Vec<_>>();
+        let norm = data.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let projected = random_projection(&data, 2000, 3);
+        let projected_norm = projected.iter().map(|y| y * y).sum::<f64>().sqrt();
+        assert!((projected_norm / norm - 1.0).abs() < 0.1);
+        let matrix = projection_matrix(20, 2000, 3);
+        assert!((projection_sensitivity(&matrix) - 1.0).abs() < 0.15);
+        assert_eq!(project_back(&[1.0, 2.0], &[vec![1.0, 0.0, 3.0], vec![0.0, 1.0, 1.0]]), vec![1.0, 2.0, 5.0]);
+    }
+}
@@ -0,0 +1,21 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::noiser::NoiseType;
+
+/// One noised histogram as it left the tool, with everything needed to account for it later.
+#[derive(Clone)]
+pub struct DpRelease {
+    pub timestamp: SystemTime,
+    pub field: String,
+    pub noise_type: NoiseType,
+    pub epsilon: f64,
+    pub noised_counts: Vec<u64>,
+}
+
+impl DpRelease {
+    /// Time of day of the release as `HH:MM:SS` in UTC.
+    pub fn time_of_day(&self) -> String {
+        let seconds = self.timestamp.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs()) % 86400;
+        format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+}
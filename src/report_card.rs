@@ -0,0 +1,69 @@
+//! Letter grades that summarize where the current configuration sits on the privacy/utility
+//! trade-off, for readers who do not want to interpret epsilon and the MSE themselves.
+
+/// Upper bounds (exclusive) on epsilon for each privacy grade; anything above gets a "C",
+/// no privacy at all (infinite epsilon) an "F".
+const PRIVACY_GRADES: [(f64, &str); 3] = [(0.1, "A+"), (1.0, "A"), (10.0, "B")];
+/// Upper bounds (exclusive) on the per-bucket MSE for each utility grade; anything above
+/// gets a "D".
+const UTILITY_GRADES: [(f64, &str); 4] = [(1.0, "A+"), (10.0, "A"), (100.0, "B"), (1000.0, "C")];
+
+pub struct ReportCard {
+    pub privacy_grade: &'static str,
+    pub utility_grade: &'static str,
+    pub summary: String,
+}
+
+pub fn privacy_grade(epsilon: f64) -> &'static str {
+    if epsilon.is_infinite() {
+        return "F";
+    }
+    PRIVACY_GRADES.iter()
+        .find(|&&(bound, _)| epsilon < bound)
+        .map_or("C", |&(_, grade)| grade)
+}
+
+pub fn utility_grade(mse: f64) -> &'static str {
+    UTILITY_GRADES.iter()
+        .find(|&&(bound, _)| mse < bound)
+        .map_or("D", |&(_, grade)| grade)
+}
+
+fn privacy_adjective(grade: &str) -> &'static str {
+    match grade {
+        "A+" => "Very strong",
+        "A" => "Strong",
+        "B" => "Moderate",
+        "C" => "Weak",
+        _ => "No",
+    }
+}
+
+fn utility_adjective(grade: &str) -> &'static str {
+    match grade {
+        "A+" => "excellent",
+        "A" => "high",
+        "B" => "moderate",
+        "C" => "low",
+        _ => "poor",
+    }
+}
+
+impl ReportCard {
+    /// Grades a release with privacy loss `epsilon` and per-bucket `mse` made at error bound
+    /// `accuracy`. When one side is weak the summary suggests an accuracy that trades some
+    /// of the other side for it: a smaller bound means less noise.
+    pub fn new(epsilon: f64, mse: f64, accuracy: usize) -> ReportCard {
+        let privacy_grade = privacy_grade(epsilon);
+        let utility_grade = utility_grade(mse);
+        let mut summary = format!("{} privacy (ε = {:.2}), {} utility (MSE = {:.0}).",
+                                  privacy_adjective(privacy_grade), epsilon,
+                                  utility_adjective(utility_grade), mse);
+        if matches!(utility_grade, "C" | "D") && accuracy > 1 {
+            summary.push_str(&format!(" Consider accuracy = {} for better utility.", accuracy / 2));
+        } else if matches!(privacy_grade, "C" | "F") {
+            summary.push_str(&format!(" Consider accuracy = {} for stronger privacy.", (accuracy * 2).max(1)));
+        }
+        ReportCard { privacy_grade, utility_grade, summary }
+    }
+}
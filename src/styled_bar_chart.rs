@@ -0,0 +1,117 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::widgets::{BarChart, Block, Widget};
+
+const BAR_GAP: u16 = 1;
+
+/// A `BarChart` whose bars can be styled one by one, which tui's own chart does not
+/// support. The chart is rendered as usual and the cells of every bar with a style in
+/// `bar_styles` are restyled afterwards.
+pub struct StyledBarChart<'a> {
+    block: Block<'a>,
+    data: &'a [(&'a str, u64)],
+    bar_width: u16,
+    bar_style: Style,
+    value_style: Style,
+    bar_styles: Vec<Option<Style>>,
+    error_bars: Vec<(u64, u64)>,
+    error_bar_style: Style,
+}
+
+impl<'a> StyledBarChart<'a> {
+    pub fn new(data: &'a [(&'a str, u64)]) -> StyledBarChart<'a> {
+        StyledBarChart {
+            block: Block::default(),
+            data,
+            bar_width: 1,
+            bar_style: Style::default(),
+            value_style: Style::default(),
+            bar_styles: Vec::new(),
+            error_bars: Vec::new(),
+            error_bar_style: Style::default(),
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> StyledBarChart<'a> {
+        self.block = block;
+        self
+    }
+
+    pub fn bar_width(mut self, width: u16) -> StyledBarChart<'a> {
+        self.bar_width = width;
+        self
+    }
+
+    pub fn bar_style(mut self, style: Style) -> StyledBarChart<'a> {
+        self.bar_style = style;
+        self
+    }
+
+    pub fn value_style(mut self, style: Style) -> StyledBarChart<'a> {
+        self.value_style = style;
+        self
+    }
+
+    /// Per-bar overrides, by position in `data`; `None` keeps the chart's default style.
+    pub fn bar_styles(mut self, styles: Vec<Option<Style>>) -> StyledBarChart<'a> {
+        self.bar_styles = styles;
+        self
+    }
+
+    /// A `(low, high)` interval per bar, drawn as a line along its right edge. The chart
+    /// is scaled so that the highest end still fits.
+    pub fn error_bars(mut self, intervals: Vec<(u64, u64)>, style: Style) -> StyledBarChart<'a> {
+        self.error_bars = intervals;
+        self.error_bar_style = style;
+        self
+    }
+}
+
+impl<'a> Widget for StyledBarChart<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let chart_area = self.block.inner(area);
+        self.block.render(area, buf);
+        let max = self.data.iter().map(|&(_, value)| value)
+            .chain(self.error_bars.iter().map(|&(_, high)| high))
+            .max()
+            .unwrap_or_default();
+        BarChart::default()
+            .data(self.data)
+            .max(max)
+            .bar_width(self.bar_width)
+            .bar_gap(BAR_GAP)
+            .bar_style(self.bar_style)
+            .value_style(self.value_style)
+            .render(chart_area, buf);
+
+        for (i, style) in self.bar_styles.iter().enumerate() {
+            let style = match style {
+                Some(style) => *style,
+                None => continue,
+            };
+            let left = chart_area.left() + i as u16 * (self.bar_width + BAR_GAP);
+            for x in left..(left + self.bar_width).min(chart_area.right()) {
+                for y in chart_area.top()..chart_area.bottom() {
+                    buf.get_mut(x, y).set_style(style);
+                }
+            }
+        }
+
+        // rows of bars count up from the one above the labels, as BarChart draws them
+        if chart_area.height < 2 {
+            return;
+        }
+        let rows = chart_area.height as u64 - 1;
+        for (i, &(low, high)) in self.error_bars.iter().enumerate().take(self.data.len()) {
+            let x = chart_area.left() + i as u16 * (self.bar_width + BAR_GAP) + self.bar_width - 1;
+            if x >= chart_area.right() {
+                break;
+            }
+            let row = |value: u64| (value * rows / max.max(1)).min(rows - 1) as u16;
+            for k in row(low)..=row(high) {
+                buf.get_mut(x, chart_area.bottom() - 2 - k).set_symbol("│").set_style(self.error_bar_style);
+            }
+        }
+    }
+}